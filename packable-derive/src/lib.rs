@@ -0,0 +1,1846 @@
+//! `#[derive(Packable)]`, `#[derive(Columnar)]`, `#[derive(Schema)]`,
+//! `#[derive(Migrate)]`, `#[derive(LayoutInfo)]`, `#[derive(DefaultPacked)]`,
+//! `#[derive(PackedSize)]`, `#[derive(View)]`, `#[derive(Sample)]` and
+//! `vector_tests!`, re-exported by `packable` behind its `derive` feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse::{Parse, ParseStream}, parse_macro_input, parse_quote, punctuated::Punctuated, Attribute, Data, DataEnum, DeriveInput, Error, Expr, Fields, GenericArgument, GenericParam, Ident, Index, Lit, LitInt, LitStr, Member, Meta, NestedMeta, Path, PathArguments, Token, Type};
+
+/// Pack the fields of a struct one after the other, in declaration order.
+///
+/// A field marked `#[packable(flatten)]` contributes its own fields in its place,
+/// e.g. a header struct shared by many messages. Nested structs are packed with no
+/// framing, so this only changes `LayoutInfo`, which lists the inner fields.
+///
+/// A field marked `#[packable(endian = "big")]`, `"little"` or `"native"` is
+/// always packed in that byte order, whatever the caller asks for.
+///
+/// A struct marked `#[packable(presence_bitmap)]` packs one bit per `Option` field
+/// in a leading bitmap, the first `Option` in the low bit of the first byte, and
+/// then only the values that are present, rather than a presence byte before each.
+/// `LayoutInfo` lists the bitmap as a field named `presence`.
+///
+/// A field marked `#[packable(as = "u16")]`, e.g. a `usize` length, is packed as
+/// that type through `PackAs`: unpacking fails with `OutOfRange` on a value the
+/// field cannot hold, and packing panics on one the wire type cannot. Adding
+/// `scale = 0.1, offset = -40.0` makes the wire type a raw count, as sensor
+/// datasheets specify registers: a float field holds `raw * scale + offset`, and
+/// packs the nearest raw count, saturating at the bounds of the wire type, or the
+/// one `rounding = "floor"`, `"ceil"` or `"truncate"` picks.
+///
+/// A field marked `#[packable(compute = "self.body.len() as u16")]` is packed as
+/// that expression, evaluated with `self` in scope, instead of its stored value, so
+/// a length or count cannot go stale. On unpack it is stored, and then checked
+/// against the expression over the other fields, failing with `InvalidValue`
+/// unless the field is also marked `verify = false`.
+///
+/// A length field marked `#[packable(size_of = "body")]` is packed as the size in
+/// bytes of `body`, a later field, panicking if it does not fit. On unpack `body`
+/// is read from exactly that many bytes, so a greedy type such as `UntilEnd` stops
+/// at its end, and bytes it leaves over fail with `TrailingBytes`. Adding
+/// `skip_extra` drops them instead, e.g. unknown extensions from a newer peer;
+/// unlike with `Region`, they are not packed again.
+///
+/// A struct marked `#[packable(accessors)]` also gets a `<field>_from(data, endian)`
+/// function per field, e.g. `Header::len_from(&packet, false)`, that decodes just
+/// that field from a packed record, for hot paths that need one or two fields per
+/// packet. Unlike `View`, fields can have any size: those before the one asked for
+/// are decoded and dropped to find where it starts, so an accessor after a `Vec` or
+/// `String` pays for decoding it.
+///
+/// A struct marked `#[packable(size = 16)]` fails to compile unless its fields, all
+/// `PackedSize`, add up to 16 bytes, and a field marked `#[packable(at = 4)]` unless
+/// it starts at byte 4, to catch mistakes in transcribing a spec's field table.
+///
+/// With the `fuzz` feature, every derived type that is also `Default` gets
+/// `Name::fuzz_unpack(data)`, the body of a cargo-fuzz target for it; see
+/// `packable::fuzz_unpack`.
+///
+/// Marking a field `#[packable(split_after)]` also generates `<Name>HeaderPart`,
+/// holding the fields up to and including it, and `<Name>BodyPart`, holding the
+/// rest, both `Packable`, with `from_parts` and `into_parts` on the struct. The
+/// header can then be decoded on its own, e.g. to learn how long the body is, and
+/// the body once the rest of the bytes arrive.
+///
+/// Enums of unit variants pack their discriminant as the integer named by their
+/// `#[repr(..)]`; unknown values fail to unpack with `InvalidDiscriminant`. An enum
+/// marked `#[packable(tag = "u32")]` packs it as that type instead, through
+/// `PackAs`, so the same opcodes can go out as a `u16` in one format and a `u32` or
+/// a `Vlq` in another. An integer tag too narrow for a discriminant is a compile
+/// error; a custom tag panics when packing a discriminant it cannot hold.
+///
+/// Values a spec reserves are named by `#[packable(reserved = "0x80..=0xff")]`,
+/// as a range or a single value, and can be repeated. They decode into the one
+/// variant marked `#[packable(reserved)]`, which holds the raw value, e.g.
+/// `Reserved(u8)`, and packs it back as it was; packing a value outside the ranges,
+/// or one a named variant already uses, panics. Without such a variant they fail
+/// with `InvalidDiscriminant`, like unknown values.
+#[proc_macro_derive(Packable, attributes(packable))]
+pub fn derive_packable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand(mut input: DeriveInput) -> Result<TokenStream2, Error> {
+    let options = container_options(&input.attrs)?;
+    let krate = options.krate;
+    let (fields, (endians, unused), presence) = match &input.data {
+        Data::Struct(_) if options.tag.is_some() || !options.reserved.is_empty() => {
+            return Err(Error::new_spanned(&input.ident, "only enums have a tag or reserved values"))
+        }
+        Data::Enum(_) if options.accessors || options.size.is_some() => {
+            return Err(Error::new_spanned(&input.ident, "only structs have accessors or a declared size"))
+        }
+        Data::Struct(data) => (field_accessors(&data.fields), field_endians(&data.fields)?, presence_bits(&input, &data.fields)?),
+        Data::Enum(data) => return expand_enum(&input, data),
+        Data::Union(_) => return Err(Error::new_spanned(&input.ident, "Packable cannot be derived for unions")),
+    };
+    for param in &mut input.generics.params {
+        if let GenericParam::Type(param) = param {
+            param.bounds.push(parse_quote!(#krate::Packable));
+        }
+    }
+    if let Some(presence) = &presence {
+        let inners = presence.bits.iter().flatten().map(|(_, _, inner)| inner);
+        input.generics.make_where_clause().predicates.extend(inners.map(|inner| -> syn::WherePredicate {
+            parse_quote!(#inner: #krate::Packable + ::core::default::Default)
+        }));
+    }
+    let wires = match &input.data {
+        Data::Struct(data) => data.fields.iter().map(|field| {
+            let mut options = field_options(&field.attrs)?;
+            let ty = &field.ty;
+            let compute = match (options.compute.take(), &options.size_of) {
+                (Some(expr), None) => Some((quote!({ let value: #ty = #expr; value }), options.verify)),
+                (None, Some(bounded)) => Some((
+                    quote! {{
+                        let size = #krate::Packable::size(&self.#bounded);
+                        let value: #ty = match ::core::convert::TryFrom::try_from(size) {
+                            Ok(value) => value,
+                            Err(_) => panic!("{} of {} bytes does not fit a {}", stringify!(#bounded), size, stringify!(#ty)),
+                        };
+                        value
+                    }},
+                    false,
+                )),
+                (Some(_), Some(_)) => return Err(Error::new_spanned(field, "a field cannot be both computed and a size_of")),
+                (None, None) => None,
+            };
+            Ok((compute, options))
+        }).collect::<Result<Vec<_>, Error>>()?,
+        _ => Vec::new(),
+    };
+    let types: Vec<&Type> = match &input.data {
+        Data::Struct(data) => data.fields.iter().map(|field| &field.ty).collect(),
+        _ => Vec::new(),
+    };
+    // The length field each field marked by a `size_of` is read within.
+    let mut bounds: Vec<Option<(&TokenStream2, bool)>> = vec![None; fields.len()];
+    for (index, (_, options)) in wires.iter().enumerate() {
+        let Some(bounded) = &options.size_of else {
+            continue
+        };
+        let Some(target) = fields.iter().position(|field| field.to_string() == quote!(#bounded).to_string()) else {
+            return Err(Error::new_spanned(bounded, "size_of names no field of this struct"))
+        };
+        let presence_bit = presence.as_ref().is_some_and(|presence| presence.bits[target].is_some());
+        if target <= index || bounds[target].is_some() || wires[target].0.is_some() || wires[target].1.pack_as.is_some() || presence_bit {
+            return Err(Error::new_spanned(bounded, "size_of must name one later field, packed as it is and bounded by no other length"));
+        }
+        bounds[target] = Some((&fields[index], options.skip_extra));
+    }
+    let mut pack = Vec::new();
+    let mut write = Vec::new();
+    let mut sizes = Vec::new();
+    let mut reads = Vec::new();
+    let mut checks = Vec::new();
+    for (index, (field, endian)) in fields.iter().zip(&endians).enumerate() {
+        let (compute, options) = &wires[index];
+        let pack_as = &options.pack_as;
+        match presence.as_ref().and_then(|presence| presence.bits[index].as_ref()) {
+            Some(_) if compute.is_some() || pack_as.is_some() => {
+                return Err(Error::new_spanned(&input.ident, "presence_bitmap Option fields cannot be computed or packed `as` another type"));
+            }
+            None if compute.is_some() || pack_as.is_some() => {
+                let ty = types[index];
+                let value = compute.as_ref().map_or_else(|| quote!(self.#field), |(value, _)| value.clone());
+                let (value, read) = match pack_as {
+                    Some(wire) => {
+                        let value = wire_value(&krate, options, wire, ty, &value);
+                        (
+                            quote! {
+                                (match #value {
+                                    Ok(wire) => wire,
+                                    Err(error) => panic!("{}: {}", stringify!(#field), error.data),
+                                })
+                            },
+                            read_wire(&krate, options, wire, ty, field, endian),
+                        )
+                    }
+                    None => (value, quote!(#krate::PackableExt::unpack_field(&mut self.#field, &mut source, #endian)?;)),
+                };
+                pack.push(quote!(#krate::Packable::pack_into(&#value, out, #endian);));
+                write.push(quote!(#krate::Packable::pack_to_writer(&#value, writer, #endian)?;));
+                sizes.push(quote!(#krate::Packable::size(&#value)));
+                reads.push(read);
+                if let Some((value, true)) = compute {
+                    checks.push(quote! {
+                        if self.#field != #value {
+                            return Err(#krate::PackableError {
+                                error_kind: #krate::ErrorKind::InvalidValue,
+                                data: format!("{} does not match the value computed from the other fields", stringify!(#field)),
+                                direction: Some(#krate::Direction::Unpack),
+                            })
+                        }
+                    });
+                }
+            }
+            Some((byte, mask, _)) => {
+                pack.push(quote! {
+                    if let ::core::option::Option::Some(value) = &self.#field {
+                        #krate::Packable::pack_into(value, out, #endian);
+                    }
+                });
+                write.push(quote! {
+                    if let ::core::option::Option::Some(value) = &self.#field {
+                        #krate::Packable::pack_to_writer(value, writer, #endian)?;
+                    }
+                });
+                sizes.push(quote!(self.#field.as_ref().map_or(0, #krate::Packable::size)));
+                reads.push(quote! {
+                    if presence[#byte] & #mask != 0 {
+                        let value = self.#field.get_or_insert_with(::core::default::Default::default);
+                        #krate::PackableExt::unpack_field(value, &mut source, #endian)?;
+                    } else {
+                        self.#field = ::core::option::Option::None;
+                    }
+                });
+            }
+            None => {
+                pack.push(quote!(#krate::Packable::pack_into(&self.#field, out, #endian);));
+                write.push(quote!(#krate::Packable::pack_to_writer(&self.#field, writer, #endian)?;));
+                sizes.push(quote!(#krate::Packable::size(&self.#field)));
+                reads.push(match bounds[index] {
+                    Some((len, skip_extra)) => quote! {
+                        let len: usize = match ::core::convert::TryInto::try_into(self.#len) {
+                            Ok(len) => len,
+                            Err(_) => return Err(#krate::PackableError {
+                                error_kind: #krate::ErrorKind::OutOfRange,
+                                data: format!("{} does not fit in usize", stringify!(#len)),
+                                direction: Some(#krate::Direction::Unpack),
+                            }),
+                        };
+                        if source.len() < len {
+                            return Err(#krate::PackableError {
+                                error_kind: #krate::ErrorKind::BufferLengthError,
+                                data: format!("{} needs {} bytes and {} are left", stringify!(#field), len, source.len()),
+                                direction: Some(#krate::Direction::Unpack),
+                            })
+                        }
+                        let (mut region, rest) = source.split_at(len);
+                        #krate::PackableExt::unpack_field(&mut self.#field, &mut region, #endian)?;
+                        if !#skip_extra && !region.is_empty() {
+                            return Err(#krate::PackableError {
+                                error_kind: #krate::ErrorKind::TrailingBytes,
+                                data: format!("{} bytes left in the {} bytes of {}", region.len(), len, stringify!(#field)),
+                                direction: Some(#krate::Direction::Unpack),
+                            })
+                        }
+                        source = rest;
+                    },
+                    None => quote!(#krate::PackableExt::unpack_field(&mut self.#field, &mut source, #endian)?;),
+                });
+            }
+        }
+    }
+    if let Some(presence) = &presence {
+        let len = presence.len;
+        let sets = presence.bits.iter().zip(&fields).filter_map(|(bit, field)| {
+            bit.as_ref().map(|(byte, mask, _)| quote! {
+                if self.#field.is_some() {
+                    presence[#byte] |= #mask;
+                }
+            })
+        });
+        let set = quote! {
+            let mut presence = [0u8; #len];
+            #( #sets )*
+        };
+        pack.insert(0, quote! {
+            #set
+            out.extend_from_slice(&presence);
+        });
+        write.insert(0, quote! {
+            #set
+            writer.write_all(&presence)?;
+        });
+        sizes.insert(0, quote!(#len));
+        reads.insert(0, quote! {
+            let mut presence = [0u8; #len];
+            #krate::PackableExt::unpack_field(&mut presence, &mut source, false)?;
+        });
+    }
+    let pack = match fields.is_empty() {
+        true => quote!(let _ = (out, little_endian);),
+        false => quote! {
+            #unused
+            #( #pack )*
+        },
+    };
+    let write = match fields.is_empty() {
+        true => quote!(let _ = (writer, little_endian);),
+        false => quote! {
+            #unused
+            #( #write )*
+        },
+    };
+    let unpack = match fields.is_empty() {
+        true => quote! {
+            let _ = (buf, little_endian);
+            Ok(0)
+        },
+        false => quote! {
+            #unused
+            let mut source = buf;
+            #( #reads )*
+            #( #checks )*
+            Ok(buf.len() - source.len())
+        },
+    };
+    let split = expand_split(&input, &krate)?;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let accessors = match options.accessors {
+        true => expand_accessors(&input, &krate, &fields, &endians, &types, &wires)?,
+        false => quote!(),
+    };
+    let fuzz = expand_fuzz(&input, &krate);
+    let layout = expand_layout_check(&input, &krate, options.size.as_ref(), &types, &wires)?;
+    Ok(quote! {
+        #layout
+
+        #split
+
+        #accessors
+
+        #fuzz
+
+        impl #impl_generics #krate::Packable for #name #ty_generics #where_clause {
+            fn pack(&self, little_endian: bool) -> ::std::vec::Vec<u8> {
+                let mut data = ::std::vec::Vec::with_capacity(#krate::Packable::size(self));
+                #krate::Packable::pack_into(self, &mut data, little_endian);
+                data
+            }
+
+            fn pack_into(&self, out: &mut ::std::vec::Vec<u8>, little_endian: bool) {
+                #pack
+            }
+
+            fn pack_to_writer<__PackableW: ::std::io::Write + ?Sized>(&self, writer: &mut __PackableW, little_endian: bool) -> ::std::io::Result<()> {
+                #write
+                Ok(())
+            }
+
+            fn size(&self) -> usize {
+                0 #( + #sizes )*
+            }
+
+            fn unpack(&mut self, data: &mut ::std::vec::Vec<u8>, little_endian: bool) -> ::std::result::Result<(), #krate::PackableError> {
+                #krate::Packable::unpack_from(self, data, little_endian)?;
+                Ok(())
+            }
+
+            fn unpack_from(&mut self, buf: &[u8], little_endian: bool) -> ::std::result::Result<usize, #krate::PackableError> {
+                #unpack
+            }
+        }
+    })
+}
+
+/// For a struct marked `#[packable(size = N)]`, or with fields marked
+/// `#[packable(at = N)]`, a compile-time check that the fields, as their
+/// `PackedSize`, add up to that size and start at those offsets.
+fn expand_layout_check(input: &DeriveInput, krate: &Path, size: Option<&LitInt>, types: &[&Type], wires: &[(Option<(TokenStream2, bool)>, FieldOptions)]) -> Result<TokenStream2, Error> {
+    if size.is_none() && wires.iter().all(|(_, options)| options.at.is_none()) {
+        return Ok(quote!())
+    }
+    if !input.generics.params.is_empty() || container_options(&input.attrs)?.presence_bitmap {
+        return Err(Error::new_spanned(&input.ident, "`size` and `at` need a fixed layout, so no generics or presence_bitmap"));
+    }
+    let name = &input.ident;
+    let mut offset = quote!(0);
+    let mut checks = Vec::new();
+    let fields = field_accessors(match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => return Ok(quote!()),
+    });
+    for ((field, ty), (_, options)) in fields.iter().zip(types).zip(wires) {
+        if let Some(at) = &options.at {
+            let message = format!("{}.{} is not at byte {}", name, field, at);
+            checks.push(quote!(assert!(#offset == #at, #message);));
+        }
+        let ty = options.pack_as.as_ref().unwrap_or(ty);
+        offset = quote!(#offset + <#ty as #krate::PackedSize>::SIZE);
+    }
+    if let Some(size) = size {
+        let message = format!("{} does not pack to #[packable(size = {})]", name, size);
+        checks.push(quote!(assert!(#offset == #size, #message);));
+    }
+    Ok(quote! {
+        const _: () = {
+            #( #checks )*
+        };
+    })
+}
+
+/// With the `fuzz` feature, an inherent `fuzz_unpack` running `packable::fuzz_unpack`
+/// on the type, for a cargo-fuzz target per message type.
+#[cfg(feature = "fuzz")]
+fn expand_fuzz(input: &DeriveInput, krate: &Path) -> TokenStream2 {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    // The higher-ranked bound is only checked where `fuzz_unpack` is called, so
+    // types without `Default` still derive, they just have no fuzz entry point.
+    quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Unpack `data` with both byte orders and check that whatever decodes
+            /// packs back and round-trips stably, e.g. from `fuzz_target!`.
+            pub fn fuzz_unpack(data: &[u8])
+            where
+                for<'__packable> Self: ::core::default::Default,
+            {
+                #krate::fuzz_unpack::<Self>(data)
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "fuzz"))]
+fn expand_fuzz(_input: &DeriveInput, _krate: &Path) -> TokenStream2 {
+    quote!()
+}
+
+/// For a struct marked `#[packable(accessors)]`, a `<field>_from` function per
+/// named field that decodes just that field from a packed record, stepping over
+/// the fields before it.
+fn expand_accessors(input: &DeriveInput, krate: &Path, fields: &[TokenStream2], endians: &[TokenStream2], types: &[&Type], wires: &[(Option<(TokenStream2, bool)>, FieldOptions)]) -> Result<TokenStream2, Error> {
+    let Data::Struct(data) = &input.data else {
+        return Ok(quote!())
+    };
+    if !matches!(data.fields, Fields::Named(_)) || container_options(&input.attrs)?.presence_bitmap {
+        return Err(Error::new_spanned(&input.ident, "accessors need named fields and no presence_bitmap"));
+    }
+    for (field, (_, options)) in data.fields.iter().zip(wires) {
+        if options.pack_as.is_some() || options.size_of.is_some() {
+            return Err(Error::new_spanned(field, "accessors read fields as their own type, so none can be packed `as` another type or a size_of"));
+        }
+    }
+    let vis = &input.vis;
+    let getters = data.fields.iter().zip(fields).enumerate().map(|(index, (declared, field))| {
+        let ident = declared.ident.as_ref().expect("named field");
+        let getter = Ident::new(&format!("{}_from", ident), ident.span());
+        let (ty, endian) = (types[index], &endians[index]);
+        let (skipped, skipped_endians) = (&types[..index], &endians[..index]);
+        let doc = format!("Decode `{}` from a packed `{}` at the start of `data`, without the other fields.", field, input.ident);
+        quote! {
+            #[doc = #doc]
+            #vis fn #getter(data: &[u8], endian: impl ::core::convert::Into<#krate::Endian>) -> ::std::result::Result<#ty, #krate::PackableError> {
+                let little_endian = endian.into().is_little();
+                let _ = little_endian;
+                let mut source = data;
+                #(
+                    #krate::PackableExt::unpack_field(&mut <#skipped as ::core::default::Default>::default(), &mut source, #skipped_endians)?;
+                )*
+                let mut value = <#ty as ::core::default::Default>::default();
+                #krate::PackableExt::unpack_field(&mut value, &mut source, #endian)?;
+                Ok(value)
+            }
+        }
+    });
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #name #ty_generics #where_clause {
+            #( #getters )*
+        }
+    })
+}
+
+/// For a struct with a field marked `#[packable(split_after)]`, a `HeaderPart` type
+/// holding the fields up to and including it, a `BodyPart` type holding the rest,
+/// and conversions between the struct and the pair.
+fn expand_split(input: &DeriveInput, krate: &Path) -> Result<TokenStream2, Error> {
+    let Data::Struct(data) = &input.data else {
+        return Ok(quote!())
+    };
+    let mut split = None;
+    for (index, field) in data.fields.iter().enumerate() {
+        if field_options(&field.attrs)?.split_after {
+            if split.is_some() {
+                return Err(Error::new_spanned(field, "only one field can be marked split_after"));
+            }
+            split = Some(index + 1);
+        }
+    }
+    let Some(split) = split else {
+        return Ok(quote!())
+    };
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(&input.generics, "split_after cannot be used on generic types"));
+    }
+    if container_options(&input.attrs)?.presence_bitmap {
+        return Err(Error::new_spanned(&input.ident, "split_after cannot be used with presence_bitmap"));
+    }
+    let accessors = field_accessors(&data.fields);
+    for (index, field) in data.fields.iter().enumerate() {
+        if let Some(bounded) = field_options(&field.attrs)?.size_of {
+            let target = accessors.iter().position(|accessor| accessor.to_string() == quote!(#bounded).to_string());
+            if target.is_some_and(|target| (target < split) != (index < split)) {
+                return Err(Error::new_spanned(bounded, "a size_of field and the field it bounds must be on the same side of split_after"));
+            }
+        }
+    }
+    let name = &input.ident;
+    let vis = &input.vis;
+    let header = Ident::new(&format!("{}HeaderPart", name), name.span());
+    let body = Ident::new(&format!("{}BodyPart", name), name.span());
+    let krate_name = quote!(#krate).to_string();
+    let mut declarations = Vec::new();
+    for field in &data.fields {
+        let mut attrs = Vec::new();
+        for attr in &field.attrs {
+            if attr.path.is_ident("doc") {
+                attrs.push(quote!(#attr));
+            } else if attr.path.is_ident("packable") {
+                let Meta::List(list) = attr.parse_meta()? else {
+                    return Err(Error::new_spanned(attr, "expected #[packable(..)]"))
+                };
+                let kept: Vec<&NestedMeta> = list.nested.iter().filter(|nested| match nested {
+                    NestedMeta::Meta(Meta::Path(path)) => !path.is_ident("split_after"),
+                    NestedMeta::Meta(Meta::NameValue(value)) => !["compute", "verify", "at"].iter().any(|name| value.path.is_ident(name)),
+                    _ => true,
+                }).collect();
+                if !kept.is_empty() {
+                    attrs.push(quote!(#[packable( #( #kept ),* )]));
+                }
+            }
+        }
+        let (field_vis, ty) = (&field.vis, &field.ty);
+        declarations.push(match &field.ident {
+            Some(ident) => quote!(#( #attrs )* #field_vis #ident: #ty),
+            None => quote!(#( #attrs )* #field_vis #ty),
+        });
+    }
+    let (header_fields, body_fields) = declarations.split_at(split);
+    let types: Vec<&Type> = data.fields.iter().map(|field| &field.ty).collect();
+    let (header_types, body_types) = types.split_at(split);
+    let (header_accessors, body_accessors) = accessors.split_at(split);
+    let (header_indices, body_indices): (Vec<Index>, Vec<Index>) = (
+        (0..split).map(Index::from).collect(),
+        (0..data.fields.len() - split).map(Index::from).collect(),
+    );
+    let mut computed = Vec::new();
+    for (field, accessor) in data.fields.iter().zip(&accessors) {
+        if let Some(expr) = field_options(&field.attrs)?.compute {
+            let ty = &field.ty;
+            computed.push(quote!(self.#accessor = { let value: #ty = #expr; value };));
+        }
+    }
+    let receiver = match computed.is_empty() {
+        true => quote!(self),
+        false => quote!(mut self),
+    };
+    let header_doc = format!("The fields of `{}` up to the split, to decode before the rest arrives.", name);
+    let body_doc = format!("The fields of `{}` after the split.", name);
+    let (header_struct, body_struct, join, parts, defaults) = match &data.fields {
+        Fields::Named(_) => (
+            quote!(#vis struct #header { #( #header_fields, )* }),
+            quote!(#vis struct #body { #( #body_fields, )* }),
+            quote!(#name { #( #header_accessors: header.#header_accessors, )* #( #body_accessors: body.#body_accessors, )* }),
+            quote!((#header { #( #header_accessors: self.#header_accessors, )* }, #body { #( #body_accessors: self.#body_accessors, )* })),
+            (
+                quote!(#header { #( #header_accessors: ::core::default::Default::default(), )* }),
+                quote!(#body { #( #body_accessors: ::core::default::Default::default(), )* }),
+            ),
+        ),
+        _ => (
+            quote!(#vis struct #header( #( #header_fields, )* );),
+            quote!(#vis struct #body( #( #body_fields, )* );),
+            quote!(#name( #( header.#header_indices, )* #( body.#body_indices, )* )),
+            quote!((#header( #( self.#header_accessors, )* ), #body( #( self.#body_accessors, )* ))),
+            (
+                quote!(#header( #( <#header_types as ::core::default::Default>::default(), )* )),
+                quote!(#body( #( <#body_types as ::core::default::Default>::default(), )* )),
+            ),
+        ),
+    };
+    let (header_default, body_default) = defaults;
+    Ok(quote! {
+        #[doc = #header_doc]
+        #[derive(#krate::Packable)]
+        #[packable(crate = #krate_name)]
+        #[allow(dead_code)]
+        #header_struct
+
+        #[doc = #body_doc]
+        #[derive(#krate::Packable)]
+        #[packable(crate = #krate_name)]
+        #[allow(dead_code)]
+        #body_struct
+
+        // Usable only when every field of the part has a default, so a field
+        // without one does not stop the struct from being split.
+        impl ::core::default::Default for #header where #( for<'__packable> #header_types: ::core::default::Default, )* {
+            fn default() -> Self {
+                #header_default
+            }
+        }
+
+        impl ::core::default::Default for #body where #( for<'__packable> #body_types: ::core::default::Default, )* {
+            fn default() -> Self {
+                #body_default
+            }
+        }
+
+        #[allow(dead_code)]
+        impl #name {
+            /// Join the two halves decoded one after the other.
+            #vis fn from_parts(header: #header, body: #body) -> Self {
+                #join
+            }
+
+            /// Split into the two halves, which pack to the same bytes as `self`.
+            #vis fn into_parts(#receiver) -> (#header, #body) {
+                #( #computed )*
+                #parts
+            }
+        }
+    })
+}
+
+/// Pack a slice of structs one field at a time: the first field of every record,
+/// then the second, and so on.
+#[proc_macro_derive(Columnar, attributes(packable))]
+pub fn derive_columnar(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_columnar(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand_columnar(mut input: DeriveInput) -> Result<TokenStream2, Error> {
+    let krate = crate_path(&input.attrs)?;
+    let (fields, (endians, unused)) = match &input.data {
+        Data::Struct(data) => (field_accessors(&data.fields), field_endians(&data.fields)?),
+        _ => return Err(Error::new_spanned(&input.ident, "Columnar can only be derived for structs")),
+    };
+    if let Data::Struct(data) = &input.data {
+        for field in &data.fields {
+            let options = field_options(&field.attrs)?;
+            if options.compute.is_some() || options.pack_as.is_some() || options.size_of.is_some() {
+                return Err(Error::new_spanned(field, "Columnar packs fields as they are, so they cannot be computed, a size_of or packed `as` another type"));
+            }
+        }
+    }
+    for param in &mut input.generics.params {
+        if let GenericParam::Type(param) = param {
+            param.bounds.push(parse_quote!(#krate::Packable));
+        }
+    }
+    let unpack = match fields.is_empty() {
+        true => quote!(let _ = (data, little_endian);),
+        false => quote! {
+            #unused
+            let mut source: &[u8] = data;
+            #(
+                for record in &mut records {
+                    #krate::UnpackSource::unpack_into(&mut source, &mut record.#fields, #endians)?;
+                }
+            )*
+        },
+    };
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #krate::Columnar for #name #ty_generics #where_clause {
+            fn pack_columns(records: &[Self], endian: impl ::core::convert::Into<#krate::Endian>) -> ::std::vec::Vec<u8> {
+                let little_endian = endian.into().is_little();
+                #unused
+                let mut data = ::std::vec::Vec::new();
+                #(
+                    for record in records {
+                        data.extend(#krate::Packable::pack(&record.#fields, #endians));
+                    }
+                )*
+                data
+            }
+
+            fn unpack_columns(data: &[u8], count: usize, endian: impl ::core::convert::Into<#krate::Endian>) -> ::std::result::Result<::std::vec::Vec<Self>, #krate::PackableError> {
+                let little_endian = endian.into().is_little();
+                let mut records: ::std::vec::Vec<Self> = (0..count).map(|_| ::std::default::Default::default()).collect();
+                #unpack
+                Ok(records)
+            }
+        }
+    })
+}
+
+/// Set `PackedSize::SIZE` to the sum of the field sizes for a struct, counting a
+/// field marked `as` another type as that type, or the size of the repr for an
+/// enum.
+#[proc_macro_derive(PackedSize, attributes(packable))]
+pub fn derive_packed_size(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_packed_size(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand_packed_size(mut input: DeriveInput) -> Result<TokenStream2, Error> {
+    let krate = crate_path(&input.attrs)?;
+    let size = match &input.data {
+        Data::Struct(data) => {
+            let types = data.fields.iter()
+                .map(|field| Ok(field_options(&field.attrs)?.pack_as.unwrap_or_else(|| field.ty.clone())))
+                .collect::<Result<Vec<Type>, Error>>()?;
+            quote!(0 #( + <#types as #krate::PackedSize>::SIZE )*)
+        }
+        Data::Enum(data) => {
+            let repr = enum_repr(&input)?;
+            enum_variants(data)?;
+            match container_options(&input.attrs)?.tag {
+                Some(tag) => quote!(<#tag as #krate::PackedSize>::SIZE),
+                None => quote!(::core::mem::size_of::<#repr>()),
+            }
+        }
+        Data::Union(_) => return Err(Error::new_spanned(&input.ident, "PackedSize cannot be derived for unions")),
+    };
+    for param in &mut input.generics.params {
+        if let GenericParam::Type(param) = param {
+            param.bounds.push(parse_quote!(#krate::PackedSize));
+        }
+    }
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #krate::PackedSize for #name #ty_generics #where_clause {
+            const SIZE: usize = #size;
+        }
+    })
+}
+
+/// Generate `<Name>View<'a>` over a `&'a [u8]` and `<Name>ViewMut<'a>` over a
+/// `&'a mut [u8]` for a struct whose fields are all `PackedSize`. Each field has a
+/// getter of the same name that decodes it from its fixed offset on every call,
+/// and the mutable view a `set_<field>` that packs a new value in place, so a
+/// packed record can be read or patched without unpacking the whole struct.
+#[proc_macro_derive(View, attributes(packable))]
+pub fn derive_view(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_view(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand_view(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let krate = crate_path(&input.attrs)?;
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) if matches!(data.fields, Fields::Named(_)) => &data.fields,
+        _ => return Err(Error::new_spanned(name, "View can only be derived for structs with named fields")),
+    };
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(&input.generics, "View cannot be derived for generic types"));
+    }
+    let vis = &input.vis;
+    let view = Ident::new(&format!("{}View", name), name.span());
+    let view_mut = Ident::new(&format!("{}ViewMut", name), name.span());
+    let mut offset = quote!(0);
+    let mut getters = Vec::new();
+    let mut setters = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let setter = Ident::new(&format!("set_{}", ident), ident.span());
+        let ty = &field.ty;
+        let options = field_options(&field.attrs)?;
+        if options.pack_as.is_some() {
+            return Err(Error::new_spanned(field, "View reads fields as their own type, so they cannot be packed `as` another type"));
+        }
+        let doc = options.doc.map(|doc| quote!(#[doc = #doc]));
+        let endian = options.endian.unwrap_or_else(|| quote!(self.little_endian));
+        let range = quote!(#offset..#offset + <#ty as #krate::PackedSize>::SIZE);
+        getters.push(quote! {
+            #doc
+            #vis fn #ident(&self) -> ::std::result::Result<#ty, #krate::PackableError> {
+                let mut value = <#ty as ::core::default::Default>::default();
+                #krate::Packable::unpack_from(&mut value, &self.data[#range], #endian)?;
+                Ok(value)
+            }
+        });
+        setters.push(quote! {
+            #vis fn #setter(&mut self, value: &#ty) {
+                let mut window = &mut self.data[#range];
+                if #krate::Packable::pack_to_writer(value, &mut window, #endian).is_err() {
+                    panic!("{} packs to more than its PackedSize", stringify!(#ident));
+                }
+            }
+        });
+        offset = quote!(#offset + <#ty as #krate::PackedSize>::SIZE);
+    }
+    let new = quote! {
+        if data.len() < #offset {
+            return Err(#krate::PackableError {
+                error_kind: #krate::ErrorKind::BufferLengthError,
+                data: format!("except {} bytes and get {}", #offset, data.len()),
+                direction: Some(#krate::Direction::Unpack),
+            })
+        }
+    };
+    let (view_doc, view_mut_doc) = (
+        format!("A packed `{}` read one field at a time, straight from the bytes.", name),
+        format!("A packed `{}` read and written one field at a time, in place.", name),
+    );
+    Ok(quote! {
+        #[doc = #view_doc]
+        #[derive(Debug, Clone, Copy)]
+        #vis struct #view<'a> {
+            data: &'a [u8],
+            little_endian: bool,
+        }
+
+        #[doc = #view_mut_doc]
+        #[derive(Debug)]
+        #vis struct #view_mut<'a> {
+            data: &'a mut [u8],
+            little_endian: bool,
+        }
+
+        #[allow(dead_code)]
+        impl<'a> #view<'a> {
+            /// A view of the record at the start of `data`, which can be longer.
+            #vis fn new(data: &'a [u8], endian: impl ::core::convert::Into<#krate::Endian>) -> ::std::result::Result<Self, #krate::PackableError> {
+                #new
+                Ok(#view { data, little_endian: endian.into().is_little() })
+            }
+
+            #( #getters )*
+        }
+
+        #[allow(dead_code)]
+        impl<'a> #view_mut<'a> {
+            /// A view of the record at the start of `data`, which can be longer.
+            #vis fn new(data: &'a mut [u8], endian: impl ::core::convert::Into<#krate::Endian>) -> ::std::result::Result<Self, #krate::PackableError> {
+                #new
+                Ok(#view_mut { data, little_endian: endian.into().is_little() })
+            }
+
+            #( #getters )*
+
+            #( #setters )*
+        }
+    })
+}
+
+/// Hash the wire layout into `Schema::SCHEMA_HASH`: the schemas of the fields in
+/// order for a struct, the repr and discriminants for an enum. A field with its
+/// own `endian` hashes like the same field wrapped in `Le` or `Be`.
+#[proc_macro_derive(Schema, attributes(packable))]
+pub fn derive_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_schema(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand_schema(mut input: DeriveInput) -> Result<TokenStream2, Error> {
+    let krate = crate_path(&input.attrs)?;
+    let name = &input.ident;
+    let hash = match &input.data {
+        Data::Struct(data) => {
+            let count = data.fields.len() as u64;
+            let mut fields = Vec::new();
+            for field in &data.fields {
+                let options = field_options(&field.attrs)?;
+                let ty = options.pack_as.as_ref().unwrap_or(&field.ty);
+                fields.push(match options.endian {
+                    Some(endian) => quote! {
+                        #krate::schema_combine(
+                            if #endian { #krate::schema_hash("le") } else { #krate::schema_hash("be") },
+                            <#ty as #krate::Schema>::SCHEMA_HASH,
+                        )
+                    },
+                    None => quote!(<#ty as #krate::Schema>::SCHEMA_HASH),
+                });
+            }
+            let presence = match container_options(&input.attrs)?.presence_bitmap {
+                true => quote!(let hash = #krate::schema_combine(hash, #krate::schema_hash("presence"));),
+                false => quote!(),
+            };
+            quote! {
+                let hash = #krate::schema_combine(#krate::schema_hash("struct"), #count);
+                #presence
+                #( let hash = #krate::schema_combine(hash, #fields); )*
+                hash
+            }
+        }
+        Data::Enum(data) => {
+            let repr = enum_repr(&input)?;
+            let options = container_options(&input.attrs)?;
+            let tag = options.tag.unwrap_or_else(|| parse_quote!(#repr));
+            let discriminants = enum_variants(data)?.units.into_iter().map(|(_, discriminant)| discriminant);
+            let (starts, ends): (Vec<&Expr>, Vec<&Expr>) = options.reserved.iter().map(|(start, end)| (start, end)).unzip();
+            quote! {
+                let hash = #krate::schema_combine(#krate::schema_hash("enum"), <#tag as #krate::Schema>::SCHEMA_HASH);
+                #( let hash = #krate::schema_combine(hash, { let value: #repr = #discriminants; value as u64 }); )*
+                #(
+                    let hash = #krate::schema_combine(hash, #krate::schema_hash("reserved"));
+                    let hash = #krate::schema_combine(hash, { let value: #repr = #starts; value as u64 });
+                    let hash = #krate::schema_combine(hash, { let value: #repr = #ends; value as u64 });
+                )*
+                hash
+            }
+        }
+        Data::Union(_) => return Err(Error::new_spanned(name, "Schema cannot be derived for unions")),
+    };
+    for param in &mut input.generics.params {
+        if let GenericParam::Type(param) = param {
+            param.bounds.push(parse_quote!(#krate::Schema));
+        }
+    }
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #krate::Schema for #name #ty_generics #where_clause {
+            const SCHEMA_HASH: u64 = { #hash };
+        }
+    })
+}
+
+/// Convert from an older version of a struct, named by `#[migrate(from = "Old")]`,
+/// with `From`: each field is copied from the old field of the same name, or the one
+/// named by `#[migrate(rename = "..")]`, through `Into`. Fields marked
+/// `#[migrate(default)]` are new and start from `Default`.
+#[proc_macro_derive(Migrate, attributes(migrate))]
+pub fn derive_migrate(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_migrate(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand_migrate(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) if matches!(data.fields, Fields::Named(_)) => &data.fields,
+        _ => return Err(Error::new_spanned(name, "Migrate can only be derived for structs with named fields")),
+    };
+    let mut from: Option<Path> = None;
+    for option in migrate_options(&input.attrs)? {
+        match option {
+            Meta::NameValue(value) if value.path.is_ident("from") => match &value.lit {
+                Lit::Str(path) => from = Some(path.parse()?),
+                lit => return Err(Error::new_spanned(lit, "expected a type name in quotes")),
+            },
+            option => return Err(Error::new_spanned(option, "expected `from = \"..\"`")),
+        }
+    }
+    let from = from.ok_or_else(|| Error::new_spanned(name, "Migrate needs #[migrate(from = \"..\")]"))?;
+    let mut inits = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let mut source = Some(ident.clone());
+        for option in migrate_options(&field.attrs)? {
+            match option {
+                Meta::Path(path) if path.is_ident("default") => source = None,
+                Meta::NameValue(value) if value.path.is_ident("rename") => match &value.lit {
+                    Lit::Str(old) => source = Some(old.parse()?),
+                    lit => return Err(Error::new_spanned(lit, "expected a field name in quotes")),
+                },
+                option => return Err(Error::new_spanned(option, "expected `default` or `rename = \"..\"`")),
+            }
+        }
+        inits.push(match source {
+            Some(old) => quote!(#ident: ::core::convert::Into::into(old.#old)),
+            None => quote!(#ident: ::core::default::Default::default()),
+        });
+    }
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::core::convert::From<#from> for #name #ty_generics #where_clause {
+            fn from(old: #from) -> Self {
+                #name { #( #inits, )* }
+            }
+        }
+    })
+}
+
+/// The items of every `#[migrate(..)]` attribute.
+fn migrate_options(attrs: &[Attribute]) -> Result<Vec<Meta>, Error> {
+    let mut options = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("migrate")) {
+        let Meta::List(list) = attr.parse_meta()? else {
+            return Err(Error::new_spanned(attr, "expected #[migrate(..)]"))
+        };
+        for nested in list.nested {
+            match nested {
+                NestedMeta::Meta(meta) => options.push(meta),
+                NestedMeta::Lit(lit) => return Err(Error::new_spanned(lit, "expected an option name")),
+            }
+        }
+    }
+    Ok(options)
+}
+
+/// Describe each field of a struct for `packable::analyze` and `packable::explain`:
+/// its name, its offset and size in the packed value, whether its value is
+/// endian-neutral, and what it means if it is marked `#[packable(doc = "..")]`.
+#[proc_macro_derive(LayoutInfo, attributes(packable))]
+pub fn derive_layout_info(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_layout_info(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand_layout_info(mut input: DeriveInput) -> Result<TokenStream2, Error> {
+    let krate = crate_path(&input.attrs)?;
+    let (accessors, names, options, presence, types) = match &input.data {
+        Data::Struct(data) => {
+            let names: Vec<String> = data.fields.iter().enumerate().map(|(index, field)| match &field.ident {
+                Some(ident) => ident.to_string(),
+                None => index.to_string(),
+            }).collect();
+            let options = data.fields.iter()
+                .map(|field| field_options(&field.attrs))
+                .collect::<Result<Vec<FieldOptions>, Error>>()?;
+            let types: Vec<&Type> = data.fields.iter().map(|field| &field.ty).collect();
+            (field_accessors(&data.fields), names, options, presence_bits(&input, &data.fields)?, types)
+        }
+        _ => return Err(Error::new_spanned(&input.ident, "LayoutInfo can only be derived for structs")),
+    };
+    let sizes: Vec<TokenStream2> = accessors.iter().zip(&options).enumerate().map(|(index, (accessor, options))| {
+        let ty = types[index];
+        match (presence.as_ref().is_some_and(|presence| presence.bits[index].is_some()), &options.pack_as) {
+            (true, _) => quote!(self.#accessor.as_ref().map_or(0, #krate::Packable::size)),
+            (false, Some(wire)) => {
+                let wire = wire_value(&krate, options, wire, ty, &quote!(self.#accessor));
+                quote!(#wire.map_or(0, |wire| #krate::Packable::size(&wire)))
+            }
+            (false, None) => quote!(#krate::Packable::size(&self.#accessor)),
+        }
+    }).collect();
+    let layouts = accessors.iter().zip(&names).zip(options).zip(&types).map(|(((accessor, name), options), ty)| match options.flatten {
+        true => quote! {
+            for mut field in #krate::LayoutInfo::field_layout(&self.#accessor) {
+                field.offset += offset;
+                fields.push(field);
+            }
+        },
+        false => {
+            let (little, big) = match &options.pack_as {
+                Some(wire) => {
+                    let wire = wire_value(&krate, &options, wire, ty, &quote!(self.#accessor));
+                    let pack = |little_endian| quote! {
+                        #wire.map_or_else(|_| ::std::vec::Vec::new(), |wire| #krate::Packable::pack(&wire, #little_endian))
+                    };
+                    (pack(true), pack(false))
+                }
+                None => (quote!(#krate::Packable::pack(&self.#accessor, true)), quote!(#krate::Packable::pack(&self.#accessor, false))),
+            };
+            let doc = match options.doc {
+                Some(doc) => quote!(::core::option::Option::Some(#doc)),
+                None => quote!(::core::option::Option::None),
+            };
+            quote! {
+                fields.push(#krate::FieldLayout {
+                    name: #name,
+                    offset,
+                    size,
+                    endian_neutral: #krate::endian_neutral(&#little, &#big),
+                    doc: #doc,
+                });
+            }
+        }
+    });
+    let bitmap = presence.as_ref().map(|presence| {
+        let len = presence.len;
+        quote! {
+            fields.push(#krate::FieldLayout {
+                name: "presence",
+                offset: 0,
+                size: #len,
+                endian_neutral: false,
+                doc: ::core::option::Option::Some("which Option fields are present"),
+            });
+            offset += #len;
+        }
+    });
+    for param in &mut input.generics.params {
+        if let GenericParam::Type(param) = param {
+            param.bounds.push(parse_quote!(#krate::Packable));
+        }
+    }
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #krate::LayoutInfo for #name #ty_generics #where_clause {
+            fn field_layout(&self) -> ::std::vec::Vec<#krate::FieldLayout> {
+                let mut fields = ::std::vec::Vec::new();
+                let mut offset = 0;
+                #bitmap
+                #(
+                    let size = #sizes;
+                    #layouts
+                    offset += size;
+                )*
+                let _ = offset;
+                fields
+            }
+        }
+    })
+}
+
+/// Implement `Sample` by drawing every field, or for a unit enum a variant, as the
+/// `#[packable(..)]` attributes `Packable` uses allow: `as` and `scale` fields are
+/// drawn as their wire type and converted, computed and `size_of` fields are set
+/// from the rest, and a `#[packable(reserved)]` variant holds a reserved value.
+#[proc_macro_derive(Sample, attributes(packable))]
+pub fn derive_sample(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_sample(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand_sample(mut input: DeriveInput) -> Result<TokenStream2, Error> {
+    let options = container_options(&input.attrs)?;
+    let krate = &options.krate;
+    let name = &input.ident;
+    let (body, computed) = match &input.data {
+        Data::Struct(data) => {
+            let mut values = Vec::new();
+            let mut computed = Vec::new();
+            for (field, accessor) in data.fields.iter().zip(field_accessors(&data.fields)) {
+                let options = field_options(&field.attrs)?;
+                let ty = &field.ty;
+                values.push(match (&options.pack_as, &options.scale) {
+                    (Some(wire), Some(ScaleOptions { scale, offset, .. })) => quote! {
+                        #krate::from_raw_count(<#wire as #krate::Sample>::sample(rng), #scale, #offset) as #ty
+                    },
+                    // A wire value the field cannot hold is drawn again, a few times.
+                    (Some(wire), None) => quote! {
+                        (0..64)
+                            .find_map(|_| ::core::convert::TryInto::<#ty>::try_into(<#wire as #krate::Sample>::sample(rng)).ok())
+                            .unwrap_or_default()
+                    },
+                    (None, _) => quote!(<#ty as #krate::Sample>::sample(rng)),
+                });
+                if let Some(expr) = options.compute {
+                    computed.push(quote!(self.#accessor = { let value: #ty = #expr; value };));
+                }
+                if let Some(bounded) = options.size_of {
+                    computed.push(quote! {
+                        self.#accessor = ::core::convert::TryFrom::try_from(#krate::Packable::size(&self.#bounded)).unwrap_or_default();
+                    });
+                }
+            }
+            let body = match &data.fields {
+                Fields::Named(fields) => {
+                    let idents = fields.named.iter().map(|field| &field.ident);
+                    quote!(#name { #( #idents: #values, )* })
+                }
+                Fields::Unnamed(_) => quote!(#name( #( #values, )* )),
+                Fields::Unit => quote!(#name),
+            };
+            (body, computed)
+        }
+        Data::Enum(data) => {
+            let repr = enum_repr(&input)?;
+            let EnumVariants { units, reserved } = enum_variants(data)?;
+            let (variants, discriminants): (Vec<&Ident>, Vec<TokenStream2>) = units.into_iter().unzip();
+            let indices = 0..variants.len() as u64;
+            let choices = variants.len() as u64 + u64::from(reserved.is_some() && !options.reserved.is_empty());
+            if choices == 0 {
+                return Err(Error::new_spanned(name, "Sample needs a variant to pick"));
+            }
+            let pick_reserved = match reserved {
+                Some(reserved) if !options.reserved.is_empty() => {
+                    let count = options.reserved.len();
+                    let (starts, ends): (Vec<&Expr>, Vec<&Expr>) = options.reserved.iter().map(|(start, end)| (start, end)).unzip();
+                    quote! {
+                        _ => {
+                            let ranges: [(#repr, #repr); #count] = [ #( (#starts, #ends), )* ];
+                            let (start, end) = ranges[rng.below(#count as u64) as usize];
+                            let offset = match ((end as i128 - start as i128) as u64).checked_add(1) {
+                                Some(span) => rng.below(span),
+                                None => rng.next_u64(),
+                            };
+                            let value = (start as i128 + offset as i128) as #repr;
+                            // A value a named variant uses decodes as that variant.
+                            match value {
+                                #( value if value == #discriminants => #name::#variants, )*
+                                value => #name::#reserved(value),
+                            }
+                        }
+                    }
+                }
+                _ => quote!(_ => unreachable!()),
+            };
+            (
+                quote! {
+                    match rng.below(#choices) {
+                        #( #indices => #name::#variants, )*
+                        #pick_reserved
+                    }
+                },
+                Vec::new(),
+            )
+        }
+        Data::Union(_) => return Err(Error::new_spanned(name, "Sample cannot be derived for unions")),
+    };
+    for param in &mut input.generics.params {
+        if let GenericParam::Type(param) = param {
+            param.bounds.push(parse_quote!(#krate::Sample));
+            if !computed.is_empty() {
+                param.bounds.push(parse_quote!(#krate::Packable));
+            }
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    // Computed fields are written through `self`, as their expressions expect.
+    let refresh = match computed.is_empty() {
+        true => quote!(),
+        false => quote! {
+            trait Computed {
+                fn computed(&mut self);
+            }
+
+            impl #impl_generics Computed for #name #ty_generics #where_clause {
+                fn computed(&mut self) {
+                    #( #computed )*
+                }
+            }
+
+            Computed::computed(&mut sample);
+        },
+    };
+    Ok(quote! {
+        impl #impl_generics #krate::Sample for #name #ty_generics #where_clause {
+            fn sample(rng: &mut #krate::SampleRng) -> Self {
+                #[allow(unused_mut)]
+                let mut sample = #body;
+                #refresh
+                sample
+            }
+        }
+    })
+}
+
+/// Pack `ConstDefault::DEFAULT` at compile time into `DEFAULT_PACKED` (big-endian)
+/// and `DEFAULT_PACKED_LE`. Fields must be integers, `f32` or `f64`, `bool` or
+/// `[u8; N]`, whose packed form is known without running `pack`.
+#[proc_macro_derive(DefaultPacked, attributes(packable))]
+pub fn derive_default_packed(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_default_packed(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand_default_packed(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let krate = crate_path(&input.attrs)?;
+    const INTEGERS: [&str; 12] = ["u8", "u16", "u32", "u64", "u128", "i8", "i16", "i32", "i64", "i128", "f32", "f64"];
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(&input.generics, "DefaultPacked cannot be derived for generic types"));
+    }
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => return Err(Error::new_spanned(name, "DefaultPacked can only be derived for structs")),
+    };
+    let accessors = field_accessors(fields);
+    let mut sizes = Vec::new();
+    let mut big = Vec::new();
+    let mut little = Vec::new();
+    for (field, accessor) in fields.iter().zip(&accessors) {
+        let ty = &field.ty;
+        let is_integer = matches!(ty, Type::Path(path) if INTEGERS.iter().any(|integer| path.path.is_ident(integer)));
+        let is_bool = matches!(ty, Type::Path(path) if path.path.is_ident("bool"));
+        let is_bytes = matches!(ty, Type::Array(array) if matches!(&*array.elem, Type::Path(path) if path.path.is_ident("u8")));
+        let (be, le) = if is_integer {
+            (quote!(value.#accessor.to_be_bytes()), quote!(value.#accessor.to_le_bytes()))
+        } else if is_bool {
+            (quote!([value.#accessor as u8]), quote!([value.#accessor as u8]))
+        } else if is_bytes {
+            (quote!(value.#accessor), quote!(value.#accessor))
+        } else {
+            return Err(Error::new_spanned(ty, "DefaultPacked fields must be integers, floats, bool or [u8; N]"));
+        };
+        let (be, le) = match field_options(&field.attrs)?.endian {
+            Some(endian) => {
+                let fixed = quote!(if #endian { #le } else { #be });
+                (fixed.clone(), fixed)
+            }
+            None => (be, le),
+        };
+        sizes.push(quote!(::core::mem::size_of::<#ty>()));
+        big.push(be);
+        little.push(le);
+    }
+    let size = quote!(0 #( + #sizes )*);
+    let build = |parts: &[TokenStream2]| quote! {
+        {
+            let value = <#name as #krate::ConstDefault>::DEFAULT;
+            let mut out = [0u8; #size];
+            let mut offset = 0;
+            #(
+                let bytes = #parts;
+                let mut index = 0;
+                while index < bytes.len() {
+                    out[offset + index] = bytes[index];
+                    index += 1;
+                }
+                offset += bytes.len();
+            )*
+            let _ = (&value, offset);
+            out
+        }
+    };
+    let (big, little) = (build(&big), build(&little));
+    Ok(quote! {
+        impl #name {
+            pub const DEFAULT_PACKED: [u8; #size] = #big;
+            pub const DEFAULT_PACKED_LE: [u8; #size] = #little;
+        }
+    })
+}
+
+fn field_accessors(fields: &Fields) -> Vec<TokenStream2> {
+    fields.iter().enumerate().map(|(index, field)| match &field.ident {
+        Some(ident) => quote!(#ident),
+        None => {
+            let index = Index::from(index);
+            quote!(#index)
+        }
+    }).collect()
+}
+
+/// The byte order each field is packed in: its `endian` option if it has one,
+/// otherwise the caller's `little_endian`. Also a statement silencing the unused
+/// `little_endian` when every field has its own.
+fn field_endians(fields: &Fields) -> Result<(Vec<TokenStream2>, TokenStream2), Error> {
+    let mut endians = Vec::new();
+    let mut unused = !fields.is_empty();
+    for field in fields {
+        endians.push(match field_options(&field.attrs)?.endian {
+            Some(endian) => endian,
+            None => {
+                unused = false;
+                quote!(little_endian)
+            }
+        });
+    }
+    let unused = match unused {
+        true => quote!(let _ = little_endian;),
+        false => quote!(),
+    };
+    Ok((endians, unused))
+}
+
+fn expand_enum(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2, Error> {
+    let options = container_options(&input.attrs)?;
+    let krate = &options.krate;
+    let name = &input.ident;
+    let repr = enum_repr(input)?;
+    let EnumVariants { units, reserved } = enum_variants(data)?;
+    let (variants, discriminants): (Vec<&Ident>, Vec<TokenStream2>) = units.into_iter().unzip();
+    if reserved.is_some() && options.reserved.is_empty() {
+        return Err(Error::new_spanned(name, "a reserved variant needs #[packable(reserved = \"..\")] ranges"));
+    }
+    let ranges: Vec<TokenStream2> = options.reserved.iter().map(|(start, end)| quote!((#start..=#end).contains(&value))).collect();
+    // A reserved value outside the ranges, or shadowed by a named variant, would
+    // not decode back into the reserved variant, so packing it panics.
+    let (hold, pack_reserved) = match reserved {
+        Some(reserved) => (
+            quote!(#name::#reserved(value)),
+            quote! {
+                #name::#reserved(value) => {
+                    let value = *value;
+                    if !(#( #ranges )||*) #( || value == #discriminants )* {
+                        panic!("{} is not reserved in {}", value, stringify!(#name));
+                    }
+                    value
+                }
+            },
+        ),
+        None => (
+            quote! {
+                return Err(#krate::PackableError {
+                    error_kind: #krate::ErrorKind::InvalidDiscriminant,
+                    data: format!("{} is reserved in {}", value, stringify!(#name)),
+                    direction: Some(#krate::Direction::Unpack),
+                })
+            },
+            quote!(),
+        ),
+    };
+    let discriminant = quote! {
+        let discriminant: #repr = match self {
+            #( #name::#variants => #discriminants, )*
+            #pack_reserved
+        };
+    };
+    let (tag, size, read) = match &options.tag {
+        Some(tag) => (
+            quote! {
+                #discriminant
+                let tag = match #krate::PackAs::<#tag, #repr>::new(discriminant) {
+                    Ok(tag) => tag,
+                    Err(_) => panic!("{} does not fit a {} tag", discriminant, stringify!(#tag)),
+                };
+            },
+            quote!(#krate::Packable::size(&tag)),
+            quote! {
+                let mut tag = #krate::PackAs::<#tag, #repr>::default();
+                let consumed = #krate::Packable::unpack_from(&mut tag, buf, little_endian)?;
+                let discriminant = tag.get();
+            },
+        ),
+        None => (
+            quote! {
+                #discriminant
+                let tag = discriminant;
+            },
+            quote!(::core::mem::size_of::<#repr>()),
+            quote! {
+                let mut discriminant: #repr = 0;
+                let consumed = #krate::Packable::unpack_from(&mut discriminant, buf, little_endian)?;
+            },
+        ),
+    };
+    let size = match options.tag {
+        Some(_) => quote! {
+            #tag
+            #size
+        },
+        None => size,
+    };
+    // An integer tag is checked against every discriminant at compile time, so
+    // only a custom tag such as a `Vlq` can still panic when packing.
+    let fits = match &options.tag {
+        Some(Type::Path(path)) if path.path.get_ident().is_some_and(|tag| INTEGERS.iter().any(|integer| tag == integer)) => {
+            let messages = variants.iter().map(|variant| format!("{}::{} does not fit a {} tag", name, variant, quote!(#path)));
+            quote! {
+                const _: () = {
+                    #( assert!({ let value: #repr = #discriminants; value as i128 >= #path::MIN as i128 && value as i128 <= #path::MAX as i128 }, #messages); )*
+                };
+            }
+        }
+        _ => quote!(),
+    };
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let fuzz = expand_fuzz(input, krate);
+    Ok(quote! {
+        #fits
+
+        #fuzz
+
+        impl #impl_generics #krate::Packable for #name #ty_generics #where_clause {
+            fn pack(&self, little_endian: bool) -> ::std::vec::Vec<u8> {
+                #tag
+                #krate::Packable::pack(&tag, little_endian)
+            }
+
+            fn size(&self) -> usize {
+                #size
+            }
+
+            fn unpack(&mut self, data: &mut ::std::vec::Vec<u8>, little_endian: bool) -> ::std::result::Result<(), #krate::PackableError> {
+                #krate::Packable::unpack_from(self, data, little_endian)?;
+                Ok(())
+            }
+
+            fn unpack_from(&mut self, buf: &[u8], little_endian: bool) -> ::std::result::Result<usize, #krate::PackableError> {
+                #read
+                *self = match discriminant {
+                    #( value if value == #discriminants => #name::#variants, )*
+                    #( value if #ranges => #hold, )*
+                    value => return Err(#krate::PackableError {
+                        error_kind: #krate::ErrorKind::InvalidDiscriminant,
+                        data: format!("{} is not a {} discriminant", value, stringify!(#name)),
+                        direction: Some(#krate::Direction::Unpack),
+                    }),
+                };
+                Ok(consumed)
+            }
+        }
+    })
+}
+
+/// The unit variants of a derived enum with their discriminants, and the variant
+/// marked `#[packable(reserved)]`, if any, which holds the reserved values.
+struct EnumVariants<'a> {
+    units: Vec<(&'a Ident, TokenStream2)>,
+    reserved: Option<&'a Ident>,
+}
+
+fn enum_variants(data: &DataEnum) -> Result<EnumVariants<'_>, Error> {
+    let mut variants = EnumVariants { units: Vec::new(), reserved: None };
+    let (mut base, mut offset) = (None, 0u64);
+    for variant in &data.variants {
+        if let Some((_, expr)) = &variant.discriminant {
+            (base, offset) = (Some(expr), 0);
+        }
+        let next = proc_macro2::Literal::u64_unsuffixed(offset);
+        let discriminant = match base {
+            Some(expr) if offset == 0 => quote!((#expr)),
+            Some(expr) => quote!((#expr) + #next),
+            None => quote!(#next),
+        };
+        offset += 1;
+        let mut reserved = false;
+        for attr in variant.attrs.iter().filter(|attr| attr.path.is_ident("packable")) {
+            match attr.parse_meta()? {
+                Meta::List(list) if list.nested.iter().all(|nested| matches!(nested, NestedMeta::Meta(Meta::Path(path)) if path.is_ident("reserved"))) => reserved = true,
+                meta => return Err(Error::new_spanned(meta, "expected #[packable(reserved)]")),
+            }
+        }
+        match (&variant.fields, reserved) {
+            (Fields::Unit, false) => variants.units.push((&variant.ident, discriminant)),
+            (Fields::Unnamed(fields), true) if fields.unnamed.len() == 1 && variants.reserved.is_none() => variants.reserved = Some(&variant.ident),
+            (_, true) => return Err(Error::new_spanned(variant, "only one variant can be reserved, and it holds just the raw value")),
+            (_, false) => return Err(Error::new_spanned(variant, "derived enums can only have unit variants, besides one #[packable(reserved)] variant")),
+        }
+    }
+    Ok(variants)
+}
+
+/// Where the generated code finds the `packable` crate: `::packable` unless a
+/// `#[packable(crate = "..")]` attribute names another path, e.g. for a crate that
+/// re-exports it.
+/// Generate one `#[test]` per line of a test vector file, read at compile time
+/// relative to the crate root: `vector_tests!(Route, "testdata/route.csv");`.
+/// Lines are `name,le|be,hex input,expected` as for `parse_vectors`, except that
+/// the expected value is a Rust expression of the type, such as
+/// `Route { kind: 1, hops: 2 }`, compared with the decoded value itself. Each name
+/// becomes the name of its test.
+#[proc_macro]
+pub fn vector_tests(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as VectorTests);
+    expand_vector_tests(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+struct VectorTests {
+    ty: Type,
+    path: LitStr,
+}
+
+impl Parse for VectorTests {
+    fn parse(input: ParseStream) -> Result<Self, Error> {
+        let ty = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let path = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+        Ok(VectorTests { ty, path })
+    }
+}
+
+fn expand_vector_tests(input: VectorTests) -> Result<TokenStream2, Error> {
+    let VectorTests { ty, path } = input;
+    let krate: Path = parse_quote!(::packable);
+    let file = std::path::Path::new(&std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default()).join(path.value());
+    let text = std::fs::read_to_string(&file).map_err(|error| Error::new_spanned(&path, format!("cannot read {}: {}", file.display(), error)))?;
+    let mut tests = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue
+        }
+        let invalid = |message: &str| Error::new_spanned(&path, format!("{} line {}: {}", path.value(), index + 1, message));
+        let fields: Vec<&str> = line.splitn(4, ',').map(str::trim).collect();
+        let [name, endian, hex, expected] = fields[..] else {
+            return Err(invalid("expected 4 comma separated fields"))
+        };
+        let test: Ident = syn::parse_str(name).map_err(|_| invalid("the name must be a valid identifier"))?;
+        let litle_endian = match endian {
+            "le" => true,
+            "be" => false,
+            _ => return Err(invalid("endianness must be le or be")),
+        };
+        let digits: Vec<u32> = hex.chars().filter(|c| !c.is_whitespace()).map(|c| c.to_digit(16)).collect::<Option<_>>()
+            .filter(|digits: &Vec<u32>| digits.len().is_multiple_of(2))
+            .ok_or_else(|| invalid("invalid hex input"))?;
+        let bytes = digits.chunks(2).map(|pair| (pair[0] << 4 | pair[1]) as u8);
+        let expected = match expected.strip_prefix("error:") {
+            Some(kind) => {
+                let kind: TokenStream2 = kind.trim().parse().map_err(|_| invalid("invalid error kind"))?;
+                quote!(::core::result::Result::Err(#krate::ErrorKind::#kind))
+            }
+            None => {
+                let value: TokenStream2 = expected.parse().map_err(|_| invalid("invalid expected value"))?;
+                quote!(::core::result::Result::Ok({ let value: #ty = #value; value }))
+            }
+        };
+        tests.push(quote! {
+            #[test]
+            fn #test() {
+                let vector = #krate::TestVector::<#ty> {
+                    name: ::std::string::String::from(#name),
+                    litle_endian: #litle_endian,
+                    input: ::std::vec![#( #bytes ),*],
+                    expected: #expected,
+                };
+                if let ::core::result::Result::Err(failure) = vector.check() {
+                    panic!("{}", failure)
+                }
+            }
+        });
+    }
+    let file = file.to_string_lossy().into_owned();
+    Ok(quote! {
+        const _: &str = include_str!(#file);
+        #( #tests )*
+    })
+}
+
+fn crate_path(attrs: &[Attribute]) -> Result<Path, Error> {
+    Ok(container_options(attrs)?.krate)
+}
+
+/// What the `#[packable(..)]` attributes of a struct or enum ask for.
+struct ContainerOptions {
+    krate: Path,
+    tag: Option<Type>,
+    presence_bitmap: bool,
+    accessors: bool,
+    reserved: Vec<(Expr, Expr)>,
+    size: Option<LitInt>,
+}
+
+fn container_options(attrs: &[Attribute]) -> Result<ContainerOptions, Error> {
+    let mut options = ContainerOptions { krate: parse_quote!(::packable), tag: None, presence_bitmap: false, accessors: false, reserved: Vec::new(), size: None };
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("packable")) {
+        let Meta::List(list) = attr.parse_meta()? else {
+            return Err(Error::new_spanned(attr, "expected #[packable(..)]"))
+        };
+        for nested in &list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("crate") => match &value.lit {
+                    Lit::Str(path) => options.krate = path.parse()?,
+                    lit => return Err(Error::new_spanned(lit, "expected a path in quotes")),
+                },
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("tag") => match &value.lit {
+                    Lit::Str(tag) => options.tag = Some(tag.parse()?),
+                    lit => return Err(Error::new_spanned(lit, "expected a type in quotes")),
+                },
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("presence_bitmap") => options.presence_bitmap = true,
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("accessors") => options.accessors = true,
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("size") => match &value.lit {
+                    Lit::Int(size) => options.size = Some(size.clone()),
+                    lit => return Err(Error::new_spanned(lit, "expected a size in bytes")),
+                },
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("reserved") => match &value.lit {
+                    Lit::Str(range) => {
+                        let text = range.value();
+                        let (start, end) = text.split_once("..=").unwrap_or((&text, &text));
+                        options.reserved.push((syn::parse_str(start)?, syn::parse_str(end)?));
+                    }
+                    lit => return Err(Error::new_spanned(lit, "expected a value or an inclusive range in quotes")),
+                },
+                nested => return Err(Error::new_spanned(nested, "expected `crate = \"..\"`, `tag = \"..\"`, `reserved = \"..\"`, `presence_bitmap`, `accessors` or `size = ..`")),
+            }
+        }
+    }
+    Ok(options)
+}
+
+/// Where each `Option` field of a `#[packable(presence_bitmap)]` struct keeps its
+/// presence bit: the byte and mask in the bitmap, and the type inside the `Option`.
+struct Presence {
+    len: usize,
+    bits: Vec<Option<(usize, u8, Type)>>,
+}
+
+fn presence_bits(input: &DeriveInput, fields: &Fields) -> Result<Option<Presence>, Error> {
+    if !container_options(&input.attrs)?.presence_bitmap {
+        return Ok(None)
+    }
+    let mut count = 0usize;
+    let mut bits = Vec::new();
+    for field in fields {
+        bits.push(option_inner(&field.ty).map(|inner| {
+            let bit = (count / 8, 1u8 << (count % 8), inner.clone());
+            count += 1;
+            bit
+        }));
+    }
+    if count == 0 {
+        return Err(Error::new_spanned(&input.ident, "presence_bitmap needs at least one Option field"))
+    }
+    Ok(Some(Presence { len: count.div_ceil(8), bits }))
+}
+
+/// `T` for a field of type `Option<T>`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None
+    };
+    let segment = path.path.segments.last().filter(|segment| segment.ident == "Option")?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None
+    };
+    match args.args.first() {
+        Some(GenericArgument::Type(inner)) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+/// What the `#[packable(..)]` attributes of a field ask for.
+#[derive(Default)]
+struct FieldOptions {
+    flatten: bool,
+    split_after: bool,
+    endian: Option<TokenStream2>,
+    doc: Option<String>,
+    compute: Option<TokenStream2>,
+    verify: bool,
+    pack_as: Option<Type>,
+    scale: Option<ScaleOptions>,
+    size_of: Option<Member>,
+    skip_extra: bool,
+    at: Option<LitInt>,
+}
+
+/// How a field marked `#[packable(scale = .., offset = ..)]` maps to its raw count.
+struct ScaleOptions {
+    scale: TokenStream2,
+    offset: TokenStream2,
+    rounding: TokenStream2,
+}
+
+fn field_options(attrs: &[Attribute]) -> Result<FieldOptions, Error> {
+    let mut options = FieldOptions { verify: true, ..FieldOptions::default() };
+    let (mut scale, mut offset, mut rounding) = (None, None, None);
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("packable")) {
+        let Meta::List(list) = attr.parse_meta()? else {
+            return Err(Error::new_spanned(attr, "expected #[packable(..)]"))
+        };
+        for nested in &list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("flatten") => options.flatten = true,
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("split_after") => options.split_after = true,
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip_extra") => options.skip_extra = true,
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("at") => match &value.lit {
+                    Lit::Int(at) => options.at = Some(at.clone()),
+                    lit => return Err(Error::new_spanned(lit, "expected an offset in bytes")),
+                },
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("size_of") => match &value.lit {
+                    Lit::Str(field) => options.size_of = Some(field.parse()?),
+                    lit => return Err(Error::new_spanned(lit, "expected a field name in quotes")),
+                },
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("endian") => {
+                    options.endian = Some(match &value.lit {
+                        Lit::Str(endian) if endian.value() == "little" => quote!(true),
+                        Lit::Str(endian) if endian.value() == "big" => quote!(false),
+                        Lit::Str(endian) if endian.value() == "native" => quote!(cfg!(target_endian = "little")),
+                        lit => return Err(Error::new_spanned(lit, "expected \"little\", \"big\" or \"native\"")),
+                    })
+                }
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("doc") => match &value.lit {
+                    Lit::Str(doc) => options.doc = Some(doc.value()),
+                    lit => return Err(Error::new_spanned(lit, "expected a description in quotes")),
+                },
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("compute") => match &value.lit {
+                    Lit::Str(expr) => options.compute = Some(expr.parse()?),
+                    lit => return Err(Error::new_spanned(lit, "expected an expression in quotes")),
+                },
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("as") => match &value.lit {
+                    Lit::Str(wire) => options.pack_as = Some(wire.parse()?),
+                    lit => return Err(Error::new_spanned(lit, "expected a type in quotes")),
+                },
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("scale") => scale = Some(float(&value.lit)?),
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("offset") => offset = Some(float(&value.lit)?),
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("rounding") => {
+                    rounding = Some(match &value.lit {
+                        Lit::Str(mode) if mode.value() == "nearest" => quote!(Nearest),
+                        Lit::Str(mode) if mode.value() == "floor" => quote!(Floor),
+                        Lit::Str(mode) if mode.value() == "ceil" => quote!(Ceil),
+                        Lit::Str(mode) if mode.value() == "truncate" => quote!(Truncate),
+                        lit => return Err(Error::new_spanned(lit, "expected \"nearest\", \"floor\", \"ceil\" or \"truncate\"")),
+                    })
+                }
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("verify") => match &value.lit {
+                    Lit::Bool(verify) => options.verify = verify.value,
+                    lit => return Err(Error::new_spanned(lit, "expected true or false")),
+                },
+                nested => return Err(Error::new_spanned(nested, "expected `flatten`, `split_after`, `endian = \"..\"`, `doc = \"..\"`, `compute = \"..\"`, `verify = false`, `as = \"..\"`, `scale = ..`, `offset = ..`, `rounding = \"..\"`, `size_of = \"..\"`, `skip_extra` or `at = ..`")),
+            }
+        }
+    }
+    if !options.verify && options.compute.is_none() {
+        return Err(Error::new_spanned(&attrs[0], "`verify` only applies to computed fields"));
+    }
+    if options.skip_extra && options.size_of.is_none() {
+        return Err(Error::new_spanned(&attrs[0], "`skip_extra` only applies to size_of fields"));
+    }
+    if scale.is_some() || offset.is_some() || rounding.is_some() {
+        if options.pack_as.is_none() {
+            return Err(Error::new_spanned(&attrs[0], "`scale`, `offset` and `rounding` need the raw count type, e.g. `as = \"u16\"`"));
+        }
+        options.scale = Some(ScaleOptions {
+            scale: scale.unwrap_or_else(|| quote!(1.0)),
+            offset: offset.unwrap_or_else(|| quote!(0.0)),
+            rounding: rounding.unwrap_or_else(|| quote!(Nearest)),
+        });
+    }
+    Ok(options)
+}
+
+/// A number given as `scale = 0.1` or `offset = -40`, as an `f64` expression.
+fn float(lit: &Lit) -> Result<TokenStream2, Error> {
+    let value: f64 = match lit {
+        Lit::Float(float) => float.base10_parse()?,
+        Lit::Int(int) => int.base10_parse()?,
+        lit => return Err(Error::new_spanned(lit, "expected a number")),
+    };
+    let magnitude = value.abs();
+    Ok(match value.is_sign_negative() {
+        true => quote!((-#magnitude)),
+        false => quote!(#magnitude),
+    })
+}
+
+/// The value a field marked `as` another type is packed as, as a `Result` that
+/// fails when `value` does not fit the wire type. Scaled fields saturate instead.
+fn wire_value(krate: &Path, options: &FieldOptions, wire: &Type, ty: &Type, value: &TokenStream2) -> TokenStream2 {
+    match &options.scale {
+        Some(ScaleOptions { scale, offset, rounding }) => quote! {
+            ::core::result::Result::<#wire, #krate::PackableError>::Ok(
+                #krate::to_raw_count((#value) as f64, #scale, #offset, #krate::Rounding::#rounding)
+            )
+        },
+        None => quote!(#krate::PackAs::<#wire, #ty>::new(#value)),
+    }
+}
+
+/// Unpack a field marked `as` another type from `source` through its wire type.
+fn read_wire(krate: &Path, options: &FieldOptions, wire: &Type, ty: &Type, field: &TokenStream2, endian: &TokenStream2) -> TokenStream2 {
+    match &options.scale {
+        Some(ScaleOptions { scale, offset, .. }) => quote! {
+            let mut wire = <#wire as ::core::default::Default>::default();
+            #krate::PackableExt::unpack_field(&mut wire, &mut source, #endian)?;
+            self.#field = #krate::from_raw_count(wire, #scale, #offset) as #ty;
+        },
+        None => quote! {
+            let mut wire = #krate::PackAs::<#wire, #ty>::default();
+            #krate::PackableExt::unpack_field(&mut wire, &mut source, #endian)?;
+            self.#field = wire.get();
+        },
+    }
+}
+
+/// The integers an enum can be `#[repr(..)]` as.
+const INTEGERS: [&str; 8] = ["u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64"];
+
+/// The integer type of a `#[repr(..)]` attribute.
+fn enum_repr(input: &DeriveInput) -> Result<Ident, Error> {
+    for attr in input.attrs.iter().filter(|attr| attr.path.is_ident("repr")) {
+        let reprs = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+        for repr in reprs {
+            if let Meta::Path(path) = repr {
+                if let Some(repr) = path.get_ident().filter(|repr| INTEGERS.iter().any(|integer| *repr == integer)) {
+                    return Ok(repr.clone())
+                }
+            }
+        }
+    }
+    Err(Error::new_spanned(&input.ident, "Packable enums need an integer #[repr(..)]"))
+}
@@ -0,0 +1,66 @@
+use std::{alloc::{GlobalAlloc, Layout, System}, cell::Cell};
+
+thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count(size: usize){
+    // try_with: the allocator also runs while thread locals are torn down.
+    let _ = ALLOCATED.try_with(|allocated| allocated.set(allocated.get().wrapping_add(size)));
+}
+
+/// Global allocator wrapper that counts the bytes each thread allocates, read
+/// back with `allocated_during`. Install it with
+/// `#[global_allocator] static ALLOC: CountingAlloc = CountingAlloc(System);`.
+pub struct CountingAlloc<A = System>(pub A);
+
+// SAFETY: every call is forwarded unchanged to the wrapped allocator.
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAlloc<A>{
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count(layout.size());
+        self.0.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count(layout.size());
+        self.0.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count(new_size.saturating_sub(layout.size()));
+        self.0.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.0.dealloc(ptr, layout)
+    }
+}
+
+/// Run `f` and return its result with the bytes it allocated on this thread, e.g.
+/// `allocated_during(|| codec.decode::<Message>(&frame))` to hold a peer to a
+/// memory budget. Frees are not subtracted. Always zero unless `CountingAlloc` is
+/// the global allocator.
+pub fn allocated_during<R, F: FnOnce() -> R>(f: F) -> (R, usize){
+    let before = ALLOCATED.with(Cell::get);
+    let result = f();
+    (result, ALLOCATED.with(Cell::get).wrapping_sub(before))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::alloc::System;
+    use crate::{KeyValue, from_bytes};
+    use super::{CountingAlloc, allocated_during};
+
+    #[global_allocator]
+    static ALLOC: CountingAlloc = CountingAlloc(System);
+
+    #[test]
+    fn test_allocated_during(){
+        let data = [0, 1, 0, 0, 0, 64].iter().copied().chain([7; 64]).collect::<Vec<u8>>();
+        let (value, allocated) = allocated_during(|| from_bytes::<KeyValue>(&data, false));
+        assert_eq!(Some(&[7; 64][..]), value.unwrap().raw(1));
+        assert!(allocated >= 64, "{}", allocated);
+        assert_eq!(4, allocated_during(|| from_bytes::<u32>(&[0; 4], false)).1);
+    }
+}
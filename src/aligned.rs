@@ -0,0 +1,109 @@
+use core::{fmt, ops::{Deref, DerefMut}, ptr::NonNull};
+use std::alloc::{self, Layout};
+use crate::{Endian, Packable, PackableError, ErrorKind};
+
+/// Heap buffer whose start is aligned to a caller-chosen boundary, for handing
+/// packed frames to DMA engines.
+pub struct AlignedBuf{
+    ptr: NonNull<u8>,
+    len: usize,
+    layout: Layout,
+}
+
+// The buffer owns its allocation exclusively, like a Vec<u8>.
+unsafe impl Send for AlignedBuf{}
+unsafe impl Sync for AlignedBuf{}
+
+impl AlignedBuf{
+    /// Zero-filled buffer of `len` bytes starting on an `align` boundary.
+    pub fn zeroed(len: usize, align: usize) -> Result<Self, PackableError>{
+        let layout = Layout::from_size_align(len.max(1), align).map_err(|error| PackableError { 
+            error_kind: ErrorKind::OutOfRange, 
+            data: format!("invalid alignment {}: {}", align, error),
+            direction: None,
+        })?;
+        // SAFETY: the layout has a non-zero size.
+        let ptr = unsafe { alloc::alloc_zeroed(layout) };
+        match NonNull::new(ptr){
+            Some(ptr) => Ok(AlignedBuf { ptr, len, layout }),
+            None => alloc::handle_alloc_error(layout),
+        }
+    }
+
+    pub fn alignment(&self) -> usize{
+        self.layout.align()
+    }
+
+    pub fn as_slice(&self) -> &[u8]{
+        // SAFETY: ptr is valid for len initialized bytes for the lifetime of self.
+        unsafe { core::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8]{
+        // SAFETY: as above, and &mut self guarantees exclusive access.
+        unsafe { core::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Drop for AlignedBuf{
+    fn drop(&mut self) {
+        // SAFETY: ptr was allocated in `zeroed` with this exact layout.
+        unsafe { alloc::dealloc(self.ptr.as_ptr(), self.layout) }
+    }
+}
+
+impl Deref for AlignedBuf{
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl DerefMut for AlignedBuf{
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.as_mut_slice()
+    }
+}
+
+impl fmt::Debug for AlignedBuf{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AlignedBuf").field("align", &self.alignment()).field("data", &self.as_slice()).finish()
+    }
+}
+
+/// Pack `value` into a buffer aligned to `align` bytes whose length is zero-padded
+/// up to a multiple of `pad_to` (use 1 for no padding).
+pub fn pack_aligned<T: Packable + ?Sized>(value: &T, endian: impl Into<Endian>, align: usize, pad_to: usize) -> Result<AlignedBuf, PackableError>{
+    let litle_endian = endian.into().is_little();
+    if pad_to == 0{
+        return Err(PackableError { 
+            error_kind: ErrorKind::OutOfRange, 
+            data: "padding multiple must not be zero".to_string(),
+            direction: None,
+        })
+    }
+    let data = value.pack(litle_endian);
+    let mut buffer = AlignedBuf::zeroed(data.len().div_ceil(pad_to) * pad_to, align)?;
+    buffer[..data.len()].copy_from_slice(&data);
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pack_aligned;
+
+    #[test]
+    fn test_pack_aligned(){
+        let buffer = pack_aligned(&[1u8, 2, 3, 4, 5], false, 32, 4).unwrap();
+        assert_eq!(0, buffer.as_ptr() as usize % 32);
+        assert_eq!(&[1, 2, 3, 4, 5, 0, 0, 0], &buffer[..]);
+
+        let buffer = pack_aligned(&0x0102u16, true, 64, 1).unwrap();
+        assert_eq!(0, buffer.as_ptr() as usize % 64);
+        assert_eq!(&[2, 1], &buffer[..]);
+
+        assert!(pack_aligned(&0u8, false, 3, 1).is_err());
+        assert!(pack_aligned(&0u8, false, 4, 0).is_err());
+    }
+}
@@ -0,0 +1,75 @@
+use core::ops::Range;
+use crate::{Endian, ErrorKind, Packable, PackableError};
+
+/// Where one field was unpacked from, or where unpacking it failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldRecord{
+    pub name: &'static str,
+    /// The bytes the field used, or everything from its start on failure.
+    pub range: Range<usize>,
+    pub bytes: Vec<u8>,
+    pub error: Option<ErrorKind>,
+}
+
+/// Provenance of every field unpacked by `unpack_audited!`, for taking apart
+/// malformed frames or keeping as evidence of what was decoded from what.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AuditLog{
+    pub fields: Vec<FieldRecord>,
+}
+
+impl AuditLog{
+    pub fn new() -> Self{
+        AuditLog::default()
+    }
+
+    /// Unpack `value` from `data` at `offset` and record the bytes it came from,
+    /// returning how many it used.
+    pub fn unpack_field<T: Packable + ?Sized>(&mut self, name: &'static str, value: &mut T, data: &[u8], offset: usize, endian: impl Into<Endian>) -> Result<usize, PackableError>{
+        let litle_endian = endian.into().is_little();
+        let rest = data.get(offset..).unwrap_or_default();
+        match value.unpack_from(rest, litle_endian){
+            Ok(consumed) => {
+                self.fields.push(FieldRecord { name, range: offset..offset + consumed, bytes: rest[..consumed].to_vec(), error: None });
+                Ok(consumed)
+            }
+            Err(error) => {
+                self.fields.push(FieldRecord { name, range: offset..offset + rest.len(), bytes: rest.to_vec(), error: Some(error.error_kind) });
+                Err(error)
+            }
+        }
+    }
+
+    /// The field that failed to unpack, if any.
+    pub fn failure(&self) -> Option<&FieldRecord>{
+        self.fields.iter().find(|field| field.error.is_some())
+    }
+
+    pub fn clear(&mut self){
+        self.fields.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ErrorKind;
+    use super::AuditLog;
+
+    #[test]
+    fn test_audit(){
+        let (mut kind, mut length, mut name) = (0u8, 0u16, String::new());
+        let mut log = AuditLog::new();
+        let data = [1, 0, 2, 0, 0, 0, 2, b'o', b'k'];
+        assert_eq!(9, unpack_audited!(false, &data, &mut log, kind, length, name).unwrap());
+        let ranges: Vec<_> = log.fields.iter().map(|field| (field.name, field.range.clone())).collect();
+        assert_eq!(vec![("kind", 0..1), ("length", 1..3), ("name", 3..9)], ranges);
+        assert_eq!(vec![0, 2], log.fields[1].bytes);
+        assert!(log.failure().is_none());
+
+        log.clear();
+        let error = unpack_audited!(false, &data[..6], &mut log, kind, length, name).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+        let failure = log.failure().unwrap();
+        assert_eq!(("name", 3..6, vec![0, 0, 0]), (failure.name, failure.range.clone(), failure.bytes.clone()));
+    }
+}
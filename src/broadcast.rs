@@ -0,0 +1,88 @@
+use std::{io::{self, Write}, sync::Arc};
+use crate::{Direction, Endian, ErrorKind, Packable, PackableError};
+
+/// A message packed once and shared by every subscriber. Each copy only adds
+/// its own small header, such as a sequence number, in front of the shared body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Broadcast{
+    body: Arc<[u8]>,
+    litle_endian: bool,
+}
+
+/// One subscriber's copy: its own header followed by the shared body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame{
+    pub header: Vec<u8>,
+    pub body: Arc<[u8]>,
+}
+
+impl Broadcast{
+    pub fn new<T: Packable + ?Sized>(message: &T, endian: impl Into<Endian>) -> Self{
+        let litle_endian = endian.into().is_little();
+        Broadcast { body: message.pack(litle_endian).into(), litle_endian }
+    }
+
+    pub fn body(&self) -> Arc<[u8]>{
+        self.body.clone()
+    }
+
+    /// Frame the shared body behind `header`, without copying the body.
+    pub fn frame<H: Packable + ?Sized>(&self, header: &H) -> Frame{
+        Frame { header: header.pack(self.litle_endian), body: self.body.clone() }
+    }
+
+    /// A private copy of the body with `value` written over the bytes at `offset`,
+    /// for formats that keep the sequence number inside the message itself.
+    pub fn patched<T: Packable + ?Sized>(&self, offset: usize, value: &T) -> Result<Vec<u8>, PackableError>{
+        let bytes = value.pack(self.litle_endian);
+        let end = offset.checked_add(bytes.len()).filter(|&end| end <= self.body.len()).ok_or_else(|| PackableError { 
+            error_kind: ErrorKind::OutOfRange, 
+            data: format!("patch of {} bytes at offset {} past end of {} byte body", bytes.len(), offset, self.body.len()),
+            direction: Some(Direction::Pack),
+        })?;
+        let mut data = self.body.to_vec();
+        data[offset..end].copy_from_slice(&bytes);
+        Ok(data)
+    }
+}
+
+impl Frame{
+    pub fn len(&self) -> usize{
+        self.header.len() + self.body.len()
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.len() == 0
+    }
+
+    pub fn to_vec(&self) -> Vec<u8>{
+        [&self.header[..], &self.body[..]].concat()
+    }
+
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()>{
+        writer.write_all(&self.header)?;
+        writer.write_all(&self.body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::ErrorKind;
+    use super::Broadcast;
+
+    #[test]
+    fn test_broadcast(){
+        let broadcast = Broadcast::new(&(0u16, 0xaabbu16), false);
+        let frames: Vec<_> = (1u8..=3).map(|sequence| broadcast.frame(&sequence)).collect();
+        assert!(frames.iter().all(|frame| Arc::ptr_eq(&frame.body, &broadcast.body())));
+        assert_eq!(vec![2, 0, 0, 0xaa, 0xbb], frames[1].to_vec());
+        let mut written = Vec::new();
+        frames[2].write_to(&mut written).unwrap();
+        assert_eq!(vec![3, 0, 0, 0xaa, 0xbb], written);
+
+        assert_eq!(vec![0, 7, 0xaa, 0xbb], broadcast.patched(0, &7u16).unwrap());
+        assert_eq!(vec![0, 0, 0xaa, 0xbb], &broadcast.body()[..]);
+        assert!(matches!(broadcast.patched(3, &7u16).unwrap_err().error_kind, ErrorKind::OutOfRange));
+    }
+}
@@ -0,0 +1,106 @@
+use core::fmt;
+use crate::{Packable, PackableError};
+
+/// Opaque fixed-size payload (hashes, ids, keys) with a hex `Debug`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Bytes<const N: usize>(pub [u8; N]);
+
+impl<const N: usize> Bytes<N>{
+    pub fn as_slice(&self) -> &[u8]{
+        &self.0
+    }
+
+    pub fn from_hex(hex: &str) -> Option<Self>{
+        let hex = hex.as_bytes();
+        if hex.len() != N * 2{
+            return None
+        }
+        let mut bytes = [0; N];
+        for (byte, digits) in bytes.iter_mut().zip(hex.chunks(2)){
+            let high = (digits[0] as char).to_digit(16)?;
+            let low = (digits[1] as char).to_digit(16)?;
+            *byte = (high << 4 | low) as u8;
+        }
+        Some(Bytes(bytes))
+    }
+}
+
+impl<const N: usize> Default for Bytes<N>{
+    fn default() -> Self {
+        Bytes([0; N])
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for Bytes<N>{
+    fn from(bytes: [u8; N]) -> Self {
+        Bytes(bytes)
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for Bytes<N>{
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<const N: usize> PartialEq<[u8]> for Bytes<N>{
+    fn eq(&self, other: &[u8]) -> bool {
+        self.0[..] == *other
+    }
+}
+
+impl<const N: usize> PartialEq<&[u8]> for Bytes<N>{
+    fn eq(&self, other: &&[u8]) -> bool {
+        self.0[..] == **other
+    }
+}
+
+impl<const N: usize> fmt::LowerHex for Bytes<N>{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in &self.0{
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> fmt::Debug for Bytes<N>{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Bytes({:x})", self)
+    }
+}
+
+impl<const N: usize> Packable for Bytes<N>{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        self.0.pack(litle_endian)
+    }
+
+    fn size(&self) -> usize {
+        N
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.0.unpack(data, litle_endian)
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        self.0.unpack_from(buf, litle_endian)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{to_bytes, from_bytes};
+    use super::Bytes;
+
+    #[test]
+    fn test_bytes(){
+        let id = Bytes::<4>::from_hex("deadBEEF").unwrap();
+        assert_eq!("Bytes(deadbeef)", format!("{:?}", id));
+        assert_eq!(id, &[0xde, 0xad, 0xbe, 0xef][..]);
+        assert_eq!(vec![0xde, 0xad, 0xbe, 0xef], to_bytes(&id, true));
+        assert_eq!(id, from_bytes::<Bytes<4>>(id.as_slice(), false).unwrap());
+        assert_eq!(None, Bytes::<4>::from_hex("deadbee"));
+        assert_eq!(None, Bytes::<2>::from_hex("zz00"));
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{Packable, PackableError, UnpackSource, buffer_length_error};
+
+/// `unpack!` source over a sequence of byte slices read as one contiguous input,
+/// e.g. the two halves of a wrapped ring buffer or several network reads.
+///
+/// A value is decoded in place from the last segment. Before that, the rest of the
+/// input is copied into one slice for each value, because types such as `Remaining`
+/// or `UntilEnd` read as far as they are given and would stop at a segment end.
+#[derive(Debug, Clone)]
+pub struct Chain<'a>{
+    segments: &'a [&'a [u8]],
+    offset: usize,
+}
+
+impl<'a> Chain<'a>{
+    pub fn new(segments: &'a [&'a [u8]]) -> Self{
+        let mut chain = Chain { segments, offset: 0 };
+        chain.skip_empty();
+        chain
+    }
+
+    pub fn len(&self) -> usize{
+        self.segments.iter().map(|segment| segment.len()).sum::<usize>() - self.offset
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.segments.is_empty()
+    }
+
+    fn skip_empty(&mut self){
+        while let Some(segment) = self.segments.first(){
+            if self.offset < segment.len(){
+                break
+            }
+            self.offset -= segment.len();
+            self.segments = &self.segments[1..];
+        }
+    }
+
+    fn advance(&mut self, count: usize){
+        self.offset += count;
+        self.skip_empty();
+    }
+
+    fn copy(&self, count: usize) -> Vec<u8>{
+        let mut data = Vec::with_capacity(count);
+        let mut offset = self.offset;
+        for segment in self.segments{
+            if data.len() == count{
+                break
+            }
+            let end = segment.len().min(offset + count - data.len());
+            data.extend_from_slice(&segment[offset..end]);
+            offset = 0;
+        }
+        data
+    }
+}
+
+impl UnpackSource for Chain<'_>{
+    fn take(&mut self, size: usize) -> Result<Vec<u8>, PackableError> {
+        if self.len() < size{
+            return Err(buffer_length_error(size, self.len()))
+        }
+        let data = self.copy(size);
+        self.advance(size);
+        Ok(data)
+    }
+
+    fn unpack_into<T: Packable + ?Sized>(&mut self, value: &mut T, litle_endian: bool) -> Result<usize, PackableError>{
+        let consumed = match self.segments{
+            [] => value.unpack_from(&[], litle_endian)?,
+            [last] => value.unpack_from(&last[self.offset..], litle_endian)?,
+            _ => value.unpack_from(&self.copy(self.len()), litle_endian)?,
+        };
+        self.advance(consumed);
+        Ok(consumed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, Remaining, UntilEnd, Vlq};
+    use super::Chain;
+
+    #[test]
+    fn test_chain(){
+        let segments: [&[u8]; 4] = [&[0, 0], &[], &[1, 2, 0x81], &[0x00, 9]];
+        let mut chain = Chain::new(&segments);
+        let (mut a, mut b, mut c) = (0u32, Vlq::<4>::default(), 0u8);
+        assert_eq!(7, crate::unpack!(false, &mut chain, a, b, c).unwrap());
+        assert_eq!((0x0102, 0x80, 9), (a, b.get(), c));
+        assert!(chain.is_empty());
+
+        let mut chain = Chain::new(&segments);
+        let error = crate::unpack!(false, &mut chain, a, a).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+        assert_eq!(3, chain.len());
+    }
+
+    #[test]
+    fn test_chain_growing_window(){
+        let segments: [&[u8]; 3] = [&[0, 0, 0, 5, 1], &[2, 3], &[4, 5, 7]];
+        let mut chain = Chain::new(&segments);
+        let (mut payload, mut after) = (Vec::<u8>::new(), 0u8);
+        assert_eq!(10, crate::unpack!(false, &mut chain, payload, after).unwrap());
+        assert_eq!((vec![1, 2, 3, 4, 5], 7), (payload, after));
+    }
+
+    #[test]
+    fn test_chain_greedy(){
+        let segments: [&[u8]; 2] = [&[0, 7, 1], &[2, 3]];
+        let (mut kind, mut payload) = (0u16, Remaining::default());
+        let mut chain = Chain::new(&segments);
+        assert_eq!(5, crate::unpack!(false, &mut chain, kind, payload).unwrap());
+        assert_eq!((7, &[1, 2, 3][..]), (kind, &payload.0[..]));
+        assert!(chain.is_empty());
+
+        let segments: [&[u8]; 2] = [&[0, 1, 0, 2], &[0, 3]];
+        let mut values = UntilEnd::<u16>::default();
+        let mut chain = Chain::new(&segments);
+        assert_eq!(6, crate::unpack!(false, &mut chain, values).unwrap());
+        assert_eq!(vec![1, 2, 3], values.0);
+    }
+}
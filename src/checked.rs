@@ -0,0 +1,114 @@
+use core::{fmt, marker::PhantomData};
+use crate::{Packable, PackableError, Direction, ErrorKind, UnpackSource};
+
+/// Checksum algorithm for `Checked` trailers.
+pub trait Checksum{
+    type Output: Packable + Default + PartialEq + fmt::Debug;
+
+    fn checksum(data: &[u8]) -> Self::Output;
+}
+
+/// CRC-32 as used by zlib and Ethernet (reflected, polynomial 0x04c11db7).
+pub struct Crc32;
+
+impl Checksum for Crc32{
+    type Output = u32;
+
+    fn checksum(data: &[u8]) -> u32{
+        let mut crc = !0u32;
+        for byte in data{
+            crc ^= *byte as u32;
+            for _ in 0..8{
+                crc = (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg());
+            }
+        }
+        !crc
+    }
+}
+
+/// A `T` followed by a `C` checksum of all of its packed bytes, so a trailer that
+/// covers the whole preceding frame needs no offsets. Unpacking fails with
+/// `ChecksumMismatch` when the trailer does not match.
+pub struct Checked<T, C>{
+    pub value: T,
+    checksum: PhantomData<C>,
+}
+
+impl<T, C> Checked<T, C>{
+    pub fn new(value: T) -> Self{
+        Checked { value, checksum: PhantomData }
+    }
+}
+
+impl<T: Default, C> Default for Checked<T, C>{
+    fn default() -> Self {
+        Checked::new(T::default())
+    }
+}
+
+impl<T: PartialEq, C> PartialEq for Checked<T, C>{
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for Checked<T, C>{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Checked").field(&self.value).finish()
+    }
+}
+
+impl<T: Packable, C: Checksum> Packable for Checked<T, C>{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        let mut data = self.value.pack(litle_endian);
+        data.extend(C::checksum(&data).pack(litle_endian));
+        data
+    }
+
+    fn size(&self) -> usize {
+        self.value.size() + C::Output::default().size()
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        let mut source = buf;
+        let len = source.unpack_into(&mut self.value, litle_endian)?;
+        let mut expected = C::Output::default();
+        source.unpack_into(&mut expected, litle_endian)?;
+        let actual = C::checksum(&buf[..len]);
+        if actual != expected{
+            return Err(PackableError { 
+                error_kind: ErrorKind::ChecksumMismatch, 
+                data: format!("checksum {:?} of {} bytes does not match trailer {:?}", actual, len, expected),
+                direction: Some(Direction::Unpack),
+            })
+        }
+        Ok(buf.len() - source.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, Packable, from_bytes};
+    use super::{Checked, Checksum, Crc32};
+
+    #[test]
+    fn test_checked(){
+        assert_eq!(0xcbf4_3926, Crc32::checksum(b"123456789"));
+
+        let frame = Checked::<_, Crc32>::new((0x31u8, *b"23456789"));
+        let data = frame.pack(false);
+        assert_eq!([0xcb, 0xf4, 0x39, 0x26], data[9..]);
+        assert_eq!(13, frame.size());
+        assert_eq!(frame, from_bytes(&data, false).unwrap());
+
+        let mut corrupt = data.clone();
+        corrupt[3] ^= 1;
+        let error = from_bytes::<Checked<(u8, [u8; 8]), Crc32>>(&corrupt, false).unwrap_err();
+        assert_eq!(ErrorKind::ChecksumMismatch, error.error_kind);
+    }
+}
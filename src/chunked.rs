@@ -0,0 +1,95 @@
+use core::marker::PhantomData;
+use crate::{Endian, Packable, PackableError, from_bytes};
+
+/// A packed value handed out in pieces, for socket buffers that only expose one
+/// contiguous chunk at a time, such as smoltcp's `send(|buf| ..)`: call `fill` with
+/// each chunk until `is_done`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkedPacker{
+    data: Vec<u8>,
+    sent: usize,
+}
+
+impl ChunkedPacker{
+    pub fn new<T: Packable + ?Sized>(value: &T, endian: impl Into<Endian>) -> Self{
+        let litle_endian = endian.into().is_little();
+        ChunkedPacker { data: value.pack(litle_endian), sent: 0 }
+    }
+
+    /// Copy as much of what is left as fits into `chunk`, returning how much.
+    pub fn fill(&mut self, chunk: &mut [u8]) -> usize{
+        let len = chunk.len().min(self.data.len() - self.sent);
+        chunk[..len].copy_from_slice(&self.data[self.sent..self.sent + len]);
+        self.sent += len;
+        len
+    }
+
+    pub fn remaining(&self) -> usize{
+        self.data.len() - self.sent
+    }
+
+    pub fn is_done(&self) -> bool{
+        self.remaining() == 0
+    }
+}
+
+/// Collects the bytes of fixed-size `T`s from a receive buffer one chunk at a time,
+/// such as smoltcp's `recv(|buf| ..)`, unpacking each value once it is complete.
+#[derive(Debug, Clone)]
+pub struct ChunkedUnpacker<T>{
+    buffer: Vec<u8>,
+    size: usize,
+    litle_endian: bool,
+    value: PhantomData<T>,
+}
+
+impl<T: Packable + Default> ChunkedUnpacker<T>{
+    pub fn new(endian: impl Into<Endian>) -> Self{
+        let litle_endian = endian.into().is_little();
+        let size = T::default().size();
+        ChunkedUnpacker { buffer: Vec::with_capacity(size), size, litle_endian, value: PhantomData }
+    }
+
+    /// Take bytes from `chunk` up to the end of the current value. Returns how many
+    /// were taken, which is what the receive buffer should dequeue, and the value if
+    /// it is now complete. Call again with the rest of the chunk for the next one.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<(usize, Option<T>), PackableError>{
+        let len = chunk.len().min(self.size - self.buffer.len());
+        self.buffer.extend_from_slice(&chunk[..len]);
+        if self.buffer.len() < self.size{
+            return Ok((len, None))
+        }
+        let value = from_bytes(&self.buffer, self.litle_endian);
+        self.buffer.clear();
+        Ok((len, Some(value?)))
+    }
+
+    /// Bytes of the value in progress received so far.
+    pub fn pending(&self) -> usize{
+        self.buffer.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChunkedPacker, ChunkedUnpacker};
+
+    #[test]
+    fn test_chunked(){
+        let mut packer = ChunkedPacker::new(&(0x0102u16, 0x03040506u32), false);
+        let mut first = [0; 4];
+        assert_eq!(4, packer.fill(&mut first));
+        let mut second = [0; 8];
+        assert_eq!(2, packer.fill(&mut second));
+        assert!(packer.is_done());
+        assert_eq!(0, packer.fill(&mut second));
+
+        let mut unpacker = ChunkedUnpacker::<(u16, u32)>::new(false);
+        assert_eq!((4, None), unpacker.feed(&first).unwrap());
+        assert_eq!(4, unpacker.pending());
+        let chunk = [5, 6, 0, 7, 0, 0];
+        assert_eq!((2, Some((0x0102, 0x03040506))), unpacker.feed(&chunk).unwrap());
+        assert_eq!((4, None), unpacker.feed(&chunk[2..]).unwrap());
+        assert_eq!(4, unpacker.pending());
+    }
+}
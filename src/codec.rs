@@ -0,0 +1,590 @@
+use std::{collections::HashMap, io::{self, Read, Write}, sync::Mutex};
+use crate::{Endian, Packable, PackableError, Direction, ErrorKind, buffer_length_error, default_endianness};
+use crate::escape::{EscapeMap, Unescaper};
+
+/// Width of the length prefix written in front of each frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthPrefix{
+    U8,
+    U16,
+    U32,
+    /// MQTT "remaining length": 7 bits per byte, least significant first, with the
+    /// high bit set on every byte but the last. Endianness does not apply.
+    Remaining,
+}
+
+/// Largest length a `LengthPrefix::Remaining` can carry in its 4 bytes.
+const REMAINING_MAX: usize = 0x0fff_ffff;
+
+impl LengthPrefix{
+    /// Encoded size of the prefix; the maximum for `Remaining`.
+    pub fn size(&self) -> usize{
+        match self{
+            LengthPrefix::U8 => 1,
+            LengthPrefix::U16 => 2,
+            LengthPrefix::U32 => 4,
+            LengthPrefix::Remaining => 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Framing{
+    #[default]
+    None,
+    LengthPrefixed(LengthPrefix),
+}
+
+/// Traffic counted by a codec, see `Codec::stats`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats{
+    pub frames_in: u64,
+    pub frames_out: u64,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    /// Failed decodes by kind of error.
+    pub errors: HashMap<ErrorKind, u64>,
+    /// Frames found only after skipping bytes to reach their sync mark.
+    pub resyncs: u64,
+}
+
+/// Cloning copies the counts so far.
+#[derive(Debug, Default)]
+struct Counters(Mutex<Stats>);
+
+impl Counters{
+    fn update(&self, update: impl FnOnce(&mut Stats)){
+        update(&mut self.0.lock().unwrap_or_else(|error| error.into_inner()))
+    }
+
+    fn snapshot(&self) -> Stats{
+        self.0.lock().unwrap_or_else(|error| error.into_inner()).clone()
+    }
+}
+
+impl Clone for Counters{
+    fn clone(&self) -> Self {
+        Counters(Mutex::new(self.snapshot()))
+    }
+}
+
+/// Encoding options bundled into one handle, created once and shared by the
+/// code that encodes and decodes messages.
+#[derive(Debug, Clone)]
+pub struct Codec{
+    litle_endian: bool,
+    max_size: Option<usize>,
+    strict: bool,
+    pub(crate) framing: Framing,
+    escape: Option<EscapeMap>,
+    sync: Option<Vec<u8>>,
+    counters: Counters,
+}
+
+impl Default for Codec{
+    fn default() -> Self {
+        Codec { 
+            litle_endian: default_endianness(), 
+            max_size: None, 
+            strict: false, 
+            framing: Framing::None, 
+            escape: None, 
+            sync: None, 
+            counters: Counters::default(),
+        }
+    }
+}
+
+impl Codec{
+    pub fn new(endian: impl Into<Endian>) -> Self{
+        let litle_endian = endian.into().is_little();
+        Codec { litle_endian, ..Default::default() }
+    }
+
+    /// Largest payload accepted by `encode` and `decode`, framing excluded.
+    pub fn max_size(mut self, max_size: usize) -> Self{
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// When set, `decode` rejects bytes left over after the message.
+    pub fn strict(mut self, strict: bool) -> Self{
+        self.strict = strict;
+        self
+    }
+
+    pub fn framing(mut self, framing: Framing) -> Self{
+        self.framing = framing;
+        self
+    }
+
+    /// Escape reserved bytes of the whole encoded frame, length prefix included.
+    pub fn escape(mut self, escape: EscapeMap) -> Self{
+        self.escape = Some(escape);
+        self
+    }
+
+    /// Start every frame with `mark`, e.g. a sync word, written ahead of any
+    /// escaping. Decoding skips whatever comes before the next mark, so a reader
+    /// that joins mid-stream or meets garbage picks up at the next frame. An
+    /// empty mark turns syncing off.
+    pub fn sync(mut self, mark: &[u8]) -> Self{
+        self.sync = (!mark.is_empty()).then(|| mark.to_vec());
+        self
+    }
+
+    pub fn is_litle_endian(&self) -> bool{
+        self.litle_endian
+    }
+
+    /// Snapshot of the frames and bytes this codec encoded and decoded, of its
+    /// decode errors and of its resyncs. Give each connection its own codec to
+    /// count them apart.
+    pub fn stats(&self) -> Stats{
+        self.counters.snapshot()
+    }
+
+    /// Count the outcome of decoding one frame of `len` bytes.
+    pub(crate) fn record_decode<T>(&self, result: Result<T, PackableError>, len: usize) -> Result<T, PackableError>{
+        match result{
+            Ok(value) => {
+                self.counters.update(|stats| {
+                    stats.frames_in += 1;
+                    stats.bytes_in += len as u64;
+                });
+                Ok(value)
+            }
+            Err(error) => Err(self.record_error(error)),
+        }
+    }
+
+    pub(crate) fn record_error(&self, error: PackableError) -> PackableError{
+        self.counters.update(|stats| *stats.errors.entry(error.error_kind).or_default() += 1);
+        error
+    }
+
+    fn check_size(&self, size: usize, direction: Direction) -> Result<(), PackableError>{
+        match self.max_size{
+            Some(max_size) if size > max_size => Err(PackableError { 
+                error_kind: ErrorKind::LimitExceeded, 
+                data: format!("{} bytes exceeds the limit of {}", size, max_size),
+                direction: Some(direction),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn encode<T: Packable + ?Sized>(&self, value: &T) -> Result<Vec<u8>, PackableError>{
+        let payload = value.pack(self.litle_endian);
+        self.check_size(payload.len(), Direction::Pack)?;
+        let mut data = match self.framing{
+            Framing::None => Vec::with_capacity(payload.len()),
+            Framing::LengthPrefixed(prefix) => self.pack_length(prefix, payload.len())?,
+        };
+        data.extend(payload);
+        let data = match &self.escape{
+            Some(escape) => escape.escape(&data),
+            None => data,
+        };
+        let data = match &self.sync{
+            Some(mark) => [mark.as_slice(), &data].concat(),
+            None => data,
+        };
+        self.counters.update(|stats| {
+            stats.frames_out += 1;
+            stats.bytes_out += data.len() as u64;
+        });
+        Ok(data)
+    }
+
+    pub fn decode<T: Packable + Default>(&self, data: &[u8]) -> Result<T, PackableError>{
+        self.record_decode(self.decode_frame(data), data.len())
+    }
+
+    fn decode_frame<T: Packable + Default>(&self, data: &[u8]) -> Result<T, PackableError>{
+        let data = match &self.sync{
+            Some(mark) => {
+                let start = data.windows(mark.len()).position(|window| window == mark.as_slice()).ok_or_else(|| PackableError { 
+                    error_kind: ErrorKind::InvalidEncoding, 
+                    data: format!("no sync mark in {} bytes", data.len()),
+                    direction: Some(Direction::Unpack),
+                })?;
+                if start > 0{
+                    self.counters.update(|stats| stats.resyncs += 1);
+                }
+                &data[start + mark.len()..]
+            }
+            None => data,
+        };
+        let unescaped;
+        let data = match &self.escape{
+            Some(escape) => {
+                unescaped = escape.unescape(data)?;
+                unescaped.as_slice()
+            }
+            None => data,
+        };
+        let mut source = data;
+        let payload = match self.framing{
+            Framing::None => data,
+            Framing::LengthPrefixed(prefix) => {
+                let len = self.unpack_length(prefix, &mut source)?;
+                self.check_size(len, Direction::Unpack)?;
+                if source.len() < len{
+                    return Err(buffer_length_error(len, source.len()))
+                }
+                if self.strict && source.len() > len{
+                    return Err(trailing_bytes_error(source.len() - len))
+                }
+                &source[..len]
+            }
+        };
+        self.decode_payload(payload)
+    }
+
+    pub(crate) fn decode_payload<T: Packable + Default>(&self, payload: &[u8]) -> Result<T, PackableError>{
+        self.check_size(payload.len(), Direction::Unpack)?;
+        let mut value = T::default();
+        let size = value.size();
+        if payload.len() < size{
+            return Err(buffer_length_error(size, payload.len()))
+        }
+        value.unpack(&mut payload.to_vec(), self.litle_endian)?;
+        if self.strict && value.size() != payload.len(){
+            return Err(trailing_bytes_error(payload.len() - value.size()))
+        }
+        Ok(value)
+    }
+
+    /// Read the next message from `reader`, or `None` if the stream ends cleanly
+    /// before it. Without framing, messages must have a fixed size.
+    pub fn read<T: Packable + Default, R: Read>(&self, reader: &mut R) -> Result<Option<T>, PackableError>{
+        match self.read_frame(reader, T::default().size()).map_err(|error| self.record_error(error))?{
+            Some((payload, len)) => self.record_decode(self.decode_payload(&payload), len).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Payload of the next frame; `unframed_size` bytes when there is no framing.
+    /// Returns the number of bytes read from `reader` along with it.
+    pub(crate) fn read_frame<R: Read>(&self, reader: &mut R, unframed_size: usize) -> Result<Option<(Vec<u8>, usize)>, PackableError>{
+        let Some((synced, skipped)) = self.read_sync(reader)? else {
+            return Ok(None)
+        };
+        let frame = match &self.escape{
+            Some(map) => {
+                let mut unescaper = Unescaper { inner: &mut *reader, map, consumed: 0 };
+                let frame = self.read_unescaped_frame(&mut unescaper, unframed_size)?;
+                frame.map(|(payload, _)| (payload, unescaper.consumed))
+            }
+            None => self.read_unescaped_frame(reader, unframed_size)?,
+        };
+        if skipped && frame.is_some(){
+            self.counters.update(|stats| stats.resyncs += 1);
+        }
+        Ok(frame.map(|(payload, len)| (payload, synced + len)))
+    }
+
+    /// Read up to and including the next sync mark, returning the bytes read and
+    /// whether any came before the mark, or `None` if the stream ends first.
+    fn read_sync<R: Read>(&self, reader: &mut R) -> Result<Option<(usize, bool)>, PackableError>{
+        let Some(mark) = &self.sync else {
+            return Ok(Some((0, false)))
+        };
+        let mut window = Vec::with_capacity(mark.len());
+        let mut read = 0;
+        while window != *mark{
+            let mut byte = [0];
+            if !read_exact_or_eof(reader, &mut byte)?{
+                return Ok(None)
+            }
+            read += 1;
+            if window.len() == mark.len(){
+                window.remove(0);
+            }
+            window.push(byte[0]);
+        }
+        Ok(Some((read, read > mark.len())))
+    }
+
+    fn read_unescaped_frame<R: Read>(&self, reader: &mut R, unframed_size: usize) -> Result<Option<(Vec<u8>, usize)>, PackableError>{
+        match self.framing{
+            Framing::None => {
+                let mut payload = vec![0; unframed_size];
+                if !read_exact_or_eof(reader, &mut payload)?{
+                    return Ok(None)
+                }
+                Ok(Some((payload, unframed_size)))
+            }
+            Framing::LengthPrefixed(prefix) => {
+                let Some(header) = read_header(reader, prefix)? else {
+                    return Ok(None)
+                };
+                let len = self.unpack_length(prefix, &mut header.as_slice())?;
+                self.check_size(len, Direction::Unpack)?;
+                // The length is untrusted, so the payload grows a chunk at a time as
+                // bytes actually arrive rather than being allocated up front.
+                let mut payload = Vec::with_capacity(len.min(READ_CHUNK));
+                while payload.len() < len{
+                    let start = payload.len();
+                    payload.resize(len.min(start + READ_CHUNK), 0);
+                    if !read_exact_or_eof(reader, &mut payload[start..])?{
+                        return Err(buffer_length_error(len, start))
+                    }
+                }
+                Ok(Some((payload, header.len() + len)))
+            }
+        }
+    }
+
+    pub fn write<T: Packable + ?Sized, W: Write>(&self, writer: &mut W, value: &T) -> Result<(), PackableError>{
+        writer.write_all(&self.encode(value)?).map_err(|error| PackableError::from(error).during(Direction::Pack))?;
+        Ok(())
+    }
+
+    fn pack_length(&self, prefix: LengthPrefix, len: usize) -> Result<Vec<u8>, PackableError>{
+        let out_of_range = || PackableError { 
+            error_kind: ErrorKind::OutOfRange, 
+            data: format!("frame of {} bytes does not fit a {:?} length prefix", len, prefix),
+            direction: Some(Direction::Pack),
+        };
+        Ok(match prefix{
+            LengthPrefix::U8 => u8::try_from(len).map_err(|_| out_of_range())?.pack(self.litle_endian),
+            LengthPrefix::U16 => u16::try_from(len).map_err(|_| out_of_range())?.pack(self.litle_endian),
+            LengthPrefix::U32 => u32::try_from(len).map_err(|_| out_of_range())?.pack(self.litle_endian),
+            LengthPrefix::Remaining if len > REMAINING_MAX => return Err(out_of_range()),
+            LengthPrefix::Remaining => {
+                let mut data = Vec::with_capacity(prefix.size());
+                let mut rest = len;
+                loop{
+                    let byte = (rest & 0x7f) as u8;
+                    rest >>= 7;
+                    if rest == 0{
+                        data.push(byte);
+                        break data
+                    }
+                    data.push(byte | 0x80);
+                }
+            }
+        })
+    }
+
+    fn unpack_length(&self, prefix: LengthPrefix, source: &mut &[u8]) -> Result<usize, PackableError>{
+        Ok(match prefix{
+            LengthPrefix::U8 => read::<u8>(source, self.litle_endian)? as usize,
+            LengthPrefix::U16 => read::<u16>(source, self.litle_endian)? as usize,
+            LengthPrefix::U32 => read::<u32>(source, self.litle_endian)? as usize,
+            LengthPrefix::Remaining => {
+                let mut len = 0;
+                for index in 0..prefix.size(){
+                    let byte = read::<u8>(source, self.litle_endian)?;
+                    len |= ((byte & 0x7f) as usize) << (7 * index);
+                    if byte & 0x80 == 0{
+                        if byte == 0 && index > 0{
+                            return Err(invalid_remaining_error("is not minimally encoded"))
+                        }
+                        return Ok(len)
+                    }
+                }
+                return Err(invalid_remaining_error("is longer than 4 bytes"))
+            }
+        })
+    }
+}
+
+/// Re-encode every message of `reader` from one codec's format into another's,
+/// returning the number of messages copied.
+pub fn transcode<T, R, W>(reader: &mut R, from: &Codec, writer: &mut W, to: &Codec) -> Result<usize, PackableError>
+where
+    T: Packable + Default,
+    R: Read,
+    W: Write,
+{
+    let mut count = 0;
+    while let Some(message) = from.read::<T, R>(reader)?{
+        to.write(writer, &message)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Most bytes a length-prefixed payload grows by before they are read.
+const READ_CHUNK: usize = 64 * 1024;
+
+/// Like `read_exact`, but returns `false` instead of failing when the stream is
+/// already at its end.
+fn read_exact_or_eof<R: Read>(reader: &mut R, buffer: &mut [u8]) -> Result<bool, PackableError>{
+    let mut filled = 0;
+    while filled < buffer.len(){
+        match reader.read(&mut buffer[filled..]){
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(buffer_length_error(buffer.len(), filled)),
+            Ok(count) => filled += count,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(PackableError::from(error).during(Direction::Unpack)),
+        }
+    }
+    Ok(true)
+}
+
+/// Bytes of the next length prefix, or `None` if the stream ends cleanly before it.
+fn read_header<R: Read>(reader: &mut R, prefix: LengthPrefix) -> Result<Option<Vec<u8>>, PackableError>{
+    if prefix != LengthPrefix::Remaining{
+        let mut header = vec![0; prefix.size()];
+        return Ok(read_exact_or_eof(reader, &mut header)?.then_some(header))
+    }
+    let mut header = Vec::with_capacity(prefix.size());
+    loop{
+        let mut byte = [0];
+        if !read_exact_or_eof(reader, &mut byte)?{
+            if header.is_empty(){
+                return Ok(None)
+            }
+            return Err(buffer_length_error(header.len() + 1, header.len()))
+        }
+        header.push(byte[0]);
+        if byte[0] & 0x80 == 0 || header.len() == prefix.size(){
+            return Ok(Some(header))
+        }
+    }
+}
+
+fn read<T: Packable + Default>(source: &mut &[u8], litle_endian: bool) -> Result<T, PackableError>{
+    let mut value = T::default();
+    crate::unpack!(litle_endian, source, value)?;
+    Ok(value)
+}
+
+fn trailing_bytes_error(count: usize) -> PackableError{
+    PackableError { 
+        error_kind: ErrorKind::TrailingBytes, 
+        data: format!("{} bytes left after the message", count),
+        direction: Some(Direction::Unpack),
+    }
+}
+
+fn invalid_remaining_error(message: &str) -> PackableError{
+    PackableError { 
+        error_kind: ErrorKind::InvalidEncoding, 
+        data: format!("remaining length {}", message),
+        direction: Some(Direction::Unpack),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ErrorKind;
+    use super::{Codec, Framing, LengthPrefix, transcode};
+
+    #[test]
+    fn test_codec_framing(){
+        let codec = Codec::new(true).framing(Framing::LengthPrefixed(LengthPrefix::U16));
+        let data = codec.encode(&0x01020304u32).unwrap();
+        assert_eq!(vec![4, 0, 4, 3, 2, 1], data);
+        assert_eq!(0x01020304u32, codec.decode::<u32>(&data).unwrap());
+
+        let error = codec.decode::<u32>(&data[..5]).unwrap_err();
+        assert!(matches!(error.error_kind, ErrorKind::BufferLengthError));
+    }
+
+    #[test]
+    fn test_codec_limits(){
+        let codec = Codec::new(false).max_size(2);
+        assert_eq!(vec![0, 1], codec.encode(&1u16).unwrap());
+        let error = codec.encode(&1u32).unwrap_err();
+        assert!(matches!(error.error_kind, ErrorKind::LimitExceeded));
+
+        let codec = Codec::new(false).strict(true);
+        assert_eq!(1u16, Codec::new(false).decode::<u16>(&[0, 1, 2]).unwrap());
+        let error = codec.decode::<u16>(&[0, 1, 2]).unwrap_err();
+        assert!(matches!(error.error_kind, ErrorKind::TrailingBytes));
+
+        let codec = Codec::new(false).framing(Framing::LengthPrefixed(LengthPrefix::U8));
+        let error = codec.encode(&[0u8; 256]).unwrap_err();
+        assert!(matches!(error.error_kind, ErrorKind::OutOfRange));
+    }
+
+    #[test]
+    fn test_codec_remaining_length(){
+        let codec = Codec::new(false).framing(Framing::LengthPrefixed(LengthPrefix::Remaining));
+        let data = codec.encode(&[7u8; 321]).unwrap();
+        assert_eq!([0xc1, 0x02, 7], data[..3]);
+        assert_eq!([7u8; 321], codec.decode::<crate::Bytes<321>>(&data).unwrap().0);
+        assert_eq!(Some(0x0102u16), codec.read(&mut &[2u8, 1, 2][..]).unwrap());
+
+        let error = codec.decode::<u8>(&[0x80, 0x00, 1]).unwrap_err();
+        assert_eq!(ErrorKind::InvalidEncoding, error.error_kind);
+        let error = codec.decode::<u8>(&[0xff, 0xff, 0xff, 0xff, 1]).unwrap_err();
+        assert_eq!(ErrorKind::InvalidEncoding, error.error_kind);
+        let error = codec.read::<u16, _>(&mut &[0x80u8][..]).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+    }
+
+    #[test]
+    fn test_codec_stats(){
+        let codec = Codec::new(false).framing(Framing::LengthPrefixed(LengthPrefix::U8)).max_size(2);
+        codec.encode(&1u16).unwrap();
+        let mut input = &[2u8, 0, 1, 4, 0][..];
+        assert_eq!(Some(1u16), codec.read(&mut input).unwrap());
+        codec.read::<u16, _>(&mut input).unwrap_err();
+        codec.decode::<u16>(&[2, 0]).unwrap_err();
+
+        let stats = codec.clone().stats();
+        assert_eq!((1, 1, 3, 3), (stats.frames_in, stats.frames_out, stats.bytes_in, stats.bytes_out));
+        assert_eq!(Some(&1), stats.errors.get(&ErrorKind::LimitExceeded));
+        assert_eq!(Some(&1), stats.errors.get(&ErrorKind::BufferLengthError));
+        assert_eq!(0, stats.resyncs);
+    }
+
+    #[test]
+    fn test_codec_sync(){
+        let codec = Codec::new(false).sync(&[0xaa, 0x55]).framing(Framing::LengthPrefixed(LengthPrefix::U8));
+        let frame = codec.encode(&7u8).unwrap();
+        assert_eq!(vec![0xaa, 0x55, 1, 7], frame);
+        assert_eq!(7u8, codec.decode(&frame).unwrap());
+        assert_eq!(0, codec.stats().resyncs);
+
+        let mut input = &[0xaa, 0x00, 0x55, 0xaa, 0x55, 1, 8, 0xaa, 0x55, 1, 9][..];
+        assert_eq!(Some(8u8), codec.read(&mut input).unwrap());
+        assert_eq!(Some(9u8), codec.read(&mut input).unwrap());
+        assert_eq!(None, codec.read::<u8, _>(&mut input).unwrap());
+        assert_eq!(5u8, codec.decode(&[3, 0xaa, 0x55, 1, 5]).unwrap());
+        let stats = codec.stats();
+        assert_eq!(2, stats.resyncs);
+        assert_eq!(20, stats.bytes_in);
+
+        let error = codec.decode::<u8>(&[1, 2, 3]).unwrap_err();
+        assert_eq!(ErrorKind::InvalidEncoding, error.error_kind);
+
+        let codec = Codec::new(false).sync(&[]);
+        assert_eq!(vec![7], codec.encode(&7u8).unwrap());
+        assert_eq!(7u8, codec.decode(&[7]).unwrap());
+        assert_eq!(Some(8u8), codec.read(&mut &[8][..]).unwrap());
+    }
+
+    #[test]
+    fn test_codec_read_untrusted_length(){
+        let codec = Codec::new(false).framing(Framing::LengthPrefixed(LengthPrefix::U32));
+        let (result, allocated) = crate::allocated_during(|| codec.read::<Vec<u8>, _>(&mut &[0xff, 0xff, 0xff, 0xff, 1, 2][..]));
+        assert_eq!(ErrorKind::BufferLengthError, result.unwrap_err().error_kind);
+        assert!(allocated < 1 << 20);
+    }
+
+    #[test]
+    fn test_transcode(){
+        let from = Codec::new(false);
+        let to = Codec::new(true).framing(Framing::LengthPrefixed(LengthPrefix::U8));
+        let mut output = Vec::new();
+        let count = transcode::<u16, _, _>(&mut &[0u8, 1, 0, 2][..], &from, &mut output, &to).unwrap();
+        assert_eq!(2, count);
+        assert_eq!(vec![2, 1, 0, 2, 2, 0], output);
+
+        let mut input = output.as_slice();
+        assert_eq!(Some(1u16), to.read(&mut input).unwrap());
+        assert_eq!(Some(2u16), to.read(&mut input).unwrap());
+        assert_eq!(None, to.read::<u16, _>(&mut input).unwrap());
+
+        let error = transcode::<u16, _, _>(&mut &[0u8, 1, 0][..], &from, &mut Vec::new(), &to).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+    }
+}
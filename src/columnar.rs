@@ -0,0 +1,9 @@
+/// Struct-of-arrays layout for a batch of records, for exports that compress and
+/// scan better when each field is stored contiguously. `#[derive(Columnar)]`
+/// implements it for structs whose fields are `Packable`.
+pub trait Columnar: Sized + Default{
+    fn pack_columns(records: &[Self], endian: impl Into<crate::Endian>) -> Vec<u8>;
+
+    /// Unpack `count` records packed by `pack_columns`.
+    fn unpack_columns(data: &[u8], count: usize, endian: impl Into<crate::Endian>) -> Result<Vec<Self>, crate::PackableError>;
+}
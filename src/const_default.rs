@@ -0,0 +1,7 @@
+/// A default value that can be built in a const context, written out by hand. With
+/// `#[derive(DefaultPacked)]` its packed bytes become the associated consts
+/// `DEFAULT_PACKED`, big-endian, and `DEFAULT_PACKED_LE`, so fixed frames such as
+/// heartbeats are baked in at compile time.
+pub trait ConstDefault: Sized{
+    const DEFAULT: Self;
+}
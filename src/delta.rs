@@ -0,0 +1,95 @@
+use crate::{Packable, PackableError, Vlq};
+
+/// A signed integer zig-zag mapped (0, -1, 1, -2, ... to 0, 1, 2, 3, ...) and
+/// packed as a `Vlq`, so values of small magnitude take few bytes either way.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ZigZag(pub i64);
+
+impl ZigZag{
+    pub fn encode(value: i64) -> u64{
+        ((value << 1) ^ (value >> 63)) as u64
+    }
+
+    pub fn decode(value: u64) -> i64{
+        (value >> 1) as i64 ^ -((value & 1) as i64)
+    }
+
+    fn wire(&self) -> Vlq<10>{
+        match Vlq::new(ZigZag::encode(self.0)){
+            Ok(wire) => wire,
+            Err(_) => unreachable!("10 groups of 7 bits hold any u64"),
+        }
+    }
+}
+
+impl Packable for ZigZag{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        self.wire().pack(litle_endian)
+    }
+
+    fn size(&self) -> usize {
+        self.wire().size()
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        let mut wire = Vlq::<10>::default();
+        let consumed = wire.unpack_from(buf, litle_endian)?;
+        self.0 = ZigZag::decode(wire.get());
+        Ok(consumed)
+    }
+}
+
+/// Per-stream state to send a slowly changing field, e.g. a timestamp or counter,
+/// as the difference from the same field of the previous record. The encoder and
+/// the decoder each keep one and must see the records in the same order.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Delta{
+    previous: i64,
+}
+
+impl Delta{
+    /// Start from `first` instead of zero.
+    pub fn new(first: i64) -> Self{
+        Delta { previous: first }
+    }
+
+    pub fn encode(&mut self, value: i64) -> ZigZag{
+        let delta = value.wrapping_sub(self.previous);
+        self.previous = value;
+        ZigZag(delta)
+    }
+
+    pub fn decode(&mut self, delta: ZigZag) -> i64{
+        self.previous = self.previous.wrapping_add(delta.0);
+        self.previous
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{to_bytes, from_bytes};
+    use super::{Delta, ZigZag};
+
+    #[test]
+    fn test_zig_zag(){
+        assert_eq!([0, 1, 2, 3, 4], [0, -1, 1, -2, 2].map(ZigZag::encode));
+        assert_eq!(u64::MAX, ZigZag::encode(i64::MIN));
+        assert_eq!(i64::MIN, ZigZag::decode(u64::MAX));
+        assert_eq!(vec![0x01], to_bytes(&ZigZag(-1), false));
+        assert_eq!(ZigZag(i64::MIN), from_bytes(&to_bytes(&ZigZag(i64::MIN), false), false).unwrap());
+    }
+
+    #[test]
+    fn test_delta(){
+        let (mut encoder, mut decoder) = (Delta::new(1_700_000_000), Delta::new(1_700_000_000));
+        let deltas = [1_700_000_010, 1_700_000_020, 1_700_000_015].map(|value| encoder.encode(value));
+        assert_eq!([ZigZag(10), ZigZag(10), ZigZag(-5)], deltas);
+        assert_eq!(3, deltas.iter().map(|delta| to_bytes(delta, false).len()).sum::<usize>());
+        assert_eq!([1_700_000_010, 1_700_000_020, 1_700_000_015], deltas.map(|delta| decoder.decode(delta)));
+    }
+}
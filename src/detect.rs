@@ -0,0 +1,115 @@
+use crate::{Codec, ErrorKind};
+
+type Check = Box<dyn Fn(&[u8]) -> Option<bool> + Send + Sync>;
+
+/// One test a stream must pass to be recognized as a protocol.
+pub enum Signature{
+    /// These bytes at `offset` into the stream, e.g. a sync word or magic number.
+    Magic { offset: usize, bytes: Vec<u8> },
+    /// The codec's length prefix reads and the whole frame fits its `max_size`.
+    Length,
+    /// Custom test returning `None` while it needs more bytes.
+    Check(Check),
+}
+
+impl Signature{
+    pub fn check<F: Fn(&[u8]) -> Option<bool> + Send + Sync + 'static>(check: F) -> Self{
+        Signature::Check(Box::new(check))
+    }
+
+    fn test(&self, codec: &Codec, data: &[u8]) -> Option<bool>{
+        match self{
+            Signature::Magic { offset, bytes } => {
+                let data = data.get(*offset..).unwrap_or_default();
+                match data.len() < bytes.len(){
+                    true if bytes.starts_with(data) => None,
+                    true => Some(false),
+                    false => Some(data.starts_with(bytes)),
+                }
+            }
+            Signature::Length => match codec.read_frame(&mut &data[..], 0){
+                Ok(Some(_)) => Some(true),
+                Ok(None) => None,
+                Err(error) if error.error_kind == ErrorKind::BufferLengthError => None,
+                Err(_) => Some(false),
+            },
+            Signature::Check(check) => check(data),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Detection<'a, K>{
+    Matched(&'a K, &'a Codec),
+    /// A protocol of higher priority than any match can still neither be ruled out
+    /// nor confirmed.
+    Incomplete,
+    Unknown,
+}
+
+/// Front end for ports shared by several protocols, e.g. device generations: the
+/// start of a stream is tested against each registered protocol in priority order.
+pub struct Detector<K>{
+    protocols: Vec<(K, Codec, Vec<Signature>)>,
+}
+
+impl<K> Default for Detector<K>{
+    fn default() -> Self {
+        Detector { protocols: Vec::new() }
+    }
+}
+
+impl<K> Detector<K>{
+    pub fn new() -> Self{
+        Detector::default()
+    }
+
+    /// Register a protocol after the existing ones, recognized when the stream
+    /// passes all of `signatures`.
+    pub fn with<S: IntoIterator<Item = Signature>>(mut self, key: K, codec: Codec, signatures: S) -> Self{
+        self.protocols.push((key, codec, signatures.into_iter().collect()));
+        self
+    }
+
+    pub fn detect(&self, data: &[u8]) -> Detection<'_, K>{
+        for (key, codec, signatures) in &self.protocols{
+            let mut complete = true;
+            for signature in signatures{
+                match signature.test(codec, data){
+                    Some(true) => {}
+                    Some(false) => {
+                        complete = false;
+                        break
+                    }
+                    None => return Detection::Incomplete,
+                }
+            }
+            if complete{
+                return Detection::Matched(key, codec)
+            }
+        }
+        Detection::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Codec, Framing, LengthPrefix};
+    use super::{Detection, Detector, Signature};
+
+    #[test]
+    fn test_detector(){
+        let v2 = Codec::new(false).framing(Framing::LengthPrefixed(LengthPrefix::U16)).max_size(16);
+        let v1 = Codec::new(false).framing(Framing::LengthPrefixed(LengthPrefix::U8));
+        let detector = Detector::new()
+            .with("v2", v2, [Signature::Magic { offset: 2, bytes: vec![0xaa, 0x55] }, Signature::Length])
+            .with("v1", v1, [Signature::Length, Signature::check(|data| data.get(1).map(|&kind| kind < 0x80))]);
+
+        assert!(matches!(detector.detect(&[0x01]), Detection::Incomplete));
+        assert!(matches!(detector.detect(&[0x00, 0x03, 0xaa, 0x55]), Detection::Incomplete));
+        assert!(matches!(detector.detect(&[0x00, 0x03, 0xaa, 0x55, 0x01]), Detection::Matched(&"v2", _)));
+        assert!(matches!(detector.detect(&[0x00, 0x20, 0xaa, 0x55]), Detection::Matched(&"v1", _)));
+        assert!(matches!(detector.detect(&[0x01, 0x10, 0x00]), Detection::Matched(&"v1", _)));
+        assert!(matches!(detector.detect(&[0x01, 0x90, 0x00]), Detection::Unknown));
+    }
+}
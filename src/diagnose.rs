@@ -0,0 +1,57 @@
+use core::fmt;
+use crate::{Direction, Endian, ErrorKind, Packable, PackableError, from_bytes};
+
+fn endian_name(litle_endian: bool) -> &'static str{
+    if litle_endian { "little-endian" } else { "big-endian" }
+}
+
+/// `from_bytes` followed by a sanity check (magic number, version, ranges). When
+/// decoding or the check fails, the input is tried again with the opposite byte
+/// order; if that passes, the error is an `EndiannessMismatch` that says so, since a
+/// misconfigured endianness otherwise looks like garbage data.
+pub fn from_bytes_validated<T, F>(data: &[u8], endian: impl Into<Endian>, validate: F) -> Result<T, PackableError>
+where
+    T: Packable + Default + fmt::Debug,
+    F: Fn(&T) -> bool,
+{
+    let litle_endian = endian.into().is_little();
+    let error = match from_bytes::<T>(data, litle_endian){
+        Ok(value) if validate(&value) => return Ok(value),
+        Ok(value) => PackableError { 
+            error_kind: ErrorKind::InvalidValue, 
+            data: format!("{:?} failed validation", value),
+            direction: Some(Direction::Unpack),
+        },
+        Err(error) => error,
+    };
+    match from_bytes::<T>(data, !litle_endian){
+        Ok(value) if validate(&value) => Err(PackableError { 
+            error_kind: ErrorKind::EndiannessMismatch, 
+            data: format!("{} ({}); value decodes correctly with opposite byte order ({}) as {:?}",
+                error.data, endian_name(litle_endian), endian_name(!litle_endian), value),
+            direction: Some(Direction::Unpack),
+        }),
+        _ => Err(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ErrorKind;
+    use super::from_bytes_validated;
+
+    #[test]
+    fn test_from_bytes_validated(){
+        let is_magic = |magic: &u32| *magic == 0xcafe_0001;
+        assert_eq!(0xcafe_0001, from_bytes_validated(&[0xca, 0xfe, 0, 1], false, is_magic).unwrap());
+
+        let error = from_bytes_validated(&[0xca, 0xfe, 0, 1], true, is_magic).unwrap_err();
+        assert_eq!(ErrorKind::EndiannessMismatch, error.error_kind);
+        assert!(error.data.contains("opposite byte order (big-endian)"));
+
+        let error = from_bytes_validated(&[0, 0, 0, 0], true, is_magic).unwrap_err();
+        assert_eq!(ErrorKind::InvalidValue, error.error_kind);
+        let error = from_bytes_validated(&[0, 0], true, is_magic).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+    }
+}
@@ -0,0 +1,124 @@
+use std::io::{self, Write};
+use crate::{Packable, PackableError, default_endianness};
+
+/// Byte order to pack with. The trait still takes a `bool` (`true` for little
+/// endian); `Endian` converts to and from it, and the macros, `to_bytes` /
+/// `from_bytes` and the other functions and constructors that take a byte order
+/// accept either, so call sites can say what they mean:
+///
+/// ```
+/// use packable::{Endian, pack};
+///
+/// assert_eq!(vec![0x12, 0x34], pack!(Endian::Big, 0x1234u16));
+/// assert_eq!(pack!(true, 0x1234u16), pack!(Endian::Little, 0x1234u16));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endian{
+    Little,
+    Big,
+    /// The byte order of the target, for data that never leaves the machine.
+    Native,
+    /// Whatever `set_default_endianness` last chose, read at each call.
+    Global,
+}
+
+impl Endian{
+    pub fn is_little(self) -> bool{
+        match self{
+            Endian::Little => true,
+            Endian::Big => false,
+            Endian::Native => cfg!(target_endian = "little"),
+            Endian::Global => default_endianness(),
+        }
+    }
+}
+
+impl From<bool> for Endian{
+    fn from(litle_endian: bool) -> Self {
+        if litle_endian { Endian::Little } else { Endian::Big }
+    }
+}
+
+impl From<Endian> for bool{
+    fn from(endian: Endian) -> Self {
+        endian.is_little()
+    }
+}
+
+macro_rules! impl_fixed_endian {
+    ( $( $(#[$doc:meta])* $name:ident => $litle_endian:expr ),* ) => {
+        $(
+            $(#[$doc])*
+            #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            pub struct $name<T>(pub T);
+
+            impl<T> From<T> for $name<T>{
+                fn from(value: T) -> Self {
+                    $name(value)
+                }
+            }
+
+            impl<T: Packable> Packable for $name<T>{
+                fn pack(&self, _litle_endian: bool) -> Vec<u8> {
+                    self.0.pack($litle_endian)
+                }
+
+                fn pack_into(&self, out: &mut Vec<u8>, _litle_endian: bool) {
+                    self.0.pack_into(out, $litle_endian)
+                }
+
+                fn pack_to_writer<W: Write + ?Sized>(&self, writer: &mut W, _litle_endian: bool) -> io::Result<()> {
+                    self.0.pack_to_writer(writer, $litle_endian)
+                }
+
+                fn size(&self) -> usize {
+                    self.0.size()
+                }
+
+                fn unpack(&mut self, data: &mut Vec<u8>, _litle_endian: bool) -> Result<(), PackableError> {
+                    self.0.unpack(data, $litle_endian)
+                }
+
+                fn unpack_from(&mut self, buf: &[u8], _litle_endian: bool) -> Result<usize, PackableError> {
+                    self.0.unpack_from(buf, $litle_endian)
+                }
+            }
+        )*
+    };
+}
+
+impl_fixed_endian!(
+    /// Always packs `T` little-endian, whatever the caller asks for, e.g. for a
+    /// vendor blob inside a big-endian frame.
+    Le => true,
+    /// Always packs `T` big-endian, whatever the caller asks for.
+    Be => false
+);
+
+#[cfg(test)]
+mod tests {
+    use crate::from_bytes;
+    use super::{Be, Endian, Le};
+
+    #[test]
+    fn test_endian(){
+        assert_eq!(Endian::Big, Endian::from(false));
+        assert!(bool::from(Endian::Little));
+        assert_eq!(cfg!(target_endian = "little"), Endian::Native.is_little());
+        assert_eq!(vec![0x34, 0x12], pack!(Endian::Little, 0x1234u16));
+        assert_eq!(0x1234u16.to_ne_bytes().to_vec(), pack!(Endian::Native, 0x1234u16));
+        let mut value = 0u16;
+        unpack!(Endian::Big, &mut vec![0x12, 0x34], value).unwrap();
+        assert_eq!(0x1234, value);
+        assert_eq!(0x3412u16, from_bytes(&[0x12, 0x34], Endian::Little).unwrap());
+    }
+
+    #[test]
+    fn test_fixed_endian(){
+        let header = (0x0102u16, Le(0x0304u16), Be(0x0506u16));
+        assert_eq!(vec![1, 2, 4, 3, 5, 6], pack!(false, header));
+        assert_eq!(vec![2, 1, 4, 3, 5, 6], pack!(true, header));
+        assert_eq!(header, from_bytes(&[1, 2, 4, 3, 5, 6], false).unwrap());
+        assert_eq!(Le(vec![1u16]), from_bytes(&[1, 0, 0, 0, 1, 0], false).unwrap());
+    }
+}
@@ -0,0 +1,155 @@
+use std::io::{self, Read};
+use crate::{Direction, ErrorKind, PackableError};
+
+/// Byte stuffing for links that reserve some byte values: each reserved byte is
+/// sent as the escape byte followed by a replacement code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EscapeMap{
+    escape: u8,
+    pairs: Vec<(u8, u8)>,
+}
+
+impl EscapeMap{
+    /// A map that only escapes the escape byte itself, as `code`.
+    pub fn new(escape: u8, code: u8) -> Self{
+        EscapeMap { escape, pairs: vec![(escape, code)] }
+    }
+
+    /// Also send `byte` as the escape byte followed by `code`.
+    pub fn with(mut self, byte: u8, code: u8) -> Self{
+        self.pairs.retain(|(raw, _)| *raw != byte);
+        self.pairs.push((byte, code));
+        self
+    }
+
+    /// HDLC/PPP control-character escaping: 0x7E, 0x7D, XON and XOFF are sent as
+    /// 0x7D followed by the byte xor 0x20.
+    pub fn hdlc() -> Self{
+        EscapeMap::new(0x7d, 0x5d).with(0x7e, 0x5e).with(0x11, 0x31).with(0x13, 0x33)
+    }
+
+    fn code(&self, byte: u8) -> Option<u8>{
+        self.pairs.iter().find(|(raw, _)| *raw == byte).map(|(_, code)| *code)
+    }
+
+    fn raw(&self, code: u8) -> Result<u8, PackableError>{
+        self.pairs.iter().find(|(_, escaped)| *escaped == code).map(|(raw, _)| *raw).ok_or_else(|| PackableError { 
+            error_kind: ErrorKind::InvalidEncoding, 
+            data: format!("unknown escape code {:#04x}", code),
+            direction: Some(Direction::Unpack),
+        })
+    }
+
+    pub fn escape(&self, data: &[u8]) -> Vec<u8>{
+        let mut escaped = Vec::with_capacity(data.len());
+        for byte in data{
+            match self.code(*byte){
+                Some(code) => escaped.extend([self.escape, code]),
+                None => escaped.push(*byte),
+            }
+        }
+        escaped
+    }
+
+    pub fn unescape(&self, data: &[u8]) -> Result<Vec<u8>, PackableError>{
+        let mut raw = Vec::with_capacity(data.len());
+        let mut bytes = data.iter();
+        while let Some(byte) = bytes.next(){
+            if *byte != self.escape{
+                raw.push(*byte);
+                continue
+            }
+            match bytes.next(){
+                Some(code) => raw.push(self.raw(*code)?),
+                None => return Err(PackableError { 
+                    error_kind: ErrorKind::BufferLengthError, 
+                    data: "input ends inside an escape sequence".to_string(),
+                    direction: Some(Direction::Unpack),
+                }),
+            }
+        }
+        Ok(raw)
+    }
+}
+
+/// Reader adapter undoing an `EscapeMap`, counting the escaped bytes it consumed.
+pub(crate) struct Unescaper<'a, R>{
+    pub(crate) inner: &'a mut R,
+    pub(crate) map: &'a EscapeMap,
+    pub(crate) consumed: usize,
+}
+
+impl<R: Read> Unescaper<'_, R>{
+    fn next_byte(&mut self) -> io::Result<Option<u8>>{
+        let mut byte = [0];
+        loop{
+            match self.inner.read(&mut byte){
+                Ok(0) => return Ok(None),
+                Ok(_) => {
+                    self.consumed += 1;
+                    return Ok(Some(byte[0]))
+                }
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
+
+impl<R: Read> Read for Unescaper<'_, R>{
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let mut filled = 0;
+        while filled < buffer.len(){
+            let Some(byte) = self.next_byte()? else {
+                break
+            };
+            buffer[filled] = if byte == self.map.escape{
+                match self.next_byte()?{
+                    Some(code) => self.map.raw(code)?,
+                    // A dangling escape reads as the end of the input, which callers
+                    // already treat as an incomplete frame.
+                    None => break,
+                }
+            }
+            else{
+                byte
+            };
+            filled += 1;
+        }
+        Ok(filled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Codec, ErrorKind, Framing, LengthPrefix, RingBuffer};
+    use super::EscapeMap;
+
+    #[test]
+    fn test_escape_map(){
+        let map = EscapeMap::hdlc();
+        let escaped = map.escape(&[0x01, 0x7e, 0x7d, 0x11]);
+        assert_eq!(vec![0x01, 0x7d, 0x5e, 0x7d, 0x5d, 0x7d, 0x31], escaped);
+        assert_eq!(vec![0x01, 0x7e, 0x7d, 0x11], map.unescape(&escaped).unwrap());
+        assert_eq!(ErrorKind::InvalidEncoding, map.unescape(&[0x7d, 0x00]).unwrap_err().error_kind);
+        assert_eq!(ErrorKind::BufferLengthError, map.unescape(&[0x7d]).unwrap_err().error_kind);
+    }
+
+    #[test]
+    fn test_codec_escape(){
+        let codec = Codec::new(false).framing(Framing::LengthPrefixed(LengthPrefix::U8)).escape(EscapeMap::hdlc());
+        let data = codec.encode(&0x7e11u16).unwrap();
+        assert_eq!(vec![2, 0x7d, 0x5e, 0x7d, 0x31], data);
+        assert_eq!(0x7e11u16, codec.decode(&data).unwrap());
+
+        let mut ring = RingBuffer::new(8);
+        ring.extend(&data[..2]);
+        assert_eq!(None, ring.decode::<u16>(&codec).unwrap());
+        ring.extend(&data[2..]);
+        assert_eq!(Some(0x7e11), ring.decode::<u16>(&codec).unwrap());
+        assert!(ring.is_empty());
+
+        let error = Codec::new(false).escape(EscapeMap::hdlc()).read::<u8, _>(&mut &[0x7d, 0x00][..]).unwrap_err();
+        assert_eq!(ErrorKind::InvalidEncoding, error.error_kind);
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{Packable, PackableError, Direction, ErrorKind};
+
+mod sealed{
+    pub trait Sealed{}
+
+    impl<T: crate::Packable + ?Sized> Sealed for T{}
+}
+
+/// Helpers for writing `Packable` impls by hand, the same ones derived impls are
+/// built on, so third-party crates can implement `Packable` for their types
+/// without depending on what the derive happens to generate. Implemented for
+/// every `Packable` and sealed, so methods can be added without breaking anyone.
+pub trait PackableExt: Packable + sealed::Sealed{
+    /// Unpack from the front of `source` and advance it past the bytes used.
+    fn unpack_field(&mut self, source: &mut &[u8], litle_endian: bool) -> Result<usize, PackableError>{
+        let consumed = self.unpack_from(source, litle_endian)?;
+        *source = &source[consumed..];
+        Ok(consumed)
+    }
+
+    /// Unpack from all of `data`, failing with `TrailingBytes` if any is left over.
+    fn unpack_exact(&mut self, data: &[u8], litle_endian: bool) -> Result<(), PackableError>{
+        let consumed = self.unpack_from(data, litle_endian)?;
+        if consumed < data.len(){
+            return Err(PackableError { 
+                error_kind: ErrorKind::TrailingBytes, 
+                data: format!("{} bytes left after the value", data.len() - consumed),
+                direction: Some(Direction::Unpack),
+            })
+        }
+        Ok(())
+    }
+
+    /// Pack into a stack buffer of `N` bytes rather than a `Vec`, failing with
+    /// `BufferLengthError` if the value does not fit.
+    #[cfg(feature = "heapless")]
+    fn pack_to_heapless<const N: usize>(&self, litle_endian: bool) -> Result<heapless::Vec<u8, N>, PackableError>{
+        crate::stack::pack_to_heapless(self, litle_endian)
+    }
+}
+
+impl<T: Packable + ?Sized> PackableExt for T{}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, Packable, PackableError, PackableExt};
+
+    /// As a downstream crate would write it.
+    #[derive(Debug, Default, PartialEq)]
+    struct Point{
+        x: i16,
+        y: i16,
+    }
+
+    impl Packable for Point{
+        fn pack(&self, litle_endian: bool) -> Vec<u8> {
+            let mut data = Vec::with_capacity(self.size());
+            self.pack_into(&mut data, litle_endian);
+            data
+        }
+
+        fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+            self.x.pack_into(out, litle_endian);
+            self.y.pack_into(out, litle_endian);
+        }
+
+        fn size(&self) -> usize {
+            4
+        }
+
+        fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+            self.unpack_from(data, litle_endian)?;
+            Ok(())
+        }
+
+        fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+            let mut source = buf;
+            self.x.unpack_field(&mut source, litle_endian)?;
+            self.y.unpack_field(&mut source, litle_endian)?;
+            Ok(buf.len() - source.len())
+        }
+    }
+
+    #[test]
+    fn test_packable_ext(){
+        let mut point = Point::default();
+        point.unpack_exact(&[0, 1, 0xff, 0xfe], false).unwrap();
+        assert_eq!(Point { x: 1, y: -2 }, point);
+        let error = point.unpack_exact(&[0, 1, 0, 2, 3], false).unwrap_err();
+        assert_eq!(ErrorKind::TrailingBytes, error.error_kind);
+        let mut source: &[u8] = &[0, 3, 0, 4, 9];
+        assert_eq!(4, point.unpack_field(&mut source, false).unwrap());
+        assert_eq!((Point { x: 3, y: 4 }, &[9][..]), (point, source));
+    }
+}
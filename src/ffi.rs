@@ -0,0 +1,120 @@
+//! `extern "C"` entry points for packing and unpacking registered message types.
+//!
+//! Types are registered from Rust with [`register`] under a numeric id. C code then
+//! passes pointers to its own copy of the `#[repr(C)]` struct together with that id.
+//!
+//! The registry is also where bindings for other languages attach. JavaScript
+//! bindings through wasm-bindgen, behind a `wasm` feature, are deferred until
+//! that crate can be taken as an optional dependency. Python bindings through
+//! pyo3 are deferred as well: they also need a runtime schema, so that dicts can
+//! be packed without a Rust type registered for them.
+
+use core::ffi::c_void;
+use std::{collections::HashMap, sync::{OnceLock, RwLock}};
+use crate::{Packable, PackableError, ErrorCode, from_bytes};
+
+struct Entry{
+    pack: unsafe fn(*const c_void, bool) -> Vec<u8>,
+    unpack: unsafe fn(&[u8], bool, *mut c_void) -> Result<usize, PackableError>,
+}
+
+fn registry() -> &'static RwLock<HashMap<u32, Entry>>{
+    static REGISTRY: OnceLock<RwLock<HashMap<u32, Entry>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+unsafe fn pack_raw<T: Packable>(value: *const c_void, litle_endian: bool) -> Vec<u8>{
+    (*(value as *const T)).pack(litle_endian)
+}
+
+unsafe fn unpack_raw<T: Packable + Default>(data: &[u8], litle_endian: bool, value: *mut c_void) -> Result<usize, PackableError>{
+    let decoded: T = from_bytes(data, litle_endian)?;
+    let size = decoded.size();
+    (value as *mut T).write(decoded);
+    Ok(size)
+}
+
+/// Make `T` reachable from C under `id`, replacing any type registered before.
+/// `T` should be `#[repr(C)]` so that C code can share its layout.
+pub fn register<T: Packable + Default + Copy + 'static>(id: u32){
+    let entry = Entry { pack: pack_raw::<T>, unpack: unpack_raw::<T> };
+    registry().write().unwrap_or_else(|error| error.into_inner()).insert(id, entry);
+}
+
+/// Pack the value of type `id` at `value` into `out`, storing the packed length in
+/// `written`. Returns an `ErrorCode`.
+///
+/// # Safety
+///
+/// `value` must point to a valid instance of the type registered under `id`, `out`
+/// must be valid for `out_len` bytes of writes and `written` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn packable_pack(id: u32, value: *const c_void, out: *mut u8, out_len: usize, litle_endian: bool, written: *mut usize) -> u32{
+    if value.is_null() || out.is_null() || written.is_null(){
+        return ErrorCode::InvalidArgument as u32
+    }
+    let registry = registry().read().unwrap_or_else(|error| error.into_inner());
+    let Some(entry) = registry.get(&id) else {
+        return ErrorCode::UnknownType as u32
+    };
+    let data = (entry.pack)(value, litle_endian);
+    *written = data.len();
+    if data.len() > out_len{
+        return ErrorCode::BufferLengthError as u32
+    }
+    core::ptr::copy_nonoverlapping(data.as_ptr(), out, data.len());
+    ErrorCode::Ok as u32
+}
+
+/// Unpack `len` bytes at `data` into the value of type `id` at `value`, storing the
+/// number of bytes used in `consumed`. Returns an `ErrorCode`.
+///
+/// # Safety
+///
+/// `data` must be valid for `len` bytes of reads, `value` must point to writable
+/// memory for the type registered under `id` and `consumed` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn packable_unpack(id: u32, data: *const u8, len: usize, value: *mut c_void, litle_endian: bool, consumed: *mut usize) -> u32{
+    if data.is_null() || value.is_null() || consumed.is_null(){
+        return ErrorCode::InvalidArgument as u32
+    }
+    let registry = registry().read().unwrap_or_else(|error| error.into_inner());
+    let Some(entry) = registry.get(&id) else {
+        return ErrorCode::UnknownType as u32
+    };
+    match (entry.unpack)(core::slice::from_raw_parts(data, len), litle_endian, value){
+        Ok(size) => {
+            *consumed = size;
+            ErrorCode::Ok as u32
+        }
+        Err(error) => error.code(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::ffi::c_void;
+    use crate::ErrorCode;
+    use super::{register, packable_pack, packable_unpack};
+
+    #[test]
+    fn test_ffi_round_trip(){
+        register::<u32>(1);
+        let (value, mut out, mut written) = (0x01020304u32, [0u8; 8], 0usize);
+        let code = unsafe { packable_pack(1, &value as *const u32 as *const c_void, out.as_mut_ptr(), out.len(), false, &mut written) };
+        assert_eq!(ErrorCode::Ok as u32, code);
+        assert_eq!(&[1, 2, 3, 4], &out[..written]);
+
+        let (mut decoded, mut consumed) = (0u32, 0usize);
+        let code = unsafe { packable_unpack(1, out.as_ptr(), written, &mut decoded as *mut u32 as *mut c_void, true, &mut consumed) };
+        assert_eq!(ErrorCode::Ok as u32, code);
+        assert_eq!((0x04030201, 4), (decoded, consumed));
+
+        let code = unsafe { packable_unpack(1, out.as_ptr(), 2, &mut decoded as *mut u32 as *mut c_void, true, &mut consumed) };
+        assert_eq!(ErrorCode::BufferLengthError as u32, code);
+        let code = unsafe { packable_pack(2, &value as *const u32 as *const c_void, out.as_mut_ptr(), out.len(), false, &mut written) };
+        assert_eq!(ErrorCode::UnknownType as u32, code);
+        let code = unsafe { packable_pack(1, &value as *const u32 as *const c_void, out.as_mut_ptr(), 3, false, &mut written) };
+        assert_eq!(ErrorCode::BufferLengthError as u32, code);
+    }
+}
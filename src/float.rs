@@ -0,0 +1,185 @@
+use crate::{Packable, PackableError};
+
+macro_rules! impl_packable_bits {
+    ( $name:ident ) => {
+        impl Packable for $name {
+            fn pack(&self, litle_endian: bool) -> Vec<u8> {
+                self.0.pack(litle_endian)
+            }
+
+            fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+                self.0.pack_into(out, litle_endian)
+            }
+
+            fn size(&self) -> usize {
+                self.0.size()
+            }
+
+            fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+                self.0.unpack(data, litle_endian)
+            }
+
+            fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+                self.0.unpack_from(buf, litle_endian)
+            }
+        }
+    };
+}
+
+/// bfloat16: the upper half of an IEEE 754 binary32.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Bf16(pub u16);
+
+impl Bf16{
+    /// Rounds to nearest, ties to even.
+    pub fn from_f32(value: f32) -> Self{
+        let bits = value.to_bits();
+        if value.is_nan(){
+            return Bf16((bits >> 16) as u16 | 0x0040)
+        }
+        let rounding = 0x7fff + ((bits >> 16) & 1);
+        Bf16((bits.wrapping_add(rounding) >> 16) as u16)
+    }
+
+    pub fn to_f32(self) -> f32{
+        f32::from_bits((self.0 as u32) << 16)
+    }
+}
+
+impl From<f32> for Bf16{
+    fn from(value: f32) -> Self {
+        Bf16::from_f32(value)
+    }
+}
+
+impl From<Bf16> for f32{
+    fn from(value: Bf16) -> Self {
+        value.to_f32()
+    }
+}
+
+impl_packable_bits!(Bf16);
+
+/// IEEE 754 decimal32 in the binary integer decimal (BID) encoding: up to seven
+/// significant decimal digits and an exponent between -101 and 90.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Decimal32(pub u32);
+
+const DECIMAL32_BIAS: i32 = 101;
+const DECIMAL32_MAX_SIGNIFICAND: u32 = 9_999_999;
+const DECIMAL32_INFINITY: u32 = 0x7800_0000;
+const DECIMAL32_NAN: u32 = 0x7c00_0000;
+
+impl Decimal32{
+    /// Rounds to seven significant digits.
+    pub fn from_f64(value: f64) -> Self{
+        let sign = if value.is_sign_negative() { 0x8000_0000 } else { 0 };
+        if value.is_nan(){
+            return Decimal32(DECIMAL32_NAN)
+        }
+        if value.is_infinite(){
+            return Decimal32(sign | DECIMAL32_INFINITY)
+        }
+        if value == 0.0{
+            return Decimal32(sign | (DECIMAL32_BIAS as u32) << 23)
+        }
+        // `{:e}` formatting rounds correctly to the requested number of digits.
+        let formatted = format!("{:.6e}", value.abs());
+        let (digits, exponent) = formatted.split_once('e').unwrap_or((&formatted, "0"));
+        let mut significand: u32 = digits.replace('.', "").parse().unwrap_or(0);
+        let mut exponent = exponent.parse::<i32>().unwrap_or(0) - 6;
+        while exponent < -DECIMAL32_BIAS && significand.is_multiple_of(10){
+            significand /= 10;
+            exponent += 1;
+        }
+        if exponent < -DECIMAL32_BIAS{
+            let divisor = 10u64.pow((-DECIMAL32_BIAS - exponent).min(8) as u32);
+            significand = ((significand as u64 + divisor / 2) / divisor) as u32;
+            exponent = -DECIMAL32_BIAS;
+        }
+        while exponent > 90 && significand * 10 <= DECIMAL32_MAX_SIGNIFICAND{
+            significand *= 10;
+            exponent -= 1;
+        }
+        if exponent > 90{
+            return Decimal32(sign | DECIMAL32_INFINITY)
+        }
+        let biased = (exponent + DECIMAL32_BIAS) as u32;
+        if significand < 1 << 23{
+            Decimal32(sign | biased << 23 | significand)
+        }
+        else{
+            Decimal32(sign | 0b11 << 29 | biased << 21 | (significand & 0x1f_ffff))
+        }
+    }
+
+    pub fn to_f64(self) -> f64{
+        let sign = if self.0 & 0x8000_0000 != 0 { -1.0 } else { 1.0 };
+        if self.0 & 0x7c00_0000 == DECIMAL32_NAN{
+            return f64::NAN
+        }
+        if self.0 & 0x7c00_0000 == DECIMAL32_INFINITY{
+            return sign * f64::INFINITY
+        }
+        let (biased, significand) = if self.0 & 0x6000_0000 == 0x6000_0000{
+            ((self.0 >> 21) & 0xff, 0x80_0000 | (self.0 & 0x1f_ffff))
+        }
+        else{
+            ((self.0 >> 23) & 0xff, self.0 & 0x7f_ffff)
+        };
+        // Non-canonical significands are defined to be zero.
+        let significand = if significand > DECIMAL32_MAX_SIGNIFICAND { 0 } else { significand };
+        let exponent = biased as i32 - DECIMAL32_BIAS;
+        // Dividing by an exact power of ten rounds better than multiplying by its inverse.
+        if exponent >= 0{
+            sign * significand as f64 * 10f64.powi(exponent)
+        }
+        else{
+            sign * significand as f64 / 10f64.powi(-exponent)
+        }
+    }
+}
+
+impl From<f64> for Decimal32{
+    fn from(value: f64) -> Self {
+        Decimal32::from_f64(value)
+    }
+}
+
+impl From<Decimal32> for f64{
+    fn from(value: Decimal32) -> Self {
+        value.to_f64()
+    }
+}
+
+impl_packable_bits!(Decimal32);
+
+#[cfg(test)]
+mod tests {
+    use crate::{to_bytes, from_bytes};
+    use super::{Bf16, Decimal32};
+
+    #[test]
+    fn test_bf16(){
+        assert_eq!(Bf16(0x3f80), Bf16::from_f32(1.0));
+        assert_eq!(Bf16(0x4049), Bf16::from_f32(core::f32::consts::PI));
+        assert_eq!(3.140625, Bf16(0x4049).to_f32());
+        assert!(Bf16::from_f32(f32::NAN).to_f32().is_nan());
+        assert_eq!(vec![0x49, 0x40], to_bytes(&Bf16(0x4049), true));
+    }
+
+    #[test]
+    fn test_decimal32(){
+        assert_eq!(Decimal32(0x2f8f_4240), Decimal32::from_f64(1.0));
+        assert_eq!(1.0, Decimal32(0x2f8f_4240).to_f64());
+        assert_eq!(123.45, Decimal32::from_f64(123.45).to_f64());
+        assert_eq!(-9_999_999.0, Decimal32::from_f64(-9_999_999.0).to_f64());
+        assert_eq!(1.234568e-5, Decimal32::from_f64(0.00001234567890).to_f64());
+        assert_eq!(f64::INFINITY, Decimal32::from_f64(1e200).to_f64());
+        assert_eq!(0.0, Decimal32::from_f64(1e-200).to_f64());
+        assert!(Decimal32::from_f64(f64::NAN).to_f64().is_nan());
+
+        let value = Decimal32::from_f64(42.5);
+        assert_eq!(value, from_bytes(&to_bytes(&value, false), false).unwrap());
+    }
+}
@@ -0,0 +1,39 @@
+use crate::{Packable, from_bytes};
+
+/// Fuzz target body for `T`: unpack `data` with both endiannesses and, whenever that
+/// succeeds, check that packing the value and unpacking it again is stable.
+///
+/// ```ignore
+/// fuzz_target!(|data: &[u8]| packable::fuzz_unpack::<Header>(data));
+/// ```
+pub fn fuzz_unpack<T: Packable + Default>(data: &[u8]){
+    for litle_endian in [false, true]{
+        let Ok(value) = from_bytes::<T>(data, litle_endian) else {
+            continue
+        };
+        let packed = value.pack(litle_endian);
+        assert_eq!(value.size(), packed.len(), "size() disagrees with pack()");
+        let again = match from_bytes::<T>(&packed, litle_endian){
+            Ok(again) => again,
+            Err(error) => panic!("repacked value does not unpack: {}", error),
+        };
+        assert_eq!(packed, again.pack(litle_endian), "pack/unpack round trip is not stable");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{KeyValue, UntilEnd, Vlq};
+    use super::fuzz_unpack;
+
+    #[test]
+    fn test_fuzz_unpack(){
+        let inputs: [&[u8]; 5] = [&[], &[0x81], &[0x81, 0x00, 7], &[0, 1, 0, 0, 0, 1, 9], &[0xff; 9]];
+        for data in inputs{
+            fuzz_unpack::<u32>(data);
+            fuzz_unpack::<Vlq>(data);
+            fuzz_unpack::<KeyValue>(data);
+            fuzz_unpack::<UntilEnd<u16>>(data);
+        }
+    }
+}
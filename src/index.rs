@@ -0,0 +1,71 @@
+use core::ops::Range;
+use crate::{Endian, Packable, PackableError, from_bytes};
+
+/// Byte offsets of the records found by `unpack_indexed`, for random access into the
+/// original buffer without parsing it again.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RecordIndex{
+    offsets: Vec<usize>,
+    end: usize,
+}
+
+impl RecordIndex{
+    pub fn len(&self) -> usize{
+        self.offsets.len()
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.offsets.is_empty()
+    }
+
+    pub fn offsets(&self) -> &[usize]{
+        &self.offsets
+    }
+
+    pub fn range(&self, record: usize) -> Option<Range<usize>>{
+        let start = *self.offsets.get(record)?;
+        let end = self.offsets.get(record + 1).copied().unwrap_or(self.end);
+        Some(start..end)
+    }
+
+    pub fn unpack<T: Packable + Default>(&self, data: &[u8], record: usize, endian: impl Into<Endian>) -> Option<Result<T, PackableError>>{
+        let litle_endian = endian.into().is_little();
+        let range = self.range(record)?;
+        Some(from_bytes(data.get(range)?, litle_endian))
+    }
+}
+
+/// Unpack consecutive records until `data` is exhausted, recording where each starts.
+pub fn unpack_indexed<T: Packable + Default>(data: &[u8], endian: impl Into<Endian>) -> Result<(Vec<T>, RecordIndex), PackableError>{
+    let litle_endian = endian.into().is_little();
+    let mut records = Vec::new();
+    let mut index = RecordIndex::default();
+    let mut source = data;
+    while !source.is_empty(){
+        let offset = data.len() - source.len();
+        let mut record = T::default();
+        crate::unpack!(litle_endian, &mut source, record)?;
+        index.offsets.push(offset);
+        records.push(record);
+    }
+    index.end = data.len();
+    Ok((records, index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unpack_indexed;
+
+    #[test]
+    fn test_unpack_indexed(){
+        let data = [0, 1, 0, 2, 0, 3];
+        let (records, index) = unpack_indexed::<u16>(&data, false).unwrap();
+        assert_eq!(vec![1, 2, 3], records);
+        assert_eq!(&[0, 2, 4], index.offsets());
+        assert_eq!(Some(2..4), index.range(1));
+        assert_eq!(None, index.range(3));
+        assert_eq!(3u16, index.unpack(&data, 2, false).unwrap().unwrap());
+
+        assert!(unpack_indexed::<u16>(&data[..5], false).is_err());
+    }
+}
@@ -0,0 +1,199 @@
+use core::{fmt, mem, ops::Deref};
+use std::{cell::RefCell, collections::HashSet, rc::Rc};
+use crate::{Packable, PackableError, UnpackSource, buffer_length_error, string::utf8};
+
+thread_local! {
+    static INTERNER: RefCell<Option<Interner>> = const { RefCell::new(None) };
+}
+
+/// A set of shared strings that `Interned` fields draw from while unpacking inside
+/// `interning`, so the same device name or unit across thousands of records is
+/// stored once.
+#[derive(Debug, Default)]
+pub struct Interner{
+    strings: HashSet<Rc<str>>,
+}
+
+impl Interner{
+    pub fn new() -> Self{
+        Interner::default()
+    }
+
+    /// The shared copy of `value`, stored on first use.
+    pub fn intern(&mut self, value: &str) -> Rc<str>{
+        if let Some(shared) = self.strings.get(value){
+            return shared.clone()
+        }
+        let shared: Rc<str> = Rc::from(value);
+        self.strings.insert(shared.clone());
+        shared
+    }
+
+    /// Number of distinct strings held.
+    pub fn len(&self) -> usize{
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.strings.is_empty()
+    }
+}
+
+struct Restore<'a>{
+    interner: &'a mut Interner,
+    previous: Option<Interner>,
+}
+
+impl Drop for Restore<'_>{
+    fn drop(&mut self) {
+        let installed = INTERNER.with(|slot| mem::replace(&mut *slot.borrow_mut(), self.previous.take()));
+        *self.interner = installed.unwrap_or_default();
+    }
+}
+
+/// Run `f` with `interner` installed for `Interned` fields unpacked on this
+/// thread, e.g. `interning(&mut names, || from_bytes::<Vec<Reading>>(&data, false))`.
+/// The interner keeps what it gathered, so it can be reused across batches, and
+/// is handed back even if `f` panics. Scopes nest; the inner one wins.
+pub fn interning<R, F: FnOnce() -> R>(interner: &mut Interner, f: F) -> R{
+    let previous = INTERNER.with(|slot| slot.borrow_mut().replace(mem::take(interner)));
+    let _restore = Restore { interner, previous };
+    f()
+}
+
+/// A string packed like `String`, as UTF-8 bytes preceded by their count as a
+/// `u32`, whose storage is shared through the `Interner` of the enclosing
+/// `interning` scope. Outside a scope each unpack allocates its own copy.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interned(Rc<str>);
+
+impl Interned{
+    pub fn new(value: &str) -> Self{
+        Interned(Rc::from(value))
+    }
+
+    pub fn as_str(&self) -> &str{
+        &self.0
+    }
+
+    /// The shared storage, to check with `Rc::ptr_eq` or hand out as is.
+    pub fn as_rc(&self) -> &Rc<str>{
+        &self.0
+    }
+}
+
+impl Default for Interned{
+    fn default() -> Self {
+        Interned::new("")
+    }
+}
+
+impl Deref for Interned{
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Interned{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<&str> for Interned{
+    fn from(value: &str) -> Self {
+        Interned::new(value)
+    }
+}
+
+impl Packable for Interned{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.size());
+        self.pack_into(&mut data, litle_endian);
+        data
+    }
+
+    fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+        let Ok(len) = u32::try_from(self.0.len()) else {
+            panic!("string of {} bytes does not fit a u32 length", self.0.len())
+        };
+        len.pack_into(out, litle_endian);
+        out.extend_from_slice(self.0.as_bytes());
+    }
+
+    fn size(&self) -> usize {
+        0u32.size() + self.0.len()
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        let mut source = buf;
+        let mut len = 0u32;
+        source.unpack_into(&mut len, litle_endian)?;
+        let len = len as usize;
+        let bytes = source.get(..len).ok_or_else(|| buffer_length_error(len, source.len()))?;
+        let value = utf8(bytes)?;
+        if *self.0 != *value{
+            self.0 = INTERNER.with(|slot| match &mut *slot.borrow_mut() {
+                Some(interner) => interner.intern(value),
+                None => Rc::from(value),
+            });
+        }
+        Ok(4 + len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use crate::{ErrorKind, allocated_during, from_bytes, to_bytes};
+    use super::{Interned, Interner, interning};
+
+    #[test]
+    fn test_interned(){
+        let names: Vec<Interned> = ["temp", "volts", "temp"].into_iter().cycle().take(3000).map(Interned::from).collect();
+        let data = to_bytes(&names, false);
+        assert_eq!(to_bytes(&"temp".to_string(), false), to_bytes(&names[0], false));
+
+        let mut interner = Interner::new();
+        let decoded = interning(&mut interner, || from_bytes::<Vec<Interned>>(&data, false)).unwrap();
+        assert_eq!(names, decoded);
+        assert_eq!(2, interner.len());
+        assert!(decoded.iter().step_by(3).all(|name| Rc::ptr_eq(name.as_rc(), decoded[0].as_rc())));
+        let (again, allocated) = allocated_during(|| interning(&mut interner, || from_bytes::<Vec<Interned>>(&data, false)).unwrap());
+        assert!(Rc::ptr_eq(again[1].as_rc(), decoded[1].as_rc()));
+
+        let (plain, plain_allocated) = allocated_during(|| from_bytes::<Vec<Interned>>(&data, false).unwrap());
+        assert!(allocated + 3000 * 16 < plain_allocated, "{} bytes interned, {} bytes plain", allocated, plain_allocated);
+        assert_eq!(names, plain);
+        assert!(!Rc::ptr_eq(plain[0].as_rc(), plain[2].as_rc()));
+
+        let error = from_bytes::<Interned>(&[0, 0, 0, 1, 0xff], false).unwrap_err();
+        assert_eq!(ErrorKind::InvalidUtf8, error.error_kind);
+    }
+
+    #[test]
+    fn test_interning_nested(){
+        let mut outer = Interner::new();
+        let mut inner = Interner::new();
+        interning(&mut outer, || {
+            from_bytes::<Interned>(&[0, 0, 0, 1, b'a'], false).unwrap();
+            interning(&mut inner, || from_bytes::<Interned>(&[0, 0, 0, 1, b'b'], false).unwrap());
+            from_bytes::<Interned>(&[0, 0, 0, 1, b'c'], false).unwrap();
+        });
+        assert_eq!(2, outer.len());
+        assert_eq!(1, inner.len());
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| interning(&mut inner, || {
+            from_bytes::<Interned>(&[0, 0, 0, 1, b'd'], false).unwrap();
+            panic!("decoder failed")
+        })));
+        assert!(panicked.is_err());
+        assert_eq!(2, inner.len());
+    }
+}
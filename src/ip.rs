@@ -0,0 +1,183 @@
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
+use crate::{Packable, PackableError, Direction, ErrorKind, UnpackSource, buffer_length_error};
+
+// Written against `core::net` so the impls carry over to `no_std` users unchanged.
+
+/// The 4 octets in network order, whatever the requested byte order.
+impl Packable for Ipv4Addr{
+    fn pack(&self, _litle_endian: bool) -> Vec<u8> {
+        self.octets().to_vec()
+    }
+
+    fn size(&self) -> usize {
+        4
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], _litle_endian: bool) -> Result<usize, PackableError> {
+        let octets: [u8; 4] = buf.get(..4).ok_or_else(|| buffer_length_error(4, buf.len()))?.try_into()?;
+        *self = Ipv4Addr::from(octets);
+        Ok(4)
+    }
+}
+
+/// The 16 octets in network order, whatever the requested byte order.
+impl Packable for Ipv6Addr{
+    fn pack(&self, _litle_endian: bool) -> Vec<u8> {
+        self.octets().to_vec()
+    }
+
+    fn size(&self) -> usize {
+        16
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], _litle_endian: bool) -> Result<usize, PackableError> {
+        let octets: [u8; 16] = buf.get(..16).ok_or_else(|| buffer_length_error(16, buf.len()))?.try_into()?;
+        *self = Ipv6Addr::from(octets);
+        Ok(16)
+    }
+}
+
+/// A version byte, 4 or 6, followed by the address. Other versions fail to unpack
+/// with `InvalidDiscriminant`.
+impl Packable for IpAddr{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        match self{
+            IpAddr::V4(address) => pack!(litle_endian, 4u8, *address),
+            IpAddr::V6(address) => pack!(litle_endian, 6u8, *address),
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self{
+            IpAddr::V4(_) => 1 + 4,
+            IpAddr::V6(_) => 1 + 16,
+        }
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        let mut source = buf;
+        let mut version = 0u8;
+        source.unpack_into(&mut version, litle_endian)?;
+        *self = match version{
+            4 => {
+                let mut address = Ipv4Addr::UNSPECIFIED;
+                source.unpack_into(&mut address, litle_endian)?;
+                IpAddr::V4(address)
+            }
+            6 => {
+                let mut address = Ipv6Addr::UNSPECIFIED;
+                source.unpack_into(&mut address, litle_endian)?;
+                IpAddr::V6(address)
+            }
+            _ => return Err(PackableError { 
+                error_kind: ErrorKind::InvalidDiscriminant, 
+                data: format!("{} is not an IP version", version),
+                direction: Some(Direction::Unpack),
+            }),
+        };
+        Ok(buf.len() - source.len())
+    }
+}
+
+/// The address followed by the port, a `u16` in the requested byte order.
+impl Packable for SocketAddrV4{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        pack!(litle_endian, *self.ip(), self.port())
+    }
+
+    fn size(&self) -> usize {
+        4 + 2
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        let (mut address, mut port) = (Ipv4Addr::UNSPECIFIED, 0u16);
+        let consumed = unpack!(litle_endian, &mut &buf[..], address, port)?;
+        *self = SocketAddrV4::new(address, port);
+        Ok(consumed)
+    }
+}
+
+/// The address followed by the port, a `u16` in the requested byte order. Flow
+/// info and scope id are not packed and unpack as zero.
+impl Packable for SocketAddrV6{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        pack!(litle_endian, *self.ip(), self.port())
+    }
+
+    fn size(&self) -> usize {
+        16 + 2
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        let (mut address, mut port) = (Ipv6Addr::UNSPECIFIED, 0u16);
+        let consumed = unpack!(litle_endian, &mut &buf[..], address, port)?;
+        *self = SocketAddrV6::new(address, port, 0, 0);
+        Ok(consumed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
+    use crate::{ErrorKind, Packable, PackableError, to_bytes};
+
+    /// None of these types has a `Default`, so `from_bytes` is out.
+    fn decode<T: Packable>(mut value: T, data: &[u8], litle_endian: bool) -> Result<T, PackableError>{
+        value.unpack_from(data, litle_endian)?;
+        Ok(value)
+    }
+
+    #[test]
+    fn test_ip_addr(){
+        let v4 = Ipv4Addr::new(192, 168, 0, 1);
+        assert_eq!(vec![192, 168, 0, 1], to_bytes(&v4, true));
+        assert_eq!(v4, decode(Ipv4Addr::UNSPECIFIED, &[192, 168, 0, 1], true).unwrap());
+        assert_eq!(Ipv6Addr::LOCALHOST, decode(Ipv6Addr::UNSPECIFIED, &to_bytes(&Ipv6Addr::LOCALHOST, false), false).unwrap());
+
+        let any = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+        assert_eq!(vec![4, 10, 0, 0, 1], to_bytes(&IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), false));
+        let v6 = IpAddr::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1));
+        assert_eq!(17, v6.size());
+        assert_eq!(v6, decode(any, &to_bytes(&v6, false), false).unwrap());
+        let error = decode(any, &[5, 1, 2, 3, 4], false).unwrap_err();
+        assert_eq!(ErrorKind::InvalidDiscriminant, error.error_kind);
+    }
+
+    #[test]
+    fn test_socket_addr(){
+        let any = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0);
+        let address = SocketAddrV4::new(Ipv4Addr::LOCALHOST, 8080);
+        assert_eq!(vec![127, 0, 0, 1, 0x1f, 0x90], to_bytes(&address, false));
+        assert_eq!(vec![127, 0, 0, 1, 0x90, 0x1f], to_bytes(&address, true));
+        assert_eq!(address, decode(any, &[127, 0, 0, 1, 0x90, 0x1f], true).unwrap());
+        let address = SocketAddrV6::new(Ipv6Addr::LOCALHOST, 443, 0, 0);
+        assert_eq!(address, decode(SocketAddrV6::new(Ipv6Addr::UNSPECIFIED, 0, 0, 0), &to_bytes(&address, false), false).unwrap());
+        let error = decode(any, &[127, 0, 0, 1, 0], false).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{Direction, Endian, ErrorKind, Packable, PackableError, UnpackSource, from_bytes};
+
+/// Field-order independent encoding: each field is written as a `u16` id, a `u32`
+/// length and the packed value. Fields can arrive in any order and unknown ids are
+/// kept but otherwise ignored, so readers survive fields being added or removed.
+///
+/// The encoding has no terminator, so unpacking takes all the remaining input: a
+/// `KeyValue` can only be the last field of a struct or the whole of a frame. An id
+/// that arrives twice keeps its last value.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct KeyValue{
+    fields: Vec<(u16, Vec<u8>)>,
+}
+
+impl KeyValue{
+    /// Fails with `OutOfRange` if `value` packs to more bytes than a `u32` length
+    /// can count.
+    pub fn insert<T: Packable + ?Sized>(&mut self, id: u16, value: &T, endian: impl Into<Endian>) -> Result<(), PackableError>{
+        let litle_endian = endian.into().is_little();
+        let data = value.pack(litle_endian);
+        if u32::try_from(data.len()).is_err(){
+            return Err(PackableError { 
+                error_kind: ErrorKind::OutOfRange, 
+                data: format!("field {} of {} bytes does not fit a u32 length", id, data.len()),
+                direction: Some(Direction::Pack),
+            })
+        }
+        self.set(id, data);
+        Ok(())
+    }
+
+    fn set(&mut self, id: u16, data: Vec<u8>){
+        match self.fields.iter_mut().find(|(field_id, _)| *field_id == id){
+            Some(field) => field.1 = data,
+            None => self.fields.push((id, data)),
+        }
+    }
+
+    pub fn get<T: Packable + Default>(&self, id: u16, endian: impl Into<Endian>) -> Result<Option<T>, PackableError>{
+        let litle_endian = endian.into().is_little();
+        match self.raw(id){
+            Some(data) => Ok(Some(from_bytes(data, litle_endian)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn raw(&self, id: u16) -> Option<&[u8]>{
+        self.fields.iter().find(|(field_id, _)| *field_id == id).map(|(_, data)| data.as_slice())
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = u16> + '_{
+        self.fields.iter().map(|(id, _)| *id)
+    }
+}
+
+impl Packable for KeyValue{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.size());
+        for (id, value) in &self.fields{
+            // `insert` and `unpack_from` only store values a u32 can count.
+            let Ok(len) = u32::try_from(value.len()) else {
+                panic!("field {} of {} bytes does not fit a u32 length", id, value.len())
+            };
+            data.extend(id.pack(litle_endian));
+            data.extend(len.pack(litle_endian));
+            data.extend_from_slice(value);
+        }
+        data
+    }
+
+    fn size(&self) -> usize {
+        self.fields.iter().map(|(_, value)| 6 + value.len()).sum()
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    /// Consumes the whole buffer, the encoding has no terminator of its own.
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        self.fields.clear();
+        let mut source = buf;
+        while !source.is_empty(){
+            let (mut id, mut len) = (0u16, 0u32);
+            crate::unpack!(litle_endian, &mut source, id, len)?;
+            let value = source.take(len as usize)?;
+            self.set(id, value);
+        }
+        Ok(buf.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Packable, from_bytes};
+    use super::KeyValue;
+
+    #[test]
+    fn test_key_value_round_trip(){
+        let mut fields = KeyValue::default();
+        fields.insert(1, &42u16, false).unwrap();
+        fields.insert(7, &[1u8, 2, 3], false).unwrap();
+        let data = fields.pack(false);
+        assert_eq!(vec![0, 1, 0, 0, 0, 2, 0, 42, 0, 7, 0, 0, 0, 3, 1, 2, 3], data);
+        assert_eq!(data.len(), fields.size());
+
+        let decoded: KeyValue = from_bytes(&data, false).unwrap();
+        assert_eq!(Some(42u16), decoded.get(1, false).unwrap());
+        assert_eq!(None, decoded.get::<u32>(2, false).unwrap());
+        assert_eq!(vec![1, 7], decoded.ids().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_key_value_greedy(){
+        let data = [0, 1, 0, 0, 0, 1, 5, 0, 2, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1, 6];
+        let (mut fields, mut after) = (KeyValue::default(), 0u8);
+        assert!(crate::unpack!(false, &mut &data[..], fields, after).is_err());
+        let decoded: KeyValue = from_bytes(&data, false).unwrap();
+        assert_eq!(Some(6u8), decoded.get(1, false).unwrap());
+        assert_eq!(vec![1, 2], decoded.ids().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_key_value_truncated(){
+        assert!(from_bytes::<KeyValue>(&[0, 1, 0, 0, 0, 4, 1, 2], false).is_err());
+    }
+}
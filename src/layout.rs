@@ -0,0 +1,119 @@
+use core::fmt::{self, Write};
+
+/// Where one field sits in a packed record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldLayout{
+    pub name: &'static str,
+    pub offset: usize,
+    pub size: usize,
+    /// The field is wider than a byte but its current value packs the same in
+    /// either byte order; see `LayoutWarning::EndianNeutral`.
+    pub endian_neutral: bool,
+    /// What the field means, from `#[packable(doc = "..")]` when derived.
+    pub doc: Option<&'static str>,
+}
+
+/// Field layouts of a value, one per field in order, as written by
+/// `#[derive(LayoutInfo)]`. Taken from a value rather than the type, because sizes
+/// and the endianness check depend on what is in the fields.
+pub trait LayoutInfo{
+    fn field_layout(&self) -> Vec<FieldLayout>;
+}
+
+/// Something in a layout that is likely a mistake in transcribing a spec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutWarning{
+    /// A non-zero value, typically a magic number, that reads the same in either
+    /// byte order, so checking it cannot catch a byte order mix-up.
+    EndianNeutral{ field: &'static str },
+    /// Two fields claim the same bytes.
+    Overlap{ first: &'static str, second: &'static str },
+    /// A field ends past the end of the record, so can never be read.
+    PastEnd{ field: &'static str, end: usize },
+}
+
+impl fmt::Display for LayoutWarning{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self{
+            LayoutWarning::EndianNeutral { field } => write!(f, "{} reads the same in either byte order", field),
+            LayoutWarning::Overlap { first, second } => write!(f, "{} overlaps {}", first, second),
+            LayoutWarning::PastEnd { field, end } => write!(f, "{} ends past the record, at byte {}", field, end),
+        }
+    }
+}
+
+/// Check `fields`, such as a field table copied from a spec or the output of
+/// `LayoutInfo`, against a record of `size` bytes if that is fixed.
+pub fn analyze(fields: &[FieldLayout], size: Option<usize>) -> Vec<LayoutWarning>{
+    let mut warnings = Vec::new();
+    for (index, field) in fields.iter().enumerate(){
+        if field.endian_neutral{
+            warnings.push(LayoutWarning::EndianNeutral { field: field.name });
+        }
+        let end = field.offset + field.size;
+        for other in &fields[index + 1..]{
+            if field.offset < other.offset + other.size && other.offset < end{
+                warnings.push(LayoutWarning::Overlap { first: field.name, second: other.name });
+            }
+        }
+        if size.is_some_and(|size| end > size){
+            warnings.push(LayoutWarning::PastEnd { field: field.name, end });
+        }
+    }
+    warnings
+}
+
+/// Describe `packed`, a value laid out as `fields`, one field per line: its
+/// offset, its bytes in hex, its name and its `doc` if it has one. Bytes past the
+/// end of `packed` are left out, so a truncated record shows where it stops.
+pub fn explain(fields: &[FieldLayout], packed: &[u8]) -> String{
+    let width = fields.iter().map(|field| (3 * field.size).saturating_sub(1)).max().unwrap_or(0);
+    let mut out = String::new();
+    for field in fields{
+        let start = field.offset.min(packed.len());
+        let end = (field.offset + field.size).min(packed.len());
+        let hex: Vec<String> = packed[start..end].iter().map(|byte| format!("{:02x}", byte)).collect();
+        let _ = write!(out, "{:>4}  {:<width$}  {}", field.offset, hex.join(" "), field.name);
+        if let Some(doc) = field.doc{
+            let _ = write!(out, ": {}", doc);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Whether `packed` in little and big endian shows a value that cannot reveal a
+/// byte order mistake: wider than a byte, not zero, and the same either way.
+pub fn endian_neutral(little: &[u8], big: &[u8]) -> bool{
+    little.len() > 1 && little == big && little.iter().any(|byte| *byte != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FieldLayout, LayoutWarning, analyze, endian_neutral, explain};
+
+    fn field(name: &'static str, offset: usize, size: usize) -> FieldLayout{
+        FieldLayout { name, offset, size, endian_neutral: false, doc: None }
+    }
+
+    #[test]
+    fn test_analyze(){
+        let fields = [field("magic", 0, 4), field("length", 4, 2), field("flags", 5, 1), field("crc", 6, 4)];
+        assert_eq!(vec![
+            LayoutWarning::Overlap { first: "length", second: "flags" },
+            LayoutWarning::PastEnd { field: "crc", end: 10 },
+        ], analyze(&fields, Some(8)));
+        assert!(analyze(&fields[..2], None).is_empty());
+
+        assert!(endian_neutral(&[0xab, 0xab], &[0xab, 0xab]));
+        assert!(!endian_neutral(&[0, 0], &[0, 0]));
+        assert!(!endian_neutral(&[7], &[7]));
+        assert!(!endian_neutral(&[1, 2], &[2, 1]));
+    }
+
+    #[test]
+    fn test_explain(){
+        let fields = [FieldLayout { doc: Some("ASCII \"PK\""), ..field("magic", 0, 2) }, field("length", 2, 4)];
+        assert_eq!("   0  50 4b        magic: ASCII \"PK\"\n   2  00 00 01     length\n", explain(&fields, &[0x50, 0x4b, 0, 0, 1]));
+    }
+}
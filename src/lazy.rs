@@ -0,0 +1,126 @@
+use core::fmt;
+use std::cell::OnceCell;
+use crate::{Packable, PackableError, from_bytes};
+
+/// A field that keeps its raw bytes on unpack and only decodes them the first time
+/// they are accessed, for large payloads that most readers never look at.
+///
+/// The extent of a variable-size payload, such as a `Vec<u8>`, is only known by
+/// decoding it, so unpacking runs `T::unpack_from` once over a scratch value and
+/// drops it, keeping just the bytes it read.
+pub struct Lazy<T>{
+    raw: Vec<u8>,
+    litle_endian: bool,
+    value: OnceCell<T>,
+}
+
+impl<T> Lazy<T>{
+    pub fn new(value: T) -> Self{
+        Lazy { raw: Vec::new(), litle_endian: false, value: OnceCell::from(value) }
+    }
+
+    pub fn raw(&self) -> &[u8]{
+        &self.raw
+    }
+
+    pub fn is_decoded(&self) -> bool{
+        self.value.get().is_some()
+    }
+}
+
+impl<T: Packable + Default> Lazy<T>{
+    pub fn get(&self) -> Result<&T, PackableError>{
+        if let Some(value) = self.value.get(){
+            return Ok(value)
+        }
+        let value = from_bytes(&self.raw, self.litle_endian)?;
+        Ok(self.value.get_or_init(|| value))
+    }
+
+    pub fn into_inner(mut self) -> Result<T, PackableError>{
+        match self.value.take(){
+            Some(value) => Ok(value),
+            None => from_bytes(&self.raw, self.litle_endian),
+        }
+    }
+}
+
+impl<T: Default> Default for Lazy<T>{
+    fn default() -> Self {
+        Lazy::new(T::default())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Lazy<T>{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value.get(){
+            Some(value) => f.debug_tuple("Lazy").field(value).finish(),
+            None => write!(f, "Lazy(<{} bytes>)", self.raw.len()),
+        }
+    }
+}
+
+impl<T: Packable + Default> Packable for Lazy<T>{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        match self.value.get(){
+            Some(value) => value.pack(litle_endian),
+            None if self.litle_endian == litle_endian => self.raw.clone(),
+            None => match self.get(){
+                Ok(value) => value.pack(litle_endian),
+                Err(_) => self.raw.clone(),
+            },
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self.value.get(){
+            Some(value) => value.size(),
+            None => self.raw.len(),
+        }
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        let size = T::default().unpack_from(buf, litle_endian)?;
+        self.raw.clear();
+        self.raw.extend_from_slice(&buf[..size]);
+        self.litle_endian = litle_endian;
+        self.value = OnceCell::new();
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Packable, Bytes};
+    use super::Lazy;
+
+    #[test]
+    fn test_lazy(){
+        let (mut header, mut payload) = (0u16, Lazy::<Bytes<4>>::default());
+        unpack!(false, &mut vec![0, 4, 1, 2, 3, 4], header, payload).unwrap();
+        assert_eq!(4, header);
+        assert!(!payload.is_decoded());
+        assert_eq!(vec![1, 2, 3, 4], payload.pack(true));
+        assert_eq!(&[1, 2, 3, 4][..], payload.get().unwrap().as_slice());
+        assert!(payload.is_decoded());
+
+        let mut value = Lazy::<u32>::default();
+        unpack!(true, &mut vec![1, 0, 0, 0], value).unwrap();
+        assert_eq!(vec![0, 0, 0, 1], value.pack(false));
+        assert_eq!(1, value.into_inner().unwrap());
+    }
+
+    #[test]
+    fn test_lazy_variable_size(){
+        let (mut payload, mut after) = (Lazy::<Vec<u8>>::default(), 0u8);
+        assert_eq!(8, unpack!(false, &mut vec![0, 0, 0, 3, 1, 2, 3, 9], payload, after).unwrap());
+        assert_eq!(9, after);
+        assert_eq!(7, payload.size());
+        assert_eq!(&vec![1, 2, 3], payload.get().unwrap());
+    }
+}
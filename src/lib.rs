@@ -1,51 +1,506 @@
+//! Binary packing of values with explicit endianness.
+//!
+//! ```
+//! let bytes = packable::to_bytes(&0x1234u16, false);
+//! assert_eq!(vec![0x12, 0x34], bytes);
+//! let value: u16 = packable::from_bytes(&bytes, false).unwrap();
+//! assert_eq!(0x1234, value);
+//! ```
+//!
+//! With the `derive` feature, `#[derive(Packable)]` implements the trait for a
+//! struct by packing its fields in declaration order, or for a `#[repr(..)]`
+//! enum of unit variants by packing its discriminant.
+
+extern crate self as packable;
+
 #[macro_export]
 macro_rules! pack {
    ( $le:expr, $( $x:expr ),* ) => {
         {
-            let litle_endian: bool = $le;
-            let mut temp_vec = Vec::new();
+            let litle_endian: bool = $crate::Endian::from($le).is_little();
+            let mut temp_vec = ::std::vec::Vec::with_capacity(0 $( + $crate::Packable::size(&$x) )*);
             $(
-                temp_vec.extend_from_slice(Packable::pack(&$x, litle_endian).as_slice());
+                $crate::Packable::pack_into(&$x, &mut temp_vec, litle_endian);
             )*
             temp_vec
         }
     };
 }
 
+/// Unpack each target in turn from an `UnpackSource`, returning the total number of
+/// bytes consumed. In-memory sources are read in place through a cursor and only
+/// advanced once at the end, so nothing is consumed if a target fails.
 #[macro_export]
 macro_rules! unpack {
     ( $le:expr, $buf:expr, $( $x:expr ),* ) => {
         {
+            let litle_endian: bool = $crate::Endian::from($le).is_little();
+            let source = $buf;
+            $crate::UnpackSource::unpack_fields(&mut *source, &mut |fields: &mut dyn $crate::FieldSource| {
+                let mut consumed: usize = 0;
+                $(
+                    consumed += fields.field(&mut $x, litle_endian)?;
+                )*
+                Ok::<usize, $crate::PackableError>(consumed)
+            })
+        }
+    };
+}
+
+/// `unpack!` into fresh values of the given types, starting from `Default`: one
+/// value, or a tuple of up to 12, to bind with `let` instead of declaring mutable
+/// targets first.
+///
+/// ```
+/// let (kind, length) = packable::unpack_new!(false, &mut vec![1, 0, 2], u8, u16).unwrap();
+/// assert_eq!((1, 2), (kind, length));
+/// ```
+#[macro_export]
+macro_rules! unpack_new {
+    ( $le:expr, $buf:expr, $t:ty ) => {
+        {
+            let mut value = <$t as ::core::default::Default>::default();
+            $crate::unpack!($le, $buf, value).map(|_| value)
+        }
+    };
+    ( $le:expr, $buf:expr, $( $t:ty ),+ ) => {
+        {
+            let mut values = <( $( $t, )+ ) as ::core::default::Default>::default();
+            $crate::unpack!($le, $buf, values).map(|_| values)
+        }
+    };
+}
+
+/// `unpack!` straight from a `std::io::Read`, such as a serial port: each target
+/// reads exactly its current `size()` bytes, so only fixed-size values are safe.
+#[macro_export]
+macro_rules! unpack_reader {
+    ( $le:expr, $reader:expr, $( $x:expr ),* ) => {
+        $crate::unpack!($le, &mut $crate::Reader($reader), $( $x ),*)
+    };
+}
+
+/// `unpack!` from a byte slice that also records in an `AuditLog` the byte range
+/// and raw bytes of each target, named after the target expression.
+#[cfg(feature = "audit")]
+#[macro_export]
+macro_rules! unpack_audited {
+    ( $le:expr, $data:expr, $log:expr, $( $x:expr ),* ) => {
+        {
+            let litle_endian: bool = $crate::Endian::from($le).is_little();
+            let data: &[u8] = $data;
+            let log: &mut $crate::AuditLog = $log;
             (|| {
-                let litle_endian: bool = $le;
-                let buffer: &mut Vec<u8> = $buf;
+                let mut offset: usize = 0;
                 $(
-                    let size = Packable::size(&$x);
-                    if buffer.len() >= size{
-                        let split_buf = buffer.split_off(size);
-                        Packable::unpack(&mut $x, buffer, litle_endian)?;
-                        *buffer = split_buf;
-                    }
-                    else{
-                        return Err(PackableError { 
-                            error_kind: ErrorKind::BufferLengthError, 
-                            data: format!("except {} bytes and get {}", size, buffer.len())
-                        })
-                    }
+                    offset += log.unpack_field(stringify!($x), &mut $x, data, offset, litle_endian)?;
                 )*
-                Ok(())
+                Ok::<usize, $crate::PackableError>(offset)
+            })()
+        }
+    };
+}
+
+/// Pack each value in turn straight into a `std::io::Write`, as with
+/// `Packable::pack_to_writer`.
+#[macro_export]
+macro_rules! pack_to {
+    ( $le:expr, $writer:expr, $( $x:expr ),* ) => {
+        {
+            let litle_endian: bool = $crate::Endian::from($le).is_little();
+            let writer = $writer;
+            (|| {
+                $(
+                    $crate::Packable::pack_to_writer(&$x, &mut *writer, litle_endian)?;
+                )*
+                Ok::<(), ::std::io::Error>(())
             })()
         }
     };
 }
 
 use core::fmt;
-use std::{mem, array::TryFromSliceError};
+use core::{mem::MaybeUninit, ptr, slice};
+use core::num::{NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroU128, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8};
+use std::{mem, array::TryFromSliceError, io::{self, Read, Write}, sync::atomic::{AtomicBool, Ordering}};
+
+mod accounting;
+mod aligned;
+mod broadcast;
+mod bytes;
+mod chain;
+mod checked;
+mod chunked;
+mod codec;
+mod columnar;
+mod const_default;
+mod delta;
+mod detect;
+mod diagnose;
+mod endian;
+mod escape;
+mod ext;
+mod float;
+mod index;
+mod intern;
+mod ip;
+mod key_value;
+mod layout;
+mod lazy;
+mod map;
+mod marked;
+mod migrate;
+mod mock;
+mod net;
+mod option;
+mod pack_as;
+mod packed_size;
+mod packet;
+mod padding;
+mod profile;
+mod region;
+mod ring;
+mod rle;
+mod sample;
+mod scaled;
+mod schema;
+mod series;
+mod status;
+mod stream;
+mod string;
+mod until_end;
+mod vec;
+mod vectors;
+mod verify;
+mod vlq;
+
+#[cfg(feature = "audit")]
+mod audit;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "fuzz")]
+mod fuzz;
+#[cfg(feature = "heapless")]
+mod stack;
+
+#[cfg(feature = "audit")]
+pub use audit::{AuditLog, FieldRecord};
+#[cfg(feature = "derive")]
+pub use packable_derive::{Columnar, DefaultPacked, LayoutInfo, Migrate, PackedSize, Packable, Sample, Schema, View, vector_tests};
+pub use accounting::{CountingAlloc, allocated_during};
+pub use aligned::{AlignedBuf, pack_aligned};
+pub use broadcast::{Broadcast, Frame};
+pub use bytes::Bytes;
+pub use chain::Chain;
+pub use checked::{Checked, Checksum, Crc32};
+pub use chunked::{ChunkedPacker, ChunkedUnpacker};
+pub use codec::{Codec, Framing, LengthPrefix, Stats, transcode};
+pub use columnar::Columnar;
+pub use const_default::ConstDefault;
+pub use delta::{Delta, ZigZag};
+pub use detect::{Detection, Detector, Signature};
+pub use diagnose::from_bytes_validated;
+pub use endian::{Be, Endian, Le};
+pub use escape::EscapeMap;
+pub use ext::PackableExt;
+pub use float::{Bf16, Decimal32};
+pub use index::{RecordIndex, unpack_indexed};
+pub use intern::{Interned, Interner, interning};
+pub use key_value::KeyValue;
+pub use layout::{FieldLayout, LayoutInfo, LayoutWarning, analyze, endian_neutral, explain};
+pub use lazy::Lazy;
+pub use marked::{Marked, OrderMark, Tiff};
+pub use migrate::{migrate, migrate_records};
+pub use mock::MockPeer;
+pub use net::{recv_framed, recv_packed, recv_packed_from, send_framed, send_packed, send_packed_to};
+pub use option::Trailing;
+pub use pack_as::{PackAs, PackAsI32, PackAsI64, PackAsU32, PackAsU64};
+pub use packed_size::PackedSize;
+pub use packet::PacketBuf;
+pub use padding::Padding;
+pub use profile::{WireProfile, from_bytes_for, to_bytes_for};
+pub use region::Region;
+pub use ring::RingBuffer;
+pub use rle::Rle;
+pub use sample::{Sample, SampleRng};
+pub use scaled::{RangePolicy, RawCount, Rounding, Scale, Scaled, from_raw_count, to_raw_count};
+pub use schema::{Schema, schema_combine, schema_hash};
+pub use series::{BlockHeader, SeriesReader, SeriesWriter, Timestamped};
+pub use status::StatusPayload;
+pub use stream::{HashWriter, pack_iter, pack_iter_counted};
+pub use string::{FixedString, PrefixedString, Utf16String};
+pub use until_end::{Remaining, UntilEnd};
+pub use vec::LenPrefixed;
+pub use vectors::{TestVector, parse_vectors, check_vectors};
+pub use verify::{Report, verify, verify_file};
+pub use vlq::Vlq;
+#[cfg(feature = "fuzz")]
+pub use fuzz::fuzz_unpack;
 
 pub trait Packable {
     fn pack(&self, litle_endian: bool) -> Vec<u8>;
+
+    /// Append the packed bytes to `out`. The default goes through `pack`; types
+    /// made of other values override it to write their parts straight into `out`,
+    /// so packing a whole message allocates once.
+    fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool){
+        out.extend_from_slice(&self.pack(litle_endian));
+    }
+
+    /// Write the packed bytes to `writer`. The default writes `pack` in one go;
+    /// sequences and derived structs override it so a large message is never held
+    /// in memory whole. That takes several writes, so wrap unbuffered writers such
+    /// as sockets in a `BufWriter`.
+    fn pack_to_writer<W: Write + ?Sized>(&self, writer: &mut W, litle_endian: bool) -> io::Result<()>
+    where
+        Self: Sized,
+    {
+        writer.write_all(&self.pack(litle_endian))
+    }
+
+    /// Packed size of the current value. After `unpack` this is the number of bytes
+    /// the value was decoded from.
     fn size(&self) -> usize;
+
+    /// Decode into an existing value. Implementations overwrite `self` in place, so a
+    /// decoder that keeps its messages around between calls can reuse their allocations.
+    /// Implementations for growable containers must clear and refill their existing
+    /// buffer rather than replacing it, so steady-state decoding does not allocate.
+    /// `data` may be shorter than the current `size()`, since the previous value may
+    /// have been larger: check its length rather than indexing into it.
     fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError>;
+
+    /// Decode from the front of `buf`, returning the number of bytes used. This is
+    /// how values are read out of a larger buffer. The default copies `buf` into
+    /// a `Vec` for `unpack`; types that may sit inside large buffers override it to
+    /// read in place, and implement `unpack` on top of it.
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError>{
+        self.unpack(&mut buf.to_vec(), litle_endian)?;
+        let consumed = self.size();
+        if buf.len() < consumed{
+            return Err(buffer_length_error(consumed, buf.len()))
+        }
+        Ok(consumed)
+    }
+
+    /// Decode into a fresh value. Simpler when the result is not kept around, but it
+    /// starts from `Default` every time instead of reusing a previous value.
+    ///
+    /// # In place or fresh
+    ///
+    /// Reusing a value through `unpack` or `unpack_from` pays off as soon as it holds
+    /// a `String` or `Vec`: once their buffers have grown to the largest message seen,
+    /// decoding allocates nothing. For plain integers and arrays the two are the same.
+    /// Per decode of a derived `{ id: u32, name: String, samples: Vec<u16> }`, release
+    /// build on one x86-64 core:
+    ///
+    /// | samples | `unpack_new` | reused value |
+    /// |---------|--------------|--------------|
+    /// | 0       | 30 ns        | 13 ns        |
+    /// | 16      | 110 ns       | 56 ns        |
+    /// | 256     | 818 ns       | 596 ns       |
+    ///
+    /// and 18 ns against 17 ns for a fixed 17-byte struct of integers.
+    fn unpack_new(data: &mut Vec<u8>, litle_endian: bool) -> Result<Self, PackableError>
+    where
+        Self: Sized + Default,
+    {
+        let mut value = Self::default();
+        value.unpack(data, litle_endian)?;
+        Ok(value)
+    }
+}
+
+static DEFAULT_LITLE_ENDIAN: AtomicBool = AtomicBool::new(false);
+
+/// Process-wide endianness for code that does not want to thread the flag through
+/// every layer: `Endian::Global` passed to `pack!`, `unpack!`, `to_bytes` or
+/// `from_bytes` reads it, as does `Codec::default()`. Starts out big-endian;
+/// explicit byte orders always take precedence.
+pub fn set_default_endianness(litle_endian: bool){
+    DEFAULT_LITLE_ENDIAN.store(litle_endian, Ordering::Relaxed)
+}
+
+pub fn default_endianness() -> bool{
+    DEFAULT_LITLE_ENDIAN.load(Ordering::Relaxed)
+}
+
+pub fn to_bytes<T: Packable + ?Sized>(value: &T, endian: impl Into<Endian>) -> Vec<u8>{
+    value.pack(endian.into().is_little())
+}
+
+/// Pack `value` into the front of a possibly uninitialized buffer and return the
+/// part that was written, avoiding a zero-fill of large scratch buffers.
+pub fn pack_uninit<'a, T: Packable + ?Sized>(value: &T, endian: impl Into<Endian>, out: &'a mut [MaybeUninit<u8>]) -> Result<&'a mut [u8], PackableError>{
+    let litle_endian = endian.into().is_little();
+    let data = value.pack(litle_endian);
+    if out.len() < data.len(){
+        return Err(PackableError { direction: Some(Direction::Pack), ..buffer_length_error(data.len(), out.len()) })
+    }
+    // SAFETY: the first data.len() bytes of out are written right here, after which
+    // they are initialized and borrowed for as long as out is.
+    unsafe {
+        let ptr = out.as_mut_ptr() as *mut u8;
+        ptr::copy_nonoverlapping(data.as_ptr(), ptr, data.len());
+        Ok(slice::from_raw_parts_mut(ptr, data.len()))
+    }
+}
+
+pub fn from_bytes<T: Packable + Default>(data: &[u8], endian: impl Into<Endian>) -> Result<T, PackableError>{
+    let litle_endian = endian.into().is_little();
+    let mut value = T::default();
+    let size = value.size();
+    if data.len() < size{
+        return Err(buffer_length_error(size, data.len()))
+    }
+    value.unpack(&mut data.to_vec(), litle_endian)?;
+    Ok(value)
+}
+
+/// Decode a single fixed-size field located `offset` bytes into `data`, without
+/// decoding anything around it.
+pub fn from_bytes_at<T: Packable + Default>(data: &[u8], offset: usize, endian: impl Into<Endian>) -> Result<T, PackableError>{
+    let litle_endian = endian.into().is_little();
+    let mut value = T::default();
+    let size = value.size();
+    let field = data.get(offset..).and_then(|data| data.get(..size))
+        .ok_or_else(|| buffer_length_error(offset + size, data.len()))?;
+    value.unpack(&mut field.to_vec(), litle_endian)?;
+    Ok(value)
+}
+
+/// Largest batch of packed elements `write_chunked` holds before writing it out.
+const WRITE_CHUNK: usize = 8 * 1024;
+
+/// Pack `items` with `pack` into a bounded buffer, writing it out as it fills, for
+/// `pack_to_writer` on sequences.
+pub(crate) fn write_chunked<W, I, F>(writer: &mut W, head: &[u8], items: I, mut pack: F) -> io::Result<()>
+where
+    W: Write + ?Sized,
+    I: IntoIterator,
+    F: FnMut(I::Item, &mut Vec<u8>),
+{
+    let mut chunk = Vec::with_capacity(WRITE_CHUNK);
+    chunk.extend_from_slice(head);
+    for item in items{
+        pack(item, &mut chunk);
+        if chunk.len() >= WRITE_CHUNK{
+            writer.write_all(&chunk)?;
+            chunk.clear();
+        }
+    }
+    writer.write_all(&chunk)
+}
+
+/// Input accepted by `unpack!`: anything that can hand out the next `size` bytes.
+pub trait UnpackSource {
+    fn take(&mut self, size: usize) -> Result<Vec<u8>, PackableError>;
+
+    /// Unpack `value` from the front of the source and consume the bytes it used.
+    /// The default reads exactly `value.size()` bytes up front, which only suits
+    /// fixed-size values; in-memory sources let variable-size values see everything.
+    fn unpack_into<T: Packable + ?Sized>(&mut self, value: &mut T, litle_endian: bool) -> Result<usize, PackableError>{
+        let size = value.size();
+        let mut field = self.take(size)?;
+        value.unpack(&mut field, litle_endian)?;
+        Ok(size)
+    }
+
+    /// Run `fields` over the source, for `unpack!`. In-memory sources override this
+    /// to hand out a cursor over their bytes and consume what was used at the end.
+    fn unpack_fields(&mut self, fields: &mut dyn FnMut(&mut dyn FieldSource) -> Result<usize, PackableError>) -> Result<usize, PackableError>
+    where
+        Self: Sized,
+    {
+        fields(self)
+    }
+}
+
+/// An `UnpackSource` behind `dyn`, as `unpack!` sees it.
+pub trait FieldSource{
+    fn field(&mut self, value: &mut dyn Packable, litle_endian: bool) -> Result<usize, PackableError>;
+}
+
+impl<S: UnpackSource> FieldSource for S{
+    fn field(&mut self, value: &mut dyn Packable, litle_endian: bool) -> Result<usize, PackableError>{
+        self.unpack_into(value, litle_endian)
+    }
+}
+
+fn buffer_length_error(size: usize, len: usize) -> PackableError{
+    PackableError { 
+        error_kind: ErrorKind::BufferLengthError, 
+        data: format!("except {} bytes and get {}", size, len),
+        direction: Some(Direction::Unpack),
+    }
+}
+
+impl UnpackSource for Vec<u8>{
+    fn take(&mut self, size: usize) -> Result<Vec<u8>, PackableError> {
+        if self.len() < size{
+            return Err(buffer_length_error(size, self.len()))
+        }
+        let rest = self.split_off(size);
+        Ok(mem::replace(self, rest))
+    }
+
+    fn unpack_into<T: Packable + ?Sized>(&mut self, value: &mut T, litle_endian: bool) -> Result<usize, PackableError>{
+        let consumed = value.unpack_from(self, litle_endian)?;
+        self.drain(..consumed);
+        Ok(consumed)
+    }
+
+    fn unpack_fields(&mut self, fields: &mut dyn FnMut(&mut dyn FieldSource) -> Result<usize, PackableError>) -> Result<usize, PackableError>{
+        let mut cursor: &[u8] = self;
+        let consumed = fields(&mut cursor)?;
+        self.drain(..consumed);
+        Ok(consumed)
+    }
+}
+
+impl UnpackSource for &[u8]{
+    fn take(&mut self, size: usize) -> Result<Vec<u8>, PackableError> {
+        if self.len() < size{
+            return Err(buffer_length_error(size, self.len()))
+        }
+        let (head, tail) = self.split_at(size);
+        *self = tail;
+        Ok(head.to_vec())
+    }
+
+    fn unpack_into<T: Packable + ?Sized>(&mut self, value: &mut T, litle_endian: bool) -> Result<usize, PackableError>{
+        let consumed = value.unpack_from(self, litle_endian)?;
+        *self = &self[consumed..];
+        Ok(consumed)
+    }
+}
+
+/// Read one fixed-size value from `reader`, exactly `size()` bytes of its default,
+/// without buffering the input first. Read failures surface as `Io`, and input
+/// that ends early as `BufferLengthError`.
+pub fn unpack_from_reader<T: Packable + Default, R: Read>(reader: R, endian: impl Into<Endian>) -> Result<T, PackableError>{
+    let litle_endian = endian.into().is_little();
+    let mut value = T::default();
+    Reader(reader).unpack_into(&mut value, litle_endian)?;
+    Ok(value)
+}
+
+/// Adapter to use any `std::io::Read` as an `unpack!` source.
+pub struct Reader<R>(pub R);
+
+impl<R: Read> UnpackSource for Reader<R>{
+    fn take(&mut self, size: usize) -> Result<Vec<u8>, PackableError> {
+        let mut buffer = vec![0; size];
+        self.0.read_exact(&mut buffer).map_err(|error| match error.kind(){
+            io::ErrorKind::UnexpectedEof => PackableError { 
+                error_kind: ErrorKind::BufferLengthError, 
+                data: format!("except {} bytes: {}", size, error),
+                direction: Some(Direction::Unpack),
+            },
+            _ => PackableError::from(error).during(Direction::Unpack),
+        })?;
+        Ok(buffer)
+    }
 }
 
 macro_rules! impl_packable_numerique {
@@ -59,19 +514,43 @@ macro_rules! impl_packable_numerique {
                     self.to_be_bytes().to_vec()
                 }
              }
+
+            fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool){
+                if litle_endian{
+                    out.extend_from_slice(&self.to_le_bytes())
+                }
+                else{
+                    out.extend_from_slice(&self.to_be_bytes())
+                }
+            }
+
+            fn pack_to_writer<W: Write + ?Sized>(&self, writer: &mut W, litle_endian: bool) -> io::Result<()> {
+                if litle_endian{
+                    writer.write_all(&self.to_le_bytes())
+                }
+                else{
+                    writer.write_all(&self.to_be_bytes())
+                }
+            }
         
             fn size(&self) -> usize {
                 mem::size_of::<$le>()
             }
         
             fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError>{
+                self.unpack_from(data, litle_endian)?;
+                Ok(())
+            }
+
+            fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError>{
+                let bytes = buf.get(..self.size()).ok_or_else(|| buffer_length_error(self.size(), buf.len()))?;
                 if litle_endian{
-                    *self = <$le>::from_le_bytes(data[0..self.size()].try_into()?);
+                    *self = <$le>::from_le_bytes(bytes.try_into()?);
                 }
                 else{
-                    *self = <$le>::from_be_bytes(data[0..self.size()].try_into()?);
+                    *self = <$le>::from_be_bytes(bytes.try_into()?);
                 }
-                Ok(())
+                Ok(bytes.len())
             }
         }
      };
@@ -90,71 +569,431 @@ impl_packable_numerique!(i128);
 impl_packable_numerique!(f32);
 impl_packable_numerique!(f64);
 
-impl<const DIMENSIONS: usize> Packable for [u8; DIMENSIONS]{
-    fn pack(&self, _litle_endian: bool) -> Vec<u8> {
-        self.to_vec()
+/// Packed like the underlying integer; unpacking a zero fails with `ZeroValue`.
+macro_rules! impl_packable_non_zero {
+    ( $( $t:ty => $int:ty ),* ) => {
+        $(
+            impl Packable for $t {
+                fn pack(&self, litle_endian: bool) -> Vec<u8> {
+                    self.get().pack(litle_endian)
+                }
+
+                fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+                    self.get().pack_into(out, litle_endian)
+                }
+
+                fn size(&self) -> usize {
+                    mem::size_of::<$int>()
+                }
+
+                fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+                    self.unpack_from(data, litle_endian)?;
+                    Ok(())
+                }
+
+                fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+                    let mut value: $int = 0;
+                    let consumed = value.unpack_from(buf, litle_endian)?;
+                    *self = <$t>::new(value).ok_or_else(|| PackableError { 
+                        error_kind: ErrorKind::ZeroValue, 
+                        data: format!("zero for {}", stringify!($t)),
+                        direction: Some(Direction::Unpack),
+                    })?;
+                    Ok(consumed)
+                }
+            }
+        )*
+    };
+}
+
+impl_packable_non_zero!(
+    NonZeroU8 => u8, NonZeroU16 => u16, NonZeroU32 => u32, NonZeroU64 => u64, NonZeroU128 => u128,
+    NonZeroI8 => i8, NonZeroI16 => i16, NonZeroI32 => i32, NonZeroI64 => i64, NonZeroI128 => i128
+);
+
+/// Elements one after the other, each in the requested byte order.
+impl<T: Packable, const DIMENSIONS: usize> Packable for [T; DIMENSIONS]{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.size());
+        self.pack_into(&mut data, litle_endian);
+        data
+    }
+
+    fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+        for element in self{
+            element.pack_into(out, litle_endian);
+        }
+    }
+
+    fn pack_to_writer<W: Write + ?Sized>(&self, writer: &mut W, litle_endian: bool) -> io::Result<()> {
+        write_chunked(writer, &[], self, |element, chunk| element.pack_into(chunk, litle_endian))
     }
 
     fn size(&self) -> usize {
-        self.len()
+        self.iter().map(Packable::size).sum()
     }
 
-    fn unpack(&mut self, data: &mut Vec<u8>, _litle_endian: bool) -> Result<(), PackableError> {
-        let value = &mut data[..self.size()];
-        self.clone_from_slice(value);
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
         Ok(())
     }
-}
 
-#[derive(Debug, Default)]
-pub struct Flag{
-    base: u8,
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        let mut source = buf;
+        for element in self{
+            source.unpack_into(element, litle_endian)?;
+        }
+        Ok(buf.len() - source.len())
+    }
 }
 
-impl Flag{
-    pub fn set(&mut self, id: u8, value: bool){
-        if value{
-            self.base = self.base|(0x1<<id)
-        }
-        else{
-            self.base = self.base&!(0x1<<id)
+/// Elements one after the other, in order.
+macro_rules! impl_packable_tuple {
+    ( $( $name:ident $index:tt ),+ ) => {
+        impl<$( $name: Packable ),+> Packable for ( $( $name, )+ ) {
+            fn pack(&self, litle_endian: bool) -> Vec<u8> {
+                let mut data = Vec::with_capacity(self.size());
+                self.pack_into(&mut data, litle_endian);
+                data
+            }
+
+            fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+                $( self.$index.pack_into(out, litle_endian); )+
+            }
+
+            fn pack_to_writer<W: Write + ?Sized>(&self, writer: &mut W, litle_endian: bool) -> io::Result<()> {
+                $( self.$index.pack_to_writer(writer, litle_endian)?; )+
+                Ok(())
+            }
+
+            fn size(&self) -> usize {
+                0 $( + self.$index.size() )+
+            }
+
+            fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+                self.unpack_from(data, litle_endian)?;
+                Ok(())
+            }
+
+            fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+                let mut source = buf;
+                $( source.unpack_into(&mut self.$index, litle_endian)?; )+
+                Ok(buf.len() - source.len())
+            }
         }
+    };
+}
+
+impl_packable_tuple!(A 0);
+impl_packable_tuple!(A 0, B 1);
+impl_packable_tuple!(A 0, B 1, C 2);
+impl_packable_tuple!(A 0, B 1, C 2, D 3);
+impl_packable_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_packable_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+impl_packable_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_packable_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+impl_packable_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+impl_packable_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+impl_packable_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+impl_packable_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+
+/// One byte, 1 for true. Any non-zero byte unpacks as true; see `StrictBool`.
+impl Packable for bool{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        (*self as u8).pack(litle_endian)
+    }
+
+    fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+        (*self as u8).pack_into(out, litle_endian)
     }
 
-    pub fn get(&self, id: u8) -> bool{
-        (self.base&(0x1<<id))>0
+    fn size(&self) -> usize {
+        1
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        let mut byte = 0u8;
+        let consumed = byte.unpack_from(buf, litle_endian)?;
+        *self = byte != 0;
+        Ok(consumed)
     }
 }
 
-impl Packable for Flag{
+/// A `u32` code point; surrogates and values above `char::MAX` fail to unpack
+/// with `InvalidChar`.
+impl Packable for char{
     fn pack(&self, litle_endian: bool) -> Vec<u8> {
-        self.base.pack(litle_endian)
+        (*self as u32).pack(litle_endian)
+    }
+
+    fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+        (*self as u32).pack_into(out, litle_endian)
     }
 
     fn size(&self) -> usize {
-        self.base.size()
+        mem::size_of::<u32>()
     }
 
     fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
-        self.base.unpack(data, litle_endian)
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        let mut code = 0u32;
+        let consumed = code.unpack_from(buf, litle_endian)?;
+        *self = char::from_u32(code).ok_or_else(|| PackableError { 
+            error_kind: ErrorKind::InvalidChar, 
+            data: format!("{:#x} is not a unicode scalar value", code),
+            direction: Some(Direction::Unpack),
+        })?;
+        Ok(consumed)
     }
 }
 
-#[derive(Debug)]
+/// A `bool` that only unpacks from 0 or 1, failing with `InvalidValue` otherwise.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StrictBool(pub bool);
+
+impl Packable for StrictBool{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        self.0.pack(litle_endian)
+    }
+
+    fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+        self.0.pack_into(out, litle_endian)
+    }
+
+    fn size(&self) -> usize {
+        self.0.size()
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        let mut byte = 0u8;
+        let consumed = byte.unpack_from(buf, litle_endian)?;
+        self.0 = match byte{
+            0 => false,
+            1 => true,
+            _ => return Err(PackableError { 
+                error_kind: ErrorKind::InvalidValue, 
+                data: format!("{:#04x} is not a boolean", byte),
+                direction: Some(Direction::Unpack),
+            }),
+        };
+        Ok(consumed)
+    }
+}
+
+/// Bit flags packed as a `T`, one of `u8` (the default) to `u64`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Flag<T = u8>{
+    base: T,
+}
+
+macro_rules! impl_flag {
+    ( $( $t:ty ),* ) => {
+        $(
+            impl Flag<$t>{
+                pub fn set(&mut self, id: u8, value: bool){
+                    if value{
+                        self.base |= 0x1<<id
+                    }
+                    else{
+                        self.base &= !(0x1<<id)
+                    }
+                }
+
+                pub fn get(&self, id: u8) -> bool{
+                    (self.base&(0x1<<id))>0
+                }
+
+                fn range_mask(hi: u8, lo: u8) -> $t{
+                    assert!(lo <= hi && (hi as u32) < <$t>::BITS, "invalid bit range {}..={}", lo, hi);
+                    <$t>::MAX >> (<$t>::BITS - 1 - (hi - lo) as u32)
+                }
+
+                /// Write `value` into bits `lo` to `hi` inclusive, e.g. a multi-bit
+                /// field of a register. Bits of `value` beyond the field are dropped.
+                ///
+                /// # Panics
+                ///
+                /// Panics if `lo > hi` or `hi` is outside the flags.
+                pub fn set_range(&mut self, hi: u8, lo: u8, value: $t){
+                    let mask = Self::range_mask(hi, lo);
+                    self.base = (self.base & !(mask << lo)) | ((value & mask) << lo);
+                }
+
+                /// Bits `lo` to `hi` inclusive, shifted down to start at bit 0.
+                ///
+                /// # Panics
+                ///
+                /// Panics if `lo > hi` or `hi` is outside the flags.
+                pub fn get_range(&self, hi: u8, lo: u8) -> $t{
+                    (self.base >> lo) & Self::range_mask(hi, lo)
+                }
+            }
+
+            impl Packable for Flag<$t>{
+                fn pack(&self, litle_endian: bool) -> Vec<u8> {
+                    self.base.pack(litle_endian)
+                }
+
+                fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+                    self.base.pack_into(out, litle_endian)
+                }
+
+                fn size(&self) -> usize {
+                    self.base.size()
+                }
+
+                fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+                    self.base.unpack(data, litle_endian)
+                }
+
+                fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+                    self.base.unpack_from(buf, litle_endian)
+                }
+            }
+        )*
+    };
+}
+
+impl_flag!(u8, u16, u32, u64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ErrorKind{
     TryFromSliceError,
     BufferLengthError,
+    OutOfRange,
+    LimitExceeded,
+    TrailingBytes,
+    Io(io::ErrorKind),
+    InvalidEncoding,
+    InvalidValue,
+    EndiannessMismatch,
+    InvalidDiscriminant,
+    InvalidChar,
+    ZeroValue,
+    ChecksumMismatch,
+    InvalidUtf8,
+}
+
+/// Which way the data was going when an error occurred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction{
+    Pack,
+    Unpack,
 }
 
 #[derive(Debug)]
 pub struct PackableError{
     pub error_kind: ErrorKind,
     pub data: String,
+    /// `None` when the error is not tied to either, such as an invalid argument.
+    pub direction: Option<Direction>,
+}
+
+/// Stable numeric codes for bindings. Values are never reused or renumbered.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode{
+    Ok = 0,
+    TryFromSliceError = 1,
+    BufferLengthError = 2,
+    OutOfRange = 3,
+    LimitExceeded = 4,
+    TrailingBytes = 5,
+    Io = 6,
+    InvalidArgument = 7,
+    UnknownType = 8,
+    InvalidEncoding = 9,
+    InvalidValue = 10,
+    EndiannessMismatch = 11,
+    InvalidDiscriminant = 12,
+    InvalidChar = 13,
+    ZeroValue = 14,
+    ChecksumMismatch = 15,
+    InvalidUtf8 = 16,
+}
+
+impl ErrorKind{
+    pub fn code(&self) -> ErrorCode{
+        match self{
+            ErrorKind::TryFromSliceError => ErrorCode::TryFromSliceError,
+            ErrorKind::BufferLengthError => ErrorCode::BufferLengthError,
+            ErrorKind::OutOfRange => ErrorCode::OutOfRange,
+            ErrorKind::LimitExceeded => ErrorCode::LimitExceeded,
+            ErrorKind::TrailingBytes => ErrorCode::TrailingBytes,
+            ErrorKind::Io(_) => ErrorCode::Io,
+            ErrorKind::InvalidEncoding => ErrorCode::InvalidEncoding,
+            ErrorKind::InvalidValue => ErrorCode::InvalidValue,
+            ErrorKind::EndiannessMismatch => ErrorCode::EndiannessMismatch,
+            ErrorKind::InvalidDiscriminant => ErrorCode::InvalidDiscriminant,
+            ErrorKind::InvalidChar => ErrorCode::InvalidChar,
+            ErrorKind::ZeroValue => ErrorCode::ZeroValue,
+            ErrorKind::ChecksumMismatch => ErrorCode::ChecksumMismatch,
+            ErrorKind::InvalidUtf8 => ErrorCode::InvalidUtf8,
+        }
+    }
+}
+
+impl PackableError{
+    pub fn code(&self) -> u32{
+        self.error_kind.code() as u32
+    }
+
+    /// Record `direction` unless the error already knows its own.
+    pub(crate) fn during(mut self, direction: Direction) -> Self{
+        self.direction.get_or_insert(direction);
+        self
+    }
 }
 
 impl fmt::Display for PackableError{
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "packable error {:?} {}", self.error_kind, self.data)
+        match self.direction{
+            Some(Direction::Pack) => write!(f, "packable error while packing {:?} {}", self.error_kind, self.data),
+            Some(Direction::Unpack) => write!(f, "packable error while unpacking {:?} {}", self.error_kind, self.data),
+            None => write!(f, "packable error {:?} {}", self.error_kind, self.data),
+        }
+    }
+}
+
+impl std::error::Error for PackableError{}
+
+/// An `io::Error` that was itself made from a `PackableError` gives it back unchanged.
+impl From<io::Error> for PackableError{
+    fn from(error: io::Error) -> Self {
+        let kind = error.kind();
+        let data = format!("{}", error);
+        match error.into_inner().map(|inner| inner.downcast::<PackableError>()){
+            Some(Ok(inner)) => *inner,
+            _ => PackableError { 
+                error_kind: ErrorKind::Io(kind), 
+                data,
+                direction: None,
+            },
+        }
+    }
+}
+
+/// `Io` errors keep their original kind, every other error becomes `InvalidData`.
+impl From<PackableError> for io::Error{
+    fn from(error: PackableError) -> Self {
+        match error.error_kind{
+            ErrorKind::Io(kind) => io::Error::new(kind, error.data),
+            _ => io::Error::new(io::ErrorKind::InvalidData, error),
+        }
     }
 }
 
@@ -162,14 +1001,15 @@ impl From<TryFromSliceError> for PackableError{
     fn from(error: TryFromSliceError) -> Self {
         PackableError { 
             error_kind: ErrorKind::TryFromSliceError, 
-            data: format!("{}", error) 
+            data: format!("{}", error),
+            direction: Some(Direction::Unpack),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::Packable;
+    use crate::{Packable, Direction, ErrorKind};
 
     #[test]
     fn it_works() {
@@ -230,4 +1070,983 @@ mod tests {
         assert_eq!(vec![64, 69, 94, 184, 81, 235, 133, 31], pack!(false, 42.74f64));
         assert_eq!(vec![31, 133, 235, 81, 184, 94, 69, 64], pack!(true, 42.74f64));
     }
+
+    #[test]
+    fn test_to_from_bytes(){
+        assert_eq!(vec![0, 0, 0, 42], crate::to_bytes(&42u32, false));
+        assert_eq!(42u32, crate::from_bytes::<u32>(&[42, 0, 0, 0], true).unwrap());
+        let error = crate::from_bytes::<u32>(&[42, 0], true).unwrap_err();
+        assert!(matches!(error.error_kind, ErrorKind::BufferLengthError));
+    }
+
+    #[test]
+    fn test_pack_uninit(){
+        let mut buffer = [std::mem::MaybeUninit::<u8>::uninit(); 8];
+        assert_eq!(&[0, 0, 1, 2], crate::pack_uninit(&0x0102u32, false, &mut buffer).unwrap());
+        let error = crate::pack_uninit(&0u64, false, &mut buffer[..4]).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+    }
+
+    #[test]
+    fn test_from_bytes_at(){
+        let data = [1, 0, 2, 0, 0, 0, 3];
+        assert_eq!(2u16, crate::from_bytes_at(&data, 2, true).unwrap());
+        assert_eq!(3u32, crate::from_bytes_at(&data, 3, false).unwrap());
+        assert_eq!(3u32, crate::from_bytes_at(&data, 3, crate::Endian::Big).unwrap());
+        assert_eq!(crate::from_bytes_at::<u16>(&data, 2, cfg!(target_endian = "little")).unwrap(), crate::from_bytes_at(&data, 2, crate::Endian::Native).unwrap());
+        let error = crate::from_bytes_at::<u32>(&data, 4, false).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+    }
+
+    #[test]
+    fn test_unpack_sources(){
+        let (mut a, mut b) = (0u8, 0u16);
+        let mut vec = vec![1, 0, 2, 3];
+        assert_eq!(3, unpack!(false, &mut vec, a, b).unwrap());
+        assert_eq!((1, 2), (a, b));
+        assert_eq!(vec![3], vec);
+
+        let mut slice: &[u8] = &[4, 5, 0, 9];
+        unpack!(true, &mut slice, a, b).unwrap();
+        assert_eq!((4, 5), (a, b));
+        assert_eq!(&[9], slice);
+
+        let mut reader = crate::Reader(std::io::Cursor::new(vec![6, 0, 7]));
+        unpack!(false, &mut reader, a, b).unwrap();
+        assert_eq!((6, 7), (a, b));
+
+        let error = unpack!(false, &mut reader, a).unwrap_err();
+        assert!(matches!(error.error_kind, ErrorKind::BufferLengthError));
+
+        let mut vec = vec![1, 0];
+        assert!(unpack!(false, &mut vec, a, b).is_err());
+        assert_eq!(vec![1, 0], vec);
+    }
+
+    #[test]
+    fn test_pack_into(){
+        let mut out = vec![9];
+        (1u8, vec![2u16], Some('a')).pack_into(&mut out, false);
+        assert_eq!(vec![9, 1, 0, 0, 0, 1, 0, 2, 1, 0, 0, 0, b'a'], out);
+
+        let (id, samples, name) = (7u32, [1u16; 64], "sensor".to_string());
+        let (data, allocated) = crate::allocated_during(|| pack!(true, id, samples, name));
+        assert_eq!(4 + 128 + 10, data.len());
+        assert_eq!(data.len(), allocated);
+    }
+
+    #[test]
+    fn test_pack_to_writer(){
+        struct Largest(Vec<u8>, usize);
+
+        impl std::io::Write for Largest{
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.1 = self.1.max(buf.len());
+                self.0.write(buf)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let samples: Vec<u16> = (0..20_000).collect();
+        let mut writer = Largest(Vec::new(), 0);
+        pack_to!(false, &mut writer, 7u8, samples).unwrap();
+        assert_eq!(pack!(false, 7u8, samples), writer.0);
+        assert!(writer.1 < 2 * 8 * 1024);
+
+        let mut file = Vec::new();
+        (1u8, [2u16; 2]).pack_to_writer(&mut file, true).unwrap();
+        assert_eq!(vec![1, 2, 0, 2, 0], file);
+    }
+
+    #[test]
+    fn test_unpack_from(){
+        let mut value = (0u8, vec![0u16; 0], String::new());
+        assert_eq!(14, value.unpack_from(&[1, 0, 0, 0, 2, 0, 3, 0, 4, 0, 0, 0, 1, b'x', 9], false).unwrap());
+        assert_eq!((1, vec![3, 4], "x".to_string()), value);
+
+        let data = vec![0u16; 100_000].pack(false);
+        let mut items = vec![0u16; 100_000];
+        let (consumed, allocated) = crate::allocated_during(|| items.unpack_from(&data, false).unwrap());
+        assert_eq!(data.len(), consumed);
+        assert_eq!(0, allocated);
+
+        let data = vec![crate::Bf16(1); 100_000].pack(false);
+        let mut items = vec![crate::Bf16::default(); 100_000];
+        let (consumed, allocated) = crate::allocated_during(|| items.unpack_from(&data, false).unwrap());
+        assert_eq!(data.len(), consumed);
+        assert_eq!(0, allocated);
+        let data = vec![crate::Vlq::<4>::new(300).unwrap(); 100_000].pack(false);
+        let mut items = vec![crate::Vlq::<4>::default(); 100_000];
+        let (consumed, allocated) = crate::allocated_during(|| items.unpack_from(&data, false).unwrap());
+        assert_eq!(data.len(), consumed);
+        assert_eq!(0, allocated);
+    }
+
+    #[test]
+    fn test_unpack_from_reader(){
+        struct Broken;
+
+        impl std::io::Read for Broken{
+            fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::ConnectionReset.into())
+            }
+        }
+
+        let mut port = std::io::Cursor::new(vec![1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!((0x0102u16, 3u8), crate::unpack_from_reader(&mut port, false).unwrap());
+        let (mut a, mut b) = (0u8, 0u16);
+        assert_eq!(3, unpack_reader!(true, &mut port, a, b).unwrap());
+        assert_eq!((4, 0x0605), (a, b));
+        let error = crate::unpack_from_reader::<u16, _>(&mut port, false).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+        let error = crate::unpack_from_reader::<u16, _>(Broken, false).unwrap_err();
+        assert_eq!(ErrorKind::Io(std::io::ErrorKind::ConnectionReset), error.error_kind);
+    }
+
+    #[test]
+    fn test_default_endianness(){
+        struct Restore(bool);
+
+        impl Drop for Restore{
+            fn drop(&mut self) {
+                crate::set_default_endianness(self.0);
+            }
+        }
+
+        let _restore = Restore(crate::default_endianness());
+        crate::set_default_endianness(true);
+        assert_eq!(vec![1, 0], crate::to_bytes(&1u16, crate::Endian::Global));
+        assert!(crate::Codec::default().is_litle_endian());
+        crate::set_default_endianness(false);
+        assert_eq!(vec![0, 1], pack!(crate::Endian::Global, 1u16));
+        assert_eq!(1u16, crate::from_bytes(&[0, 1], crate::Endian::Global).unwrap());
+    }
+
+    #[test]
+    fn test_io_error_interop(){
+        let error = crate::PackableError::from(std::io::Error::new(std::io::ErrorKind::BrokenPipe, "gone"));
+        assert!(matches!(error.error_kind, ErrorKind::Io(std::io::ErrorKind::BrokenPipe)));
+        assert_eq!(std::io::ErrorKind::BrokenPipe, std::io::Error::from(error).kind());
+
+        let error = crate::from_bytes::<u16>(&[], false).unwrap_err();
+        let error = std::io::Error::from(error);
+        assert_eq!(std::io::ErrorKind::InvalidData, error.kind());
+        assert!(error.into_inner().unwrap().downcast::<crate::PackableError>().is_ok());
+    }
+
+    #[test]
+    fn test_error_code(){
+        let error = crate::from_bytes::<u16>(&[], false).unwrap_err();
+        assert_eq!(2, error.code());
+        assert_eq!(crate::ErrorCode::Io, ErrorKind::Io(std::io::ErrorKind::Other).code());
+    }
+
+    #[test]
+    fn test_unpack_new(){
+        assert_eq!(0x1234, u16::unpack_new(&mut vec![0x12, 0x34], false).unwrap());
+        assert_eq!(0x3412, u16::unpack_new(&mut vec![0x12, 0x34], true).unwrap());
+
+        let mut data = vec![1, 0, 2, 3];
+        let (kind, length) = unpack_new!(false, &mut data, u8, u16).unwrap();
+        assert_eq!((1, 2), (kind, length));
+        assert_eq!(vec![3], data);
+        let flags: [u8; 1] = unpack_new!(false, &mut data, [u8; 1]).unwrap();
+        assert_eq!([3], flags);
+        assert!(unpack_new!(false, &mut vec![1], u8, u16).is_err());
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_unpack_reuses(){
+        #[derive(Debug, Default, PartialEq, crate::Packable)]
+        struct Message{
+            id: u32,
+            name: String,
+            samples: Vec<u16>,
+        }
+
+        let long = crate::to_bytes(&Message { id: 1, name: "pressure".to_string(), samples: vec![1, 2, 3] }, false);
+        let short = crate::to_bytes(&Message { id: 2, name: "temp".to_string(), samples: vec![4] }, false);
+        let (fresh, allocated) = crate::allocated_during(|| Message::unpack_new(&mut long.clone(), false).unwrap());
+        assert!(allocated > long.len());
+
+        let mut message = fresh;
+        for data in [&short, &long, &short]{
+            let (consumed, allocated) = crate::allocated_during(|| message.unpack_from(data, false).unwrap());
+            assert_eq!((data.len(), 0), (consumed, allocated));
+        }
+        assert_eq!(Message { id: 2, name: "temp".to_string(), samples: vec![4] }, message);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_struct(){
+        #[derive(Debug, Default, PartialEq, crate::Packable)]
+        struct Header<T>{
+            kind: u8,
+            body: T,
+            flags: [u8; 1],
+        }
+
+        #[derive(Debug, Default, PartialEq, crate::Packable)]
+        struct Pair(u16, Header<u16>);
+
+        #[derive(Default, crate::Packable)]
+        struct Empty;
+
+        #[derive(Debug, Default, PartialEq, crate::Packable)]
+        #[packable(crate = "crate")]
+        struct Local(u8);
+
+        assert_eq!(Local(5), crate::from_bytes(&[5], false).unwrap());
+
+        #[derive(Debug, Default, PartialEq, crate::Packable)]
+        struct Frame<W>{
+            width: W,
+        }
+
+        let mut out = Vec::new();
+        Frame { width: 0x0102u16 }.pack_to_writer(&mut out, false).unwrap();
+        assert_eq!(vec![1, 2], out);
+
+        let pair = Pair(0x0102, Header { kind: 3, body: 0x0405, flags: Default::default() });
+        assert_eq!(vec![1, 2, 3, 4, 5, 0], pair.pack(false));
+        assert_eq!(6, pair.size());
+        assert_eq!(pair, crate::from_bytes(&[1, 2, 3, 4, 5, 0], false).unwrap());
+        assert_eq!(ErrorKind::BufferLengthError, crate::from_bytes::<Pair>(&[1, 2, 3], false).unwrap_err().error_kind);
+        assert_eq!(0, Empty.size());
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_columnar(){
+        use crate::Columnar;
+
+        #[derive(Debug, Default, PartialEq, crate::Columnar)]
+        struct Sample{
+            id: u8,
+            value: u16,
+        }
+
+        let samples = [Sample { id: 1, value: 0x0203 }, Sample { id: 4, value: 0x0506 }];
+        let data = Sample::pack_columns(&samples, false);
+        assert_eq!(vec![1, 4, 2, 3, 5, 6], data);
+        assert_eq!(samples[..], Sample::unpack_columns(&data, 2, false).unwrap());
+        let error = Sample::unpack_columns(&data[..5], 2, false).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_enum(){
+        #[derive(Debug, Default, PartialEq, crate::Packable)]
+        #[repr(u16)]
+        enum Command{
+            #[default]
+            Ping = 0x01,
+            Data = 0x0102,
+            Close,
+        }
+
+        assert_eq!(vec![1, 2], Command::Data.pack(false));
+        assert_eq!(vec![3, 1], Command::Close.pack(true));
+        assert_eq!(2, Command::Ping.size());
+        assert_eq!(Command::Close, crate::from_bytes(&[1, 3], false).unwrap());
+        let error = crate::from_bytes::<Command>(&[0, 2], false).unwrap_err();
+        assert_eq!(ErrorKind::InvalidDiscriminant, error.error_kind);
+
+        #[derive(Debug, Default, PartialEq, crate::Packable)]
+        #[repr(u16, align(4))]
+        enum Aligned{
+            #[default]
+            Only = 7,
+        }
+
+        assert_eq!(vec![0, 7], Aligned::Only.pack(false));
+
+        #[derive(Debug, Default, PartialEq, crate::Packable)]
+        #[packable(tag = "u32")]
+        #[repr(u16)]
+        enum Wide{
+            #[default]
+            Ping = 0x01,
+            Data = 0x0102,
+        }
+
+        #[derive(Debug, Default, PartialEq, crate::Packable)]
+        #[packable(tag = "crate::Vlq<2>")]
+        #[repr(u16)]
+        enum Varint{
+            #[default]
+            Short = 0x7f,
+            Long = 0x80,
+        }
+
+        assert_eq!(vec![0, 0, 1, 2], Wide::Data.pack(false));
+        assert_eq!(4, Wide::Ping.size());
+        assert_eq!(Wide::Data, crate::from_bytes(&[2, 1, 0, 0], true).unwrap());
+        let error = crate::from_bytes::<Wide>(&[0, 1, 0, 1], false).unwrap_err();
+        assert_eq!(ErrorKind::OutOfRange, error.error_kind);
+        assert_eq!(vec![0x7f], Varint::Short.pack(false));
+        assert_eq!(vec![0x81, 0x00], Varint::Long.pack(false));
+        assert_eq!(2, Varint::Long.size());
+        assert_eq!(Varint::Long, crate::from_bytes(&[0x81, 0x00], false).unwrap());
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_enum_reserved(){
+        #[derive(Debug, Default, PartialEq, crate::Packable, crate::PackedSize, crate::Schema)]
+        #[packable(reserved = "0x80..=0xff", reserved = "0x10")]
+        #[repr(u8)]
+        enum Opcode{
+            #[default]
+            Nop,
+            Load,
+            #[packable(reserved)]
+            Reserved(u8),
+            Store = 0x20,
+            Jump,
+        }
+
+        #[derive(Debug, Default, PartialEq, crate::Packable, crate::Schema)]
+        #[packable(reserved = "0x80..=0xff")]
+        #[repr(u8)]
+        enum Strict{
+            #[default]
+            Nop,
+        }
+
+        #[derive(crate::Schema)]
+        #[repr(u8)]
+        enum Open{
+            _Nop,
+        }
+
+        assert_eq!(vec![1, 0x21], crate::pack!(false, Opcode::Load, Opcode::Jump));
+        assert_eq!(Opcode::Store, crate::from_bytes(&[0x20], false).unwrap());
+        assert_eq!(Opcode::Reserved(0x90), crate::from_bytes(&[0x90], false).unwrap());
+        assert_eq!(Opcode::Reserved(0x10), crate::from_bytes(&[0x10], false).unwrap());
+        assert_eq!(vec![0x90], Opcode::Reserved(0x90).pack(false));
+        for value in (0x80..=0xff).chain([0x10]) {
+            assert_eq!(Opcode::Reserved(value), crate::from_bytes(&Opcode::Reserved(value).pack(false), false).unwrap());
+        }
+        assert!(std::panic::catch_unwind(|| Opcode::Reserved(0x30).pack(false)).is_err());
+        assert!(std::panic::catch_unwind(|| Opcode::Reserved(0x20).pack(false)).is_err());
+        assert_eq!(1, <Opcode as crate::PackedSize>::SIZE);
+        let error = crate::from_bytes::<Opcode>(&[0x30], false).unwrap_err();
+        assert_eq!(ErrorKind::InvalidDiscriminant, error.error_kind);
+        let error = crate::from_bytes::<Strict>(&[0x80], false).unwrap_err();
+        assert_eq!(ErrorKind::InvalidDiscriminant, error.error_kind);
+        assert!(error.data.contains("reserved"));
+        assert_ne!(<Strict as crate::Schema>::SCHEMA_HASH, <Open as crate::Schema>::SCHEMA_HASH);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    #[allow(dead_code)]
+    fn test_derive_schema(){
+        use crate::Schema;
+
+        #[derive(crate::Schema)]
+        struct Reading{
+            id: u16,
+            value: [u32; 2],
+        }
+
+        #[derive(crate::Schema)]
+        struct Renamed(u16, [u32; 2]);
+
+        #[derive(crate::Schema)]
+        struct Widened(u32, [u32; 2]);
+
+        #[derive(crate::Schema)]
+        struct Wrapper<T>(T);
+
+        #[derive(crate::Schema)]
+        #[repr(u8)]
+        enum Mode{
+            Off,
+            On = 4,
+        }
+
+        #[derive(crate::Schema)]
+        #[repr(u8)]
+        enum Moved{
+            Off,
+            On = 5,
+        }
+
+        assert_eq!(Reading::SCHEMA_HASH, Renamed::SCHEMA_HASH);
+        assert_ne!(Reading::SCHEMA_HASH, Widened::SCHEMA_HASH);
+        assert_ne!(Wrapper::<Reading>::SCHEMA_HASH, Wrapper::<Widened>::SCHEMA_HASH);
+        assert_ne!(Mode::SCHEMA_HASH, Moved::SCHEMA_HASH);
+
+        #[derive(crate::Schema)]
+        struct Vendor(u16, #[packable(endian = "little")] u16);
+
+        #[derive(crate::Schema)]
+        struct Plain(u16, u16);
+
+        #[derive(crate::Schema)]
+        struct Wrapped(u16, crate::Le<u16>);
+
+        assert_ne!(Vendor::SCHEMA_HASH, Plain::SCHEMA_HASH);
+        assert_eq!(Vendor::SCHEMA_HASH, Wrapped::SCHEMA_HASH);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_layout_info(){
+        use crate::{LayoutInfo, LayoutWarning};
+
+        #[derive(crate::LayoutInfo)]
+        struct Header{
+            magic: u32,
+            version: u8,
+            #[packable(doc = "bytes after the header")]
+            length: u16,
+        }
+
+        let header = Header { magic: 0x1234_5678, version: 1, length: 0 };
+        let fields = header.field_layout();
+        assert_eq!(vec![(0, 4), (4, 1), (5, 2)], fields.iter().map(|field| (field.offset, field.size)).collect::<Vec<_>>());
+        assert_eq!(vec!["magic", "version", "length"], fields.iter().map(|field| field.name).collect::<Vec<_>>());
+        assert!(crate::analyze(&fields, Some(7)).is_empty());
+        assert_eq!(vec![None, None, Some("bytes after the header")], fields.iter().map(|field| field.doc).collect::<Vec<_>>());
+        let text = crate::explain(&fields, &[0x12, 0x34, 0x56, 0x78, 1, 0, 0]);
+        assert_eq!("   5  00 00        length: bytes after the header", text.lines().nth(2).unwrap());
+
+        let header = Header { magic: 0xcafe_feca, ..header };
+        assert_eq!(vec![LayoutWarning::EndianNeutral { field: "magic" }], crate::analyze(&header.field_layout(), None));
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_field_endian(){
+        #[derive(Debug, Default, PartialEq, crate::Packable)]
+        struct Frame{
+            length: u16,
+            #[packable(endian = "little")]
+            vendor: u16,
+        }
+
+        #[derive(Debug, Default, PartialEq, crate::Packable)]
+        struct Descriptor(#[packable(endian = "big")] u16);
+
+        let frame = Frame { length: 0x0102, vendor: 0x0304 };
+        assert_eq!(vec![1, 2, 4, 3], frame.pack(false));
+        assert_eq!(vec![2, 1, 4, 3], frame.pack(true));
+        assert_eq!(frame, crate::from_bytes(&[1, 2, 4, 3], false).unwrap());
+        assert_eq!(Descriptor(0x0102), crate::from_bytes(&[1, 2], true).unwrap());
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_packed_size(){
+        use crate::PackedSize;
+
+        #[derive(crate::PackedSize)]
+        struct Header<T>{
+            _kind: u8,
+            _body: [T; 2],
+        }
+
+        #[derive(crate::PackedSize)]
+        #[repr(u16)]
+        enum Mode{
+            _Idle,
+        }
+
+        #[derive(crate::PackedSize)]
+        struct Empty;
+
+        const _: () = assert!(Header::<u32>::SIZE == 9);
+        assert_eq!(2, Mode::SIZE);
+        assert_eq!(0, Empty::SIZE);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_flatten(){
+        use crate::LayoutInfo;
+
+        #[derive(Debug, Default, PartialEq, crate::Packable, crate::LayoutInfo)]
+        struct Header{
+            version: u8,
+            length: u16,
+        }
+
+        #[derive(Debug, Default, PartialEq, crate::Packable, crate::LayoutInfo)]
+        struct Status{
+            kind: u8,
+            #[packable(flatten)]
+            header: Header,
+            code: u8,
+        }
+
+        let status = Status { kind: 2, header: Header { version: 1, length: 0x0304 }, code: 5 };
+        assert_eq!(vec![2, 1, 3, 4, 5], status.pack(false));
+        assert_eq!(status, crate::from_bytes(&[2, 1, 3, 4, 5], false).unwrap());
+        let fields = status.field_layout();
+        assert_eq!(vec!["kind", "version", "length", "code"], fields.iter().map(|field| field.name).collect::<Vec<_>>());
+        assert_eq!(vec![0, 1, 2, 4], fields.iter().map(|field| field.offset).collect::<Vec<_>>());
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_presence_bitmap(){
+        use crate::LayoutInfo;
+
+        #[derive(Debug, Default, PartialEq, crate::Packable, crate::LayoutInfo)]
+        #[packable(presence_bitmap)]
+        struct Update{
+            id: u8,
+            name: Option<u16>,
+            flags: [Option<u8>; 1],
+            rate: Option<u8>,
+            limit: Option<u8>,
+        }
+
+        let update = Update { id: 7, name: None, flags: [Some(1)], rate: Some(3), limit: None };
+        assert_eq!(vec![0b10, 7, 1, 1, 3], update.pack(false));
+        assert_eq!(5, update.size());
+        assert_eq!(update, crate::from_bytes(&[0b10, 7, 1, 1, 3], false).unwrap());
+        let mut out = Vec::new();
+        update.pack_to_writer(&mut out, false).unwrap();
+        assert_eq!(update.pack(false), out);
+
+        let full = Update { name: Some(0x0102), limit: Some(4), ..update };
+        assert_eq!(vec![0b111, 7, 1, 2, 1, 1, 3, 4], full.pack(false));
+        assert_eq!(full, crate::from_bytes(&full.pack(true), true).unwrap());
+        let fields = full.field_layout();
+        assert_eq!(vec!["presence", "id", "name", "flags", "rate", "limit"], fields.iter().map(|field| field.name).collect::<Vec<_>>());
+        assert_eq!(vec![0, 1, 2, 4, 6, 7], fields.iter().map(|field| field.offset).collect::<Vec<_>>());
+        let error = crate::from_bytes::<Update>(&[0b1, 7, 1], false).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+
+        #[derive(Debug, Default, PartialEq, crate::Packable)]
+        #[packable(presence_bitmap)]
+        struct Maybe<T>{
+            value: Option<T>,
+        }
+
+        assert_eq!(vec![0], Maybe::<u16> { value: None }.pack(false));
+        assert_eq!(Maybe { value: Some(0x0102u16) }, crate::from_bytes(&[1, 1, 2], false).unwrap());
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_split_after(){
+        #[derive(Debug, Default, PartialEq, crate::Packable)]
+        struct Record{
+            kind: u8,
+            #[packable(split_after, endian = "little")]
+            length: u16,
+            body: Vec<u8>,
+        }
+
+        let record = Record { kind: 1, length: 6, body: vec![3, 4] };
+        let data = record.pack(false);
+        assert_eq!(vec![1, 6, 0, 0, 0, 0, 2, 3, 4], data);
+        let mut header = RecordHeaderPart::default();
+        assert_eq!(3, header.unpack_from(&data[..3], false).unwrap());
+        assert_eq!((1, 6), (header.kind, header.length));
+        let body: RecordBodyPart = crate::from_bytes(&data[3..3 + header.length as usize], false).unwrap();
+        assert_eq!(record, Record::from_parts(header, body));
+
+        let (header, body) = crate::from_bytes::<Record>(&data, false).unwrap().into_parts();
+        assert_eq!(data, [header.pack(false), body.pack(false)].concat());
+
+        #[derive(Debug, Default, PartialEq, crate::Packable)]
+        struct Pair(#[packable(split_after)] u8, u16);
+
+        let (header, body) = Pair(1, 0x0203).into_parts();
+        assert_eq!((vec![1], vec![2, 3]), (header.pack(false), body.pack(false)));
+        assert_eq!(Pair(1, 0x0203), Pair::from_parts(header, body));
+
+        #[derive(Debug, PartialEq, crate::Packable)]
+        struct Route{
+            #[packable(split_after)]
+            kind: u8,
+            address: std::net::Ipv4Addr,
+        }
+
+        let mut header = RouteHeaderPart::default();
+        assert_eq!(1, header.unpack_from(&[4, 10, 0, 0, 1], false).unwrap());
+        let body = RouteBodyPart { address: std::net::Ipv4Addr::new(10, 0, 0, 1) };
+        assert_eq!(vec![4, 10, 0, 0, 1], Route::from_parts(header, body).pack(false));
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    #[allow(dead_code)]
+    fn test_derive_view(){
+        #[derive(crate::View)]
+        struct Route{
+            kind: u8,
+            hops: u16,
+            #[packable(endian = "little")]
+            vendor: u16,
+            address: [u8; 2],
+        }
+
+        let mut frame = vec![1, 0, 2, 4, 3, 9, 8, 0xff];
+        let view = RouteView::new(&frame, false).unwrap();
+        assert_eq!((1, 2, 0x0304, [9, 8]), (view.kind().unwrap(), view.hops().unwrap(), view.vendor().unwrap(), view.address().unwrap()));
+        assert_eq!(ErrorKind::BufferLengthError, RouteView::new(&frame[..6], false).unwrap_err().error_kind);
+
+        let mut view = RouteViewMut::new(&mut frame, true).unwrap();
+        let allocations = crate::allocated_during(|| {
+            view.set_hops(&0x0506);
+            view.set_vendor(&0x0708);
+            view.hops().unwrap()
+        });
+        assert_eq!(0x0506, view.hops().unwrap());
+        assert_eq!(0, allocations.1);
+        assert_eq!(vec![1, 6, 5, 8, 7, 9, 8, 0xff], frame);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_pack_as(){
+        use crate::LayoutInfo;
+
+        #[derive(Debug, Default, PartialEq, crate::Packable, crate::LayoutInfo, crate::Schema)]
+        struct Entry {
+            #[packable(as = "u16")]
+            len: usize,
+            #[packable(as = "u8", endian = "little")]
+            count: u32,
+        }
+
+        #[allow(dead_code)]
+        #[derive(crate::Schema)]
+        struct Wire(u16, crate::Le<u8>);
+
+        let entry = Entry { len: 300, count: 7 };
+        assert_eq!(vec![1, 44, 7], entry.pack(false));
+        assert_eq!(3, entry.size());
+        assert_eq!(entry, crate::from_bytes(&[1, 44, 7], false).unwrap());
+        assert_eq!(vec![2, 1], entry.field_layout().iter().map(|field| field.size).collect::<Vec<_>>());
+        assert_eq!(<Wire as crate::Schema>::SCHEMA_HASH, <Entry as crate::Schema>::SCHEMA_HASH);
+        let result = std::panic::catch_unwind(|| Entry { len: 0, count: 256 }.pack(false));
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_scaled(){
+        use crate::LayoutInfo;
+
+        #[derive(Debug, Default, PartialEq, crate::Packable, crate::LayoutInfo)]
+        struct Register {
+            #[packable(as = "u16", scale = 0.1, offset = -40.0)]
+            temperature: f32,
+            #[packable(as = "u8", scale = 0.001, rounding = "floor")]
+            volts: f64,
+        }
+
+        let register = Register { temperature: 21.5, volts: 0.0019 };
+        assert_eq!(vec![0x02, 0x67, 1], register.pack(false));
+        assert_eq!(3, register.size());
+        assert_eq!(vec![2, 1], register.field_layout().iter().map(|field| field.size).collect::<Vec<_>>());
+        let decoded: Register = crate::from_bytes(&[0x02, 0x67, 1], false).unwrap();
+        assert!((decoded.temperature - 21.5).abs() < 1e-4);
+        assert!((decoded.volts - 0.001).abs() < 1e-12);
+        assert_eq!(vec![0, 0, 0xff], Register { temperature: -50.0, volts: 1e9 }.pack(false));
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_size_of(){
+        use crate::UntilEnd;
+
+        #[derive(Debug, Default, PartialEq, crate::Packable)]
+        struct Message {
+            #[packable(size_of = "items")]
+            len: u8,
+            items: UntilEnd<u16>,
+            crc: u8,
+        }
+
+        #[derive(Debug, Default, PartialEq, crate::Packable)]
+        struct Bounded {
+            #[packable(size_of = "value")]
+            len: u8,
+            value: u16,
+        }
+
+        #[derive(Debug, Default, PartialEq, crate::Packable)]
+        struct Extensible {
+            #[packable(size_of = "known", skip_extra)]
+            len: u16,
+            known: u16,
+            next: u8,
+        }
+
+        let message = Message { len: 0, items: UntilEnd(vec![1, 2, 3]), crc: 9 };
+        let data = message.pack(false);
+        assert_eq!(vec![6, 0, 1, 0, 2, 0, 3, 9], data);
+        assert_eq!(data[0] as usize, message.items.size());
+        let decoded: Message = crate::from_bytes(&data, false).unwrap();
+        assert_eq!((6, vec![1, 2, 3], 9), (decoded.len, decoded.items.0, decoded.crc));
+        let error = crate::from_bytes::<Bounded>(&[3, 0, 1, 0], false).unwrap_err();
+        assert_eq!(ErrorKind::TrailingBytes, error.error_kind);
+        let error = crate::from_bytes::<Message>(&[8, 0, 1, 9], false).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+        let result = std::panic::catch_unwind(|| Message { len: 0, items: UntilEnd(vec![0; 200]), crc: 0 }.pack(false));
+        assert!(result.is_err());
+
+        let decoded: Extensible = crate::from_bytes(&[0, 4, 0, 7, 0xee, 0xee, 5], false).unwrap();
+        assert_eq!((4, 7, 5), (decoded.len, decoded.known, decoded.next));
+        assert_eq!(vec![0, 2, 0, 7, 5], decoded.pack(false));
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_accessors(){
+        #[derive(Debug, Default, PartialEq, crate::Packable)]
+        #[packable(accessors)]
+        struct Record {
+            len: u16,
+            name: String,
+            #[packable(endian = "little")]
+            id: u32,
+        }
+
+        let record = Record { len: 7, name: "probe".to_string(), id: 0x0102 };
+        let data = record.pack(false);
+        assert_eq!(7, Record::len_from(&data, false).unwrap());
+        assert_eq!("probe", Record::name_from(&data, false).unwrap());
+        assert_eq!(0x0102, Record::id_from(&data, false).unwrap());
+        let (len, allocated) = crate::allocated_during(|| Record::len_from(&data, false).unwrap());
+        assert_eq!((7, 0), (len, allocated));
+        let error = Record::id_from(&data[..data.len() - 1], false).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_sample(){
+        use crate::{Sample, SampleRng, UntilEnd};
+
+        #[derive(Debug, Default, Clone, Copy, PartialEq, crate::Packable, crate::Sample)]
+        #[packable(tag = "u16", reserved = "0x80..=0xff", reserved = "0x10")]
+        #[repr(u8)]
+        enum Opcode{
+            #[default]
+            Nop,
+            Load,
+            #[packable(reserved)]
+            Reserved(u8),
+            Store = 0x20,
+        }
+
+        #[derive(Debug, Default, PartialEq, crate::Packable, crate::Sample)]
+        struct Frame{
+            opcode: Opcode,
+            #[packable(as = "u8")]
+            hops: u32,
+            #[packable(as = "u16", scale = 0.1, offset = -40.0)]
+            temperature: f64,
+            #[packable(compute = "self.tags.len() as u8")]
+            count: u8,
+            tags: Vec<Option<u16>>,
+            #[packable(size_of = "body")]
+            len: u8,
+            body: UntilEnd<u16>,
+            name: String,
+        }
+
+        let mut rng = SampleRng::new(7);
+        let mut opcodes = Vec::new();
+        for _ in 0..200{
+            let frame = Frame::sample(&mut rng);
+            assert_eq!(frame, crate::from_bytes(&frame.pack(true), true).unwrap());
+            assert_eq!(frame.tags.len(), frame.count as usize);
+            assert_eq!(frame.body.size(), frame.len as usize);
+            opcodes.push(frame.opcode);
+        }
+        assert!(opcodes.iter().any(|opcode| matches!(opcode, Opcode::Reserved(value) if *value == 0x10 || *value >= 0x80)));
+        assert!([Opcode::Nop, Opcode::Load, Opcode::Store].iter().all(|opcode| opcodes.contains(opcode)));
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_declared_layout(){
+        #[derive(Debug, Default, PartialEq, crate::Packable, crate::PackedSize)]
+        #[packable(size = 8)]
+        struct Header{
+            #[packable(at = 0)]
+            magic: [u8; 2],
+            #[packable(at = 2, as = "u16", split_after)]
+            len: usize,
+            #[packable(at = 4)]
+            flags: u32,
+        }
+
+        assert_eq!(8, <Header as crate::PackedSize>::SIZE);
+        assert_eq!(8, Header::default().pack(false).len());
+        assert_eq!(4, HeaderBodyPart::default().size());
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_compute(){
+        #[derive(Debug, Default, PartialEq, crate::Packable)]
+        struct Message{
+            #[packable(compute = "self.body.0.len() as u16")]
+            length: u16,
+            #[packable(compute = "2", verify = false)]
+            version: u8,
+            body: crate::UntilEnd<u8>,
+        }
+
+        let message = Message { length: 0, version: 0, body: crate::UntilEnd(vec![7, 8, 9]) };
+        assert_eq!(vec![0, 3, 2, 7, 8, 9], message.pack(false));
+        assert_eq!(6, message.size());
+        let mut out = Vec::new();
+        message.pack_to_writer(&mut out, false).unwrap();
+        assert_eq!(message.pack(false), out);
+
+        let decoded: Message = crate::from_bytes(&[0, 3, 1, 7, 8, 9], false).unwrap();
+        assert_eq!((3, 1), (decoded.length, decoded.version));
+        let error = crate::from_bytes::<Message>(&[0, 4, 2, 7, 8, 9], false).unwrap_err();
+        assert_eq!(ErrorKind::InvalidValue, error.error_kind);
+
+        #[derive(Debug, Default, PartialEq, crate::Packable)]
+        struct Record{
+            #[packable(split_after, compute = "self.body.0.len() as u8")]
+            length: u8,
+            body: crate::UntilEnd<u8>,
+        }
+
+        let (header, body) = Record { length: 0, body: crate::UntilEnd(vec![5, 6]) }.into_parts();
+        assert_eq!(2, header.length);
+        assert_eq!(Record { length: 2, body: crate::UntilEnd(vec![5, 6]) }, Record::from_parts(header, body));
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_default_packed(){
+        use crate::ConstDefault;
+
+        #[derive(Debug, Default, crate::Packable, crate::DefaultPacked)]
+        struct Heartbeat{
+            kind: u8,
+            sequence: u16,
+            alive: bool,
+            node: [u8; 2],
+        }
+
+        impl ConstDefault for Heartbeat{
+            const DEFAULT: Self = Heartbeat { kind: 0x7f, sequence: 0x0102, alive: true, node: *b"ab" };
+        }
+
+        const FRAME: [u8; 6] = Heartbeat::DEFAULT_PACKED;
+        assert_eq!([0x7f, 1, 2, 1, b'a', b'b'], FRAME);
+        assert_eq!(Heartbeat::DEFAULT.pack(false), Heartbeat::DEFAULT_PACKED);
+        assert_eq!(Heartbeat::DEFAULT.pack(true), Heartbeat::DEFAULT_PACKED_LE);
+
+        #[derive(crate::DefaultPacked)]
+        struct Mixed(u16, #[packable(endian = "little")] u16);
+
+        impl ConstDefault for Mixed{
+            const DEFAULT: Self = Mixed(0x0102, 0x0304);
+        }
+
+        assert_eq!([1, 2, 4, 3], Mixed::DEFAULT_PACKED);
+        assert_eq!([2, 1, 4, 3], Mixed::DEFAULT_PACKED_LE);
+    }
+
+    #[test]
+    fn test_bool(){
+        assert_eq!(vec![1, 0], pack!(false, true, false));
+        assert!(crate::from_bytes::<bool>(&[2], false).unwrap());
+        assert!(!crate::from_bytes::<bool>(&[0], false).unwrap());
+        assert_eq!(crate::StrictBool(true), crate::from_bytes(&[1], false).unwrap());
+        let error = crate::from_bytes::<crate::StrictBool>(&[2], false).unwrap_err();
+        assert_eq!(ErrorKind::InvalidValue, error.error_kind);
+    }
+
+    #[test]
+    fn test_char(){
+        assert_eq!(vec![0, 1, 0xf6, 0x00], '\u{1f600}'.pack(false));
+        assert_eq!('\u{e9}', crate::from_bytes(&[0xe9, 0, 0, 0], true).unwrap());
+        let error = crate::from_bytes::<char>(&[0, 0, 0xd8, 0], false).unwrap_err();
+        assert_eq!(ErrorKind::InvalidChar, error.error_kind);
+        assert_eq!(ErrorKind::InvalidChar, crate::from_bytes::<char>(&[0, 0x11, 0, 0], false).unwrap_err().error_kind);
+    }
+
+    #[test]
+    fn test_non_zero(){
+        use core::num::{NonZeroI16, NonZeroU32};
+
+        let mut id = NonZeroU32::MIN;
+        assert_eq!(4, crate::unpack!(false, &mut &[0, 0, 1, 0][..], id).unwrap());
+        assert_eq!(256, id.get());
+        assert_eq!(vec![0xff, 0xff], NonZeroI16::new(-1).unwrap().pack(true));
+        let error = id.unpack(&mut vec![0, 0, 0, 0], false).unwrap_err();
+        assert_eq!(ErrorKind::ZeroValue, error.error_kind);
+        assert_eq!(256, id.get());
+    }
+
+    #[test]
+    fn test_tuple(){
+        let value = (0x0102u16, 0x03040506u32, [7u8, 8]);
+        assert_eq!(vec![1, 2, 3, 4, 5, 6, 7, 8], value.pack(false));
+        assert_eq!(8, value.size());
+        assert_eq!(value, crate::from_bytes(&[1, 2, 3, 4, 5, 6, 7, 8], false).unwrap());
+        let error = crate::from_bytes::<(u8, u16)>(&[1, 2], false).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+        assert_eq!(12, (0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8).size());
+    }
+
+    #[test]
+    fn test_array(){
+        let samples = [0x0102u16, 0x0304, 0x0506];
+        assert_eq!(vec![2, 1, 4, 3, 6, 5], samples.pack(true));
+        assert_eq!(samples, crate::from_bytes::<[u16; 3]>(&[1, 2, 3, 4, 5, 6], false).unwrap());
+        assert_eq!([1.5f32, -2.0], crate::from_bytes::<[f32; 2]>(&[1.5f32, -2.0].pack(false), false).unwrap());
+        assert_eq!([[1u8, 2], [3, 4]], crate::from_bytes::<[[u8; 2]; 2]>(&[1, 2, 3, 4], false).unwrap());
+        let error = crate::from_bytes::<[u16; 2]>(&[1, 2, 3], false).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+    }
+
+    #[test]
+    fn test_flag(){
+        let mut flag = crate::Flag::<u16>::default();
+        flag.set(15, true);
+        flag.set_range(6, 4, 0b1101);
+        assert_eq!(vec![0x80, 0x50], flag.pack(false));
+        assert_eq!(0b101, flag.get_range(6, 4));
+        assert_eq!(0x8050 >> 4, flag.get_range(15, 4));
+        assert_eq!(0x8050, flag.get_range(15, 0));
+        assert!(flag.get(15) && !flag.get(5));
+
+        let mut flag: crate::Flag = Default::default();
+        flag.unpack(&mut vec![0x0f], false).unwrap();
+        assert!(flag.get(3));
+        assert_eq!(0b011, flag.get_range(4, 2));
+    }
+
+    #[test]
+    fn test_error_direction(){
+        let error = crate::from_bytes::<u16>(&[1], false).unwrap_err();
+        assert_eq!(Some(Direction::Unpack), error.direction);
+        assert!(error.to_string().contains("while unpacking"));
+
+        let mut out = [core::mem::MaybeUninit::uninit(); 1];
+        let error = crate::pack_uninit(&1u16, false, &mut out).unwrap_err();
+        assert_eq!(Some(Direction::Pack), error.direction);
+
+        let codec = crate::Codec::new(false).max_size(1);
+        assert_eq!(Some(Direction::Pack), codec.encode(&1u16).unwrap_err().direction);
+        assert_eq!(Some(Direction::Unpack), codec.decode::<u16>(&[0, 1]).unwrap_err().direction);
+        assert_eq!(None, crate::PackAs::<u8, u16>::new(300).unwrap_err().direction);
+    }
 }
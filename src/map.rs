@@ -0,0 +1,136 @@
+use std::{collections::{BTreeMap, HashMap}, hash::{BuildHasher, Hash}};
+use crate::{Packable, PackableError, Direction, ErrorKind, UnpackSource};
+
+/// Entries preceded by their count as a `u32`, each entry a key then its value, in
+/// key order. A repeated key fails to unpack with `InvalidValue`.
+///
+/// # Panics
+///
+/// `pack` panics if there are more than `u32::MAX` entries.
+impl<K: Packable + Default + Ord, V: Packable + Default> Packable for BTreeMap<K, V>{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.size());
+        pack_entries(self.len(), self.iter(), &mut data, litle_endian);
+        data
+    }
+
+    fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+        pack_entries(self.len(), self.iter(), out, litle_endian)
+    }
+
+    fn size(&self) -> usize {
+        0u32.size() + self.iter().map(|(key, value)| key.size() + value.size()).sum::<usize>()
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        self.clear();
+        unpack_entries(buf, litle_endian, |key, value| self.insert(key, value).is_none())
+    }
+}
+
+/// Packs like `BTreeMap`, but in iteration order, which is unspecified; use a
+/// `BTreeMap` where the same map must always give the same bytes.
+impl<K, V, S> Packable for HashMap<K, V, S>
+where
+    K: Packable + Default + Eq + Hash,
+    V: Packable + Default,
+    S: BuildHasher,
+{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.size());
+        pack_entries(self.len(), self.iter(), &mut data, litle_endian);
+        data
+    }
+
+    fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+        pack_entries(self.len(), self.iter(), out, litle_endian)
+    }
+
+    fn size(&self) -> usize {
+        0u32.size() + self.iter().map(|(key, value)| key.size() + value.size()).sum::<usize>()
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        self.clear();
+        unpack_entries(buf, litle_endian, |key, value| self.insert(key, value).is_none())
+    }
+}
+
+fn pack_entries<'a, K, V, I>(len: usize, entries: I, out: &mut Vec<u8>, litle_endian: bool)
+where
+    K: Packable + 'a,
+    V: Packable + 'a,
+    I: Iterator<Item = (&'a K, &'a V)>,
+{
+    let Ok(count) = u32::try_from(len) else {
+        panic!("{} entries do not fit a u32 count", len)
+    };
+    count.pack_into(out, litle_endian);
+    for (key, value) in entries{
+        key.pack_into(out, litle_endian);
+        value.pack_into(out, litle_endian);
+    }
+}
+
+/// `insert` returns false if the key was already present.
+fn unpack_entries<K, V, F>(data: &[u8], litle_endian: bool, mut insert: F) -> Result<usize, PackableError>
+where
+    K: Packable + Default,
+    V: Packable + Default,
+    F: FnMut(K, V) -> bool,
+{
+    let mut source = data;
+    let mut count = 0u32;
+    source.unpack_into(&mut count, litle_endian)?;
+    for index in 0..count{
+        let (mut key, mut value) = (K::default(), V::default());
+        source.unpack_into(&mut key, litle_endian)?;
+        source.unpack_into(&mut value, litle_endian)?;
+        if !insert(key, value){
+            return Err(PackableError { 
+                error_kind: ErrorKind::InvalidValue, 
+                data: format!("entry {} repeats an earlier key", index),
+                direction: Some(Direction::Unpack),
+            })
+        }
+    }
+    Ok(data.len() - source.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+    use crate::{ErrorKind, Packable, to_bytes, from_bytes};
+
+    #[test]
+    fn test_btree_map(){
+        let config = BTreeMap::from([(2u16, 0x20u32), (1, 0x10)]);
+        let data = vec![0, 0, 0, 2, 0, 1, 0, 0, 0, 0x10, 0, 2, 0, 0, 0, 0x20];
+        assert_eq!(data, to_bytes(&config, false));
+        assert_eq!(16, config.size());
+        assert_eq!(config, from_bytes(&data, false).unwrap());
+        let error = from_bytes::<BTreeMap<u8, u8>>(&[0, 0, 0, 2, 1, 1, 1, 2], false).unwrap_err();
+        assert_eq!(ErrorKind::InvalidValue, error.error_kind);
+        let error = from_bytes::<BTreeMap<u8, u8>>(&[0, 0, 0, 2, 1, 1, 2], false).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+    }
+
+    #[test]
+    fn test_hash_map(){
+        let config = HashMap::from([(1u16, "one".to_string()), (2, "two".to_string())]);
+        assert_eq!(config, from_bytes(&to_bytes(&config, true), true).unwrap());
+        let mut map = HashMap::from([(9u16, "stale".to_string())]);
+        unpack!(true, &mut vec![1, 0, 0, 0, 1, 0, 1, 0, 0, 0, b'x'], map).unwrap();
+        assert_eq!(HashMap::from([(1, "x".to_string())]), map);
+    }
+}
@@ -0,0 +1,115 @@
+use core::marker::PhantomData;
+use crate::{Endian, Packable, PackableError, Direction, ErrorKind, buffer_length_error};
+
+/// The mark a byte-order-switchable format starts with to say which order the
+/// rest of it is in.
+pub trait OrderMark{
+    const LITTLE: &'static [u8];
+    const BIG: &'static [u8];
+}
+
+/// `II` for little endian, `MM` for big endian, as in TIFF headers.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Tiff;
+
+impl OrderMark for Tiff{
+    const LITTLE: &'static [u8] = b"II";
+    const BIG: &'static [u8] = b"MM";
+}
+
+/// `M`'s mark followed by `value` packed in the byte order it names, whatever the
+/// caller asks for. Unpacking reads the mark first and unpacks all of `value`,
+/// nested fields included, in that order, so the format is decoded without
+/// passing the byte order around by hand. An unknown mark fails with
+/// `InvalidValue`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Marked<T, M = Tiff>{
+    pub value: T,
+    pub litle_endian: bool,
+    mark: PhantomData<M>,
+}
+
+impl<T, M> Marked<T, M>{
+    pub fn new(value: T, endian: impl Into<Endian>) -> Self{
+        let litle_endian = endian.into().is_little();
+        Marked { value, litle_endian, mark: PhantomData }
+    }
+}
+
+impl<T: Default, M> Default for Marked<T, M>{
+    fn default() -> Self {
+        Marked::new(T::default(), false)
+    }
+}
+
+impl<T, M: OrderMark> Marked<T, M>{
+    fn mark(&self) -> &'static [u8]{
+        if self.litle_endian { M::LITTLE } else { M::BIG }
+    }
+}
+
+impl<T: Packable, M: OrderMark> Packable for Marked<T, M>{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.size());
+        self.pack_into(&mut data, litle_endian);
+        data
+    }
+
+    fn pack_into(&self, out: &mut Vec<u8>, _litle_endian: bool) {
+        out.extend_from_slice(self.mark());
+        self.value.pack_into(out, self.litle_endian);
+    }
+
+    fn size(&self) -> usize {
+        self.mark().len() + self.value.size()
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], _litle_endian: bool) -> Result<usize, PackableError> {
+        let (litle_endian, mark) = if buf.starts_with(M::LITTLE){
+            (true, M::LITTLE.len())
+        }
+        else if buf.starts_with(M::BIG){
+            (false, M::BIG.len())
+        }
+        else if buf.len() < M::LITTLE.len().max(M::BIG.len()){
+            return Err(buffer_length_error(M::LITTLE.len().max(M::BIG.len()), buf.len()))
+        }
+        else{
+            return Err(PackableError { 
+                error_kind: ErrorKind::InvalidValue, 
+                data: format!("{:02x?} is not a byte order mark", &buf[..M::LITTLE.len()]),
+                direction: Some(Direction::Unpack),
+            })
+        };
+        let consumed = self.value.unpack_from(&buf[mark..], litle_endian)?;
+        self.litle_endian = litle_endian;
+        Ok(mark + consumed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, Packable, from_bytes};
+    use super::{Marked, Tiff};
+
+    #[test]
+    fn test_marked(){
+        let header = Marked::<(u16, u32), Tiff>::new((42, 8), true);
+        assert_eq!(b"II\x2a\x00\x08\x00\x00\x00".to_vec(), header.pack(false));
+        assert_eq!(8, header.size());
+        let parsed: Marked<(u16, u32)> = from_bytes(b"MM\x00\x2a\x00\x00\x00\x08", true).unwrap();
+        assert_eq!((42, 8), parsed.value);
+        assert!(!parsed.litle_endian);
+        assert_eq!(header, from_bytes(&header.pack(false), false).unwrap());
+
+        let error = from_bytes::<Marked<u16>>(b"XX\x00\x2a", false).unwrap_err();
+        assert_eq!(ErrorKind::InvalidValue, error.error_kind);
+        let error = Marked::<u16>::default().unpack_from(b"M", false).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+    }
+}
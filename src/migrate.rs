@@ -0,0 +1,62 @@
+use crate::{Endian, Packable, PackableError, UnpackSource, from_bytes};
+
+/// Decode `data` as an `Old` record and re-encode it as a `New` one. The conversion
+/// is `New::from`, which `#[derive(Migrate)]` writes by matching field names.
+pub fn migrate<Old, New>(data: &[u8], endian: impl Into<Endian>) -> Result<Vec<u8>, PackableError>
+where
+    Old: Packable + Default,
+    New: Packable + From<Old>,
+{
+    let litle_endian = endian.into().is_little();
+    let old: Old = from_bytes(data, litle_endian)?;
+    Ok(New::from(old).pack(litle_endian))
+}
+
+/// `migrate` every record of a file of consecutive records.
+pub fn migrate_records<Old, New>(data: &[u8], endian: impl Into<Endian>) -> Result<Vec<u8>, PackableError>
+where
+    Old: Packable + Default,
+    New: Packable + From<Old>,
+{
+    let litle_endian = endian.into().is_little();
+    let mut migrated = Vec::new();
+    let mut source = data;
+    while !source.is_empty(){
+        let mut old = Old::default();
+        source.unpack_into(&mut old, litle_endian)?;
+        migrated.extend(New::from(old).pack(litle_endian));
+    }
+    Ok(migrated)
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use crate::ErrorKind;
+    use super::{migrate, migrate_records};
+
+    #[derive(Debug, Default, PartialEq, crate::Packable)]
+    struct ReadingV1{
+        id: u8,
+        temp: i16,
+        status: u8,
+    }
+
+    #[derive(Debug, Default, PartialEq, crate::Packable, crate::Migrate)]
+    #[migrate(from = "ReadingV1")]
+    struct ReadingV2{
+        id: u16,
+        #[migrate(rename = "temp")]
+        temperature: i16,
+        #[migrate(default)]
+        humidity: u8,
+    }
+
+    #[test]
+    fn test_migrate(){
+        assert_eq!(vec![0, 7, 0xff, 0xfe, 0], migrate::<ReadingV1, ReadingV2>(&[7, 0xff, 0xfe, 1], false).unwrap());
+        let migrated = migrate_records::<ReadingV1, ReadingV2>(&[1, 0, 2, 0, 3, 0, 4, 0], false).unwrap();
+        assert_eq!(vec![0, 1, 0, 2, 0, 0, 3, 0, 4, 0], migrated);
+        let error = migrate_records::<ReadingV1, ReadingV2>(&[1, 0, 2, 0, 3], false).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+    }
+}
@@ -0,0 +1,123 @@
+use std::{io::{Read, Write}, net::{SocketAddr, TcpListener}, thread::{self, JoinHandle}};
+use crate::{Codec, Framing, Packable, PackableError};
+
+type Handler = Box<dyn FnMut(&Codec, &[u8]) -> Option<Result<Vec<u8>, PackableError>> + Send>;
+
+/// In-memory or TCP stand-in for a device: incoming frames are matched against
+/// handlers in registration order and the first one that accepts a frame answers it.
+pub struct MockPeer{
+    codec: Codec,
+    handlers: Vec<Handler>,
+    unhandled: usize,
+}
+
+impl MockPeer{
+    /// Frames are read and written with `codec`; a strict codec keeps handlers for
+    /// shorter messages from accepting longer ones.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `codec` has no framing, since frame boundaries are needed to
+    /// tell requests apart.
+    pub fn new(codec: Codec) -> Self{
+        assert!(codec.framing != Framing::None, "MockPeer needs a framed codec");
+        MockPeer { codec, handlers: Vec::new(), unhandled: 0 }
+    }
+
+    /// Answer frames that decode as `Req` and for which `handler` returns a response.
+    pub fn on<Req, Resp, F>(mut self, mut handler: F) -> Self
+    where
+        Req: Packable + Default,
+        Resp: Packable,
+        F: FnMut(Req) -> Option<Resp> + Send + 'static,
+    {
+        self.handlers.push(Box::new(move |codec: &Codec, payload: &[u8]| {
+            let request = codec.decode_payload::<Req>(payload).ok()?;
+            handler(request).map(|response| codec.encode(&response))
+        }));
+        self
+    }
+
+    /// Frames that no handler accepted so far.
+    pub fn unhandled(&self) -> usize{
+        self.unhandled
+    }
+
+    fn dispatch(&mut self, payload: &[u8]) -> Result<Option<Vec<u8>>, PackableError>{
+        for handler in &mut self.handlers{
+            if let Some(response) = handler(&self.codec, payload){
+                return response.map(Some)
+            }
+        }
+        self.unhandled += 1;
+        Ok(None)
+    }
+
+    /// Answer every complete frame in `input`, returning the concatenated responses.
+    pub fn respond(&mut self, input: &[u8]) -> Result<Vec<u8>, PackableError>{
+        let mut output = Vec::new();
+        self.serve(&mut &input[..], &mut output)?;
+        Ok(output)
+    }
+
+    /// Answer frames from `reader` on `writer` until the reader reaches its end.
+    pub fn serve<R: Read, W: Write>(&mut self, reader: &mut R, writer: &mut W) -> Result<(), PackableError>{
+        while let Some((payload, _)) = self.codec.read_frame(reader, 0)?{
+            if let Some(response) = self.dispatch(&payload)?{
+                writer.write_all(&response)?;
+                writer.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Listen on a local port and serve the first connection on a background thread.
+    pub fn spawn_tcp(mut self) -> Result<(SocketAddr, JoinHandle<Result<MockPeer, PackableError>>), PackableError>{
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept()?;
+            let mut writer = stream.try_clone()?;
+            self.serve(&mut stream, &mut writer)?;
+            Ok(self)
+        });
+        Ok((address, handle))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, net::{Shutdown, TcpStream}};
+    use crate::{Codec, Framing, LengthPrefix};
+    use super::MockPeer;
+
+    fn codec() -> Codec{
+        Codec::new(false).framing(Framing::LengthPrefixed(LengthPrefix::U8)).strict(true)
+    }
+
+    fn peer() -> MockPeer{
+        MockPeer::new(codec())
+            .on(|ping: u8| Some(ping.wrapping_add(1)))
+            .on(|read: u16| (read < 0x100).then_some(read as u32 * 2))
+    }
+
+    #[test]
+    fn test_mock_peer_in_memory(){
+        let mut peer = peer();
+        let mut input = codec().encode(&7u8).unwrap();
+        input.extend(codec().encode(&0x10u16).unwrap());
+        input.extend(codec().encode(&0x1000u16).unwrap());
+        assert_eq!(vec![1, 8, 4, 0, 0, 0, 0x20], peer.respond(&input).unwrap());
+        assert_eq!(1, peer.unhandled());
+    }
+
+    #[test]
+    fn test_mock_peer_tcp(){
+        let (address, handle) = peer().spawn_tcp().unwrap();
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(&codec().encode(&41u8).unwrap()).unwrap();
+        assert_eq!(Some(42u8), codec().read(&mut stream).unwrap());
+        stream.shutdown(Shutdown::Write).unwrap();
+        assert_eq!(0, handle.join().unwrap().unwrap().unhandled());
+    }
+}
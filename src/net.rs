@@ -0,0 +1,84 @@
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use crate::{Codec, Direction, Endian, Framing, LengthPrefix, Packable, PackableError, from_bytes};
+
+/// Largest UDP payload.
+const DATAGRAM_SIZE: usize = 65_535;
+
+/// Send `value` as one datagram on a connected socket.
+pub fn send_packed<T: Packable + ?Sized>(socket: &UdpSocket, value: &T, endian: impl Into<Endian>) -> Result<(), PackableError>{
+    let litle_endian = endian.into().is_little();
+    socket.send(&value.pack(litle_endian)).map_err(|error| PackableError::from(error).during(Direction::Pack))?;
+    Ok(())
+}
+
+pub fn send_packed_to<T: Packable + ?Sized, A: ToSocketAddrs>(socket: &UdpSocket, value: &T, address: A, endian: impl Into<Endian>) -> Result<(), PackableError>{
+    let litle_endian = endian.into().is_little();
+    socket.send_to(&value.pack(litle_endian), address).map_err(|error| PackableError::from(error).during(Direction::Pack))?;
+    Ok(())
+}
+
+/// Receive one datagram on a connected socket and unpack it.
+pub fn recv_packed<T: Packable + Default>(socket: &UdpSocket, endian: impl Into<Endian>) -> Result<T, PackableError>{
+    let litle_endian = endian.into().is_little();
+    let mut buffer = vec![0; DATAGRAM_SIZE];
+    let len = socket.recv(&mut buffer).map_err(|error| PackableError::from(error).during(Direction::Unpack))?;
+    from_bytes(&buffer[..len], litle_endian)
+}
+
+pub fn recv_packed_from<T: Packable + Default>(socket: &UdpSocket, endian: impl Into<Endian>) -> Result<(T, SocketAddr), PackableError>{
+    let litle_endian = endian.into().is_little();
+    let mut buffer = vec![0; DATAGRAM_SIZE];
+    let (len, address) = socket.recv_from(&mut buffer).map_err(|error| PackableError::from(error).during(Direction::Unpack))?;
+    Ok((from_bytes(&buffer[..len], litle_endian)?, address))
+}
+
+/// Stream sockets carry each message behind a `u32` length prefix.
+fn stream_codec(litle_endian: bool) -> Codec{
+    Codec::new(litle_endian).framing(Framing::LengthPrefixed(LengthPrefix::U32))
+}
+
+pub fn send_framed<T: Packable + ?Sized>(mut stream: &TcpStream, value: &T, endian: impl Into<Endian>) -> Result<(), PackableError>{
+    let litle_endian = endian.into().is_little();
+    stream_codec(litle_endian).write(&mut stream, value)
+}
+
+/// The next message, or `None` once the peer has closed the connection.
+pub fn recv_framed<T: Packable + Default>(mut stream: &TcpStream, endian: impl Into<Endian>) -> Result<Option<T>, PackableError>{
+    let litle_endian = endian.into().is_little();
+    stream_codec(litle_endian).read(&mut stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::{TcpListener, TcpStream, UdpSocket}, thread};
+    use super::{recv_framed, recv_packed, recv_packed_from, send_framed, send_packed, send_packed_to};
+
+    #[test]
+    fn test_udp(){
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+        client.connect(server.local_addr().unwrap()).unwrap();
+        send_packed(&client, &(1u8, 0x0203u16), false).unwrap();
+        let (message, address) = recv_packed_from::<(u8, u16)>(&server, false).unwrap();
+        assert_eq!((1, 0x0203), message);
+        send_packed_to(&server, &7u32, address, true).unwrap();
+        assert_eq!(7u32, recv_packed(&client, true).unwrap());
+    }
+
+    #[test]
+    fn test_tcp(){
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            while let Some(message) = recv_framed::<String>(&stream, false).unwrap(){
+                send_framed(&stream, &(message.len() as u64), false).unwrap_or_default();
+            }
+        });
+        let stream = TcpStream::connect(address).unwrap();
+        send_framed(&stream, &"hello".to_string(), false).unwrap();
+        assert_eq!(Some(5u64), recv_framed(&stream, false).unwrap());
+        drop(stream);
+        server.join().unwrap();
+    }
+}
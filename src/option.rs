@@ -0,0 +1,109 @@
+use crate::{Packable, PackableError, Direction, ErrorKind, UnpackSource};
+
+/// A presence byte, 0 or 1, followed by the value when there is one.
+impl<T: Packable + Default> Packable for Option<T>{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.size());
+        self.pack_into(&mut data, litle_endian);
+        data
+    }
+
+    fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+        self.is_some().pack_into(out, litle_endian);
+        if let Some(value) = self{
+            value.pack_into(out, litle_endian);
+        }
+    }
+
+    fn size(&self) -> usize {
+        1 + self.as_ref().map_or(0, Packable::size)
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        let mut source = buf;
+        let mut tag = 0u8;
+        source.unpack_into(&mut tag, litle_endian)?;
+        match tag{
+            0 => *self = None,
+            1 => {
+                let value = self.get_or_insert_with(T::default);
+                source.unpack_into(value, litle_endian)?;
+            }
+            _ => return Err(PackableError { 
+                error_kind: ErrorKind::InvalidValue, 
+                data: format!("{:#04x} is not a presence tag", tag),
+                direction: Some(Direction::Unpack),
+            }),
+        }
+        Ok(buf.len() - source.len())
+    }
+}
+
+/// An optional trailing field with no presence byte: absent when the input ends
+/// before it. Must be the last thing unpacked from its source.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Trailing<T>(pub Option<T>);
+
+impl<T: Packable + Default> Packable for Trailing<T>{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        self.0.as_ref().map_or_else(Vec::new, |value| value.pack(litle_endian))
+    }
+
+    fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+        if let Some(value) = &self.0{
+            value.pack_into(out, litle_endian);
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.0.as_ref().map_or(0, Packable::size)
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        if buf.is_empty(){
+            self.0 = None;
+            return Ok(0)
+        }
+        self.0.get_or_insert_with(T::default).unpack_from(buf, litle_endian)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, Packable, from_bytes};
+    use super::Trailing;
+
+    #[test]
+    fn test_option(){
+        let mut value = Some(7u32);
+        assert_eq!(1, unpack!(false, &mut vec![0], value).unwrap());
+        assert_eq!(None, value);
+        assert_eq!(vec![1, 0, 7], Some(7u16).pack(false));
+        assert_eq!(vec![0], None::<u16>.pack(false));
+        assert_eq!(3, Some(7u16).size());
+        assert_eq!(Some(7u16), from_bytes(&[1, 0, 7], false).unwrap());
+        assert_eq!(None::<u16>, from_bytes(&[0], false).unwrap());
+        assert_eq!(ErrorKind::InvalidValue, from_bytes::<Option<u16>>(&[2, 0, 7], false).unwrap_err().error_kind);
+        assert_eq!(ErrorKind::BufferLengthError, from_bytes::<Option<u16>>(&[1, 0], false).unwrap_err().error_kind);
+    }
+
+    #[test]
+    fn test_trailing(){
+        let (mut kind, mut extra) = (0u8, Trailing::<u16>::default());
+        assert_eq!(3, unpack!(false, &mut vec![1, 0, 9], kind, extra).unwrap());
+        assert_eq!(Trailing(Some(9)), extra);
+        assert_eq!(1, unpack!(false, &mut vec![1], kind, extra).unwrap());
+        assert_eq!(Trailing(None), extra);
+        assert_eq!(Vec::<u8>::new(), extra.pack(false));
+    }
+}
@@ -0,0 +1,128 @@
+use core::{fmt, marker::PhantomData};
+use crate::{Packable, PackableError, Direction, ErrorKind};
+
+/// A `T` stored on the wire as a `W`, e.g. `PackAs<u16, usize>`. The conversion is
+/// checked when the value is built and again when it is unpacked, so every
+/// `PackAs`, the default one included, holds a value that packs.
+pub struct PackAs<W, T>{
+    value: T,
+    wire: PhantomData<W>,
+}
+
+/// Fixed wire widths for the platform-sized integers, so a `usize` or `isize`
+/// round-trips between 32-bit and 64-bit targets. Values that do not fit the
+/// target's `usize` fail to unpack with `OutOfRange`.
+pub type PackAsU32<T = usize> = PackAs<u32, T>;
+pub type PackAsU64<T = usize> = PackAs<u64, T>;
+pub type PackAsI32<T = isize> = PackAs<i32, T>;
+pub type PackAsI64<T = isize> = PackAs<i64, T>;
+
+impl<W, T> PackAs<W, T>
+where
+    T: Copy + fmt::Debug + TryInto<W>,
+{
+    pub fn new(value: T) -> Result<Self, PackableError>{
+        match value.try_into(){
+            Ok(_) => Ok(PackAs { value, wire: PhantomData }),
+            Err(_) => Err(PackableError { 
+                error_kind: ErrorKind::OutOfRange, 
+                data: format!("{:?} does not fit in {}", value, core::any::type_name::<W>()),
+                direction: None,
+            }),
+        }
+    }
+}
+
+impl<W, T: Copy> PackAs<W, T>{
+    pub fn get(&self) -> T{
+        self.value
+    }
+}
+
+/// The value of the default wire, e.g. 0, so it fits the wire like any other.
+impl<W: Default + TryInto<T>, T> Default for PackAs<W, T>{
+    fn default() -> Self {
+        match W::default().try_into(){
+            Ok(value) => PackAs { value, wire: PhantomData },
+            Err(_) => panic!("the default {} does not fit in {}", core::any::type_name::<W>(), core::any::type_name::<T>()),
+        }
+    }
+}
+
+impl<W, T: Clone> Clone for PackAs<W, T>{
+    fn clone(&self) -> Self {
+        PackAs { value: self.value.clone(), wire: PhantomData }
+    }
+}
+
+impl<W, T: Copy> Copy for PackAs<W, T>{}
+
+impl<W, T: PartialEq> PartialEq for PackAs<W, T>{
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<W, T: fmt::Debug> fmt::Debug for PackAs<W, T>{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<W, T> Packable for PackAs<W, T>
+where
+    W: Packable + Default + Copy + fmt::Debug + TryInto<T>,
+    T: Copy + TryInto<W>,
+{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        match self.value.try_into(){
+            Ok(wire) => wire.pack(litle_endian),
+            Err(_) => unreachable!("checked by PackAs::new"),
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self.value.try_into(){
+            Ok(wire) => wire.size(),
+            Err(_) => unreachable!("checked by PackAs::new"),
+        }
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        let mut wire = W::default();
+        let consumed = wire.unpack_from(buf, litle_endian)?;
+        self.value = wire.try_into().map_err(|_| PackableError { 
+            error_kind: ErrorKind::OutOfRange, 
+            data: format!("{:?} does not fit in {}", wire, core::any::type_name::<T>()),
+            direction: Some(Direction::Unpack),
+        })?;
+        Ok(consumed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, to_bytes, from_bytes};
+    use super::{PackAs, PackAsI32, PackAsU64};
+
+    #[test]
+    fn test_pack_as(){
+        let len = PackAs::<u16, usize>::new(300).unwrap();
+        assert_eq!(vec![1, 44], to_bytes(&len, false));
+        assert_eq!(300, from_bytes::<PackAs<u16, usize>>(&[1, 44], false).unwrap().get());
+
+        let error = PackAs::<u8, usize>::new(300).unwrap_err();
+        assert!(matches!(error.error_kind, ErrorKind::OutOfRange));
+        let error = from_bytes::<PackAs<i8, u32>>(&[0xff], false).unwrap_err();
+        assert!(matches!(error.error_kind, ErrorKind::OutOfRange));
+
+        assert_eq!(vec![0, 0, 0, 0, 0, 0, 0, 7], to_bytes(&PackAsU64::new(7).unwrap(), false));
+        assert_eq!(-2, from_bytes::<PackAsI32>(&[0xfe, 0xff, 0xff, 0xff], true).unwrap().get());
+        assert_eq!(vec![0], to_bytes(&PackAs::<u8, usize>::default(), false));
+    }
+}
@@ -0,0 +1,117 @@
+use core::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
+use core::num::{NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroU128, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8};
+use crate::{Be, Bf16, BlockHeader, Bytes, Decimal32, FixedString, Flag, Le, Padding, StrictBool};
+
+/// The packed size of a type whose every value packs to the same number of bytes,
+/// known at compile time, for stack buffers and static checks of frame layouts:
+///
+/// ```
+/// use packable::PackedSize;
+///
+/// type Header = (u8, u16, [u32; 2]);
+/// const _: () = assert!(Header::SIZE == 11);
+/// let buffer = [0u8; Header::SIZE];
+/// # let _ = buffer;
+/// ```
+///
+/// `#[derive(PackedSize)]` sums the sizes of the fields of a struct, or takes the
+/// size of the repr of an enum. `Packable::size` of a value always equals `SIZE`.
+pub trait PackedSize{
+    const SIZE: usize;
+}
+
+macro_rules! impl_packed_size {
+    ( $( $t:ty => $size:expr ),* ) => {
+        $(
+            impl PackedSize for $t{
+                const SIZE: usize = $size;
+            }
+        )*
+    };
+}
+
+impl_packed_size!(
+    u8 => 1, u16 => 2, u32 => 4, u64 => 8, u128 => 16,
+    i8 => 1, i16 => 2, i32 => 4, i64 => 8, i128 => 16,
+    f32 => 4, f64 => 8, bool => 1, char => 4, StrictBool => 1,
+    NonZeroU8 => 1, NonZeroU16 => 2, NonZeroU32 => 4, NonZeroU64 => 8, NonZeroU128 => 16,
+    NonZeroI8 => 1, NonZeroI16 => 2, NonZeroI32 => 4, NonZeroI64 => 8, NonZeroI128 => 16,
+    Bf16 => 2, Decimal32 => 4, BlockHeader => 24,
+    Ipv4Addr => 4, Ipv6Addr => 16, SocketAddrV4 => 6, SocketAddrV6 => 18
+);
+
+impl<T: PackedSize, const N: usize> PackedSize for [T; N]{
+    const SIZE: usize = N * T::SIZE;
+}
+
+impl<const N: usize> PackedSize for Bytes<N>{
+    const SIZE: usize = N;
+}
+
+impl<const N: usize, const PAD: u8> PackedSize for FixedString<N, PAD>{
+    const SIZE: usize = N;
+}
+
+impl<const N: usize, const FILL: u8, const STRICT: bool> PackedSize for Padding<N, FILL, STRICT>{
+    const SIZE: usize = N;
+}
+
+impl_packed_size!(Flag<u8> => 1, Flag<u16> => 2, Flag<u32> => 4, Flag<u64> => 8);
+
+impl<T: PackedSize> PackedSize for Le<T>{
+    const SIZE: usize = T::SIZE;
+}
+
+impl<T: PackedSize> PackedSize for Be<T>{
+    const SIZE: usize = T::SIZE;
+}
+
+macro_rules! impl_packed_size_tuple {
+    ( $( $name:ident )+ ) => {
+        impl<$( $name: PackedSize ),+> PackedSize for ( $( $name, )+ ){
+            const SIZE: usize = 0 $( + $name::SIZE )+;
+        }
+    };
+}
+
+impl_packed_size_tuple!(A);
+impl_packed_size_tuple!(A B);
+impl_packed_size_tuple!(A B C);
+impl_packed_size_tuple!(A B C D);
+impl_packed_size_tuple!(A B C D E);
+impl_packed_size_tuple!(A B C D E F);
+impl_packed_size_tuple!(A B C D E F G);
+impl_packed_size_tuple!(A B C D E F G H);
+impl_packed_size_tuple!(A B C D E F G H I);
+impl_packed_size_tuple!(A B C D E F G H I J);
+impl_packed_size_tuple!(A B C D E F G H I J K);
+impl_packed_size_tuple!(A B C D E F G H I J K L);
+
+#[cfg(test)]
+mod tests {
+    use core::net::{Ipv6Addr, SocketAddrV4};
+    use core::num::NonZeroU32;
+    use crate::{Be, Bytes, Flag, Packable, Padding};
+    use super::PackedSize;
+
+    fn check<T: PackedSize + Packable>(value: T){
+        assert_eq!(T::SIZE, value.size());
+        assert_eq!(T::SIZE, value.pack(false).len());
+    }
+
+    #[test]
+    fn test_packed_size(){
+        check(0u16);
+        check('a');
+        check(NonZeroU32::MIN);
+        check([[0u16; 3]; 2]);
+        check(Bytes([0; 5]));
+        check(Flag::<u32>::default());
+        check(Padding::<3>);
+        check(Be(0u64));
+        check(Ipv6Addr::LOCALHOST);
+        check(SocketAddrV4::new([10, 0, 0, 1].into(), 80));
+        check((1u8, 0i32, [true; 2]));
+        let _buffer = [0u8; <(u16, [u8; 4])>::SIZE];
+    }
+}
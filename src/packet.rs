@@ -0,0 +1,113 @@
+use core::marker::PhantomData;
+use crate::{Endian, Packable, PackableError, buffer_length_error, from_bytes};
+
+/// A packet being assembled or inspected in one contiguous buffer: a fixed-size
+/// `H` header, decoded on access, followed by a raw payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PacketBuf<H>{
+    data: Vec<u8>,
+    header_len: usize,
+    litle_endian: bool,
+    header: PhantomData<H>,
+}
+
+impl<H: Packable + Default> PacketBuf<H>{
+    pub fn new(header: &H, endian: impl Into<Endian>) -> Self{
+        let litle_endian = endian.into().is_little();
+        let data = header.pack(litle_endian);
+        PacketBuf { header_len: data.len(), data, litle_endian, header: PhantomData }
+    }
+
+    /// Wrap a received packet, checking that the header is complete and decodes.
+    pub fn from_vec(data: Vec<u8>, endian: impl Into<Endian>) -> Result<Self, PackableError>{
+        let litle_endian = endian.into().is_little();
+        let header_len = H::default().size();
+        if data.len() < header_len{
+            return Err(buffer_length_error(header_len, data.len()))
+        }
+        from_bytes::<H>(&data[..header_len], litle_endian)?;
+        Ok(PacketBuf { data, header_len, litle_endian, header: PhantomData })
+    }
+
+    pub fn header(&self) -> Result<H, PackableError>{
+        from_bytes(&self.data[..self.header_len], self.litle_endian)
+    }
+
+    /// # Panics
+    ///
+    /// Panics if `header` packs to a different size than the current one.
+    pub fn set_header(&mut self, header: &H){
+        let data = header.pack(self.litle_endian);
+        assert_eq!(self.header_len, data.len(), "header size changed");
+        self.data[..self.header_len].copy_from_slice(&data);
+    }
+
+    /// Decode the header, let `update` fix it up from the payload, e.g. its length
+    /// or checksum, and write it back.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the updated header packs to a different size.
+    pub fn update_header<F: FnOnce(&mut H, &[u8])>(&mut self, update: F) -> Result<(), PackableError>{
+        let mut header = self.header()?;
+        update(&mut header, self.payload());
+        self.set_header(&header);
+        Ok(())
+    }
+
+    pub fn payload(&self) -> &[u8]{
+        &self.data[self.header_len..]
+    }
+
+    pub fn payload_mut(&mut self) -> &mut [u8]{
+        &mut self.data[self.header_len..]
+    }
+
+    pub fn extend_payload(&mut self, bytes: &[u8]){
+        self.data.extend_from_slice(bytes);
+    }
+
+    pub fn truncate_payload(&mut self, len: usize){
+        self.data.truncate(self.header_len + len);
+    }
+
+    /// Append a packed value to the payload.
+    pub fn push<T: Packable + ?Sized>(&mut self, value: &T){
+        self.data.extend(value.pack(self.litle_endian));
+    }
+
+    /// The whole packet, header included.
+    pub fn as_bytes(&self) -> &[u8]{
+        &self.data
+    }
+
+    pub fn into_vec(self) -> Vec<u8>{
+        self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Checksum, Crc32, ErrorKind};
+    use super::PacketBuf;
+
+    #[test]
+    fn test_packet_buf(){
+        let mut packet = PacketBuf::<(u8, u16, u32)>::new(&(1, 0, 0), false);
+        packet.extend_payload(b"12345");
+        packet.push(&0x3839u16);
+        packet.truncate_payload(4);
+        packet.push(&b'5');
+        packet.update_header(|header, payload| {
+            header.1 = payload.len() as u16;
+            header.2 = Crc32::checksum(payload);
+        }).unwrap();
+        assert_eq!((1, 5, Crc32::checksum(b"12345")), packet.header().unwrap());
+        assert_eq!(b"12345", packet.payload());
+
+        let received = PacketBuf::<(u8, u16, u32)>::from_vec(packet.clone().into_vec(), false).unwrap();
+        assert_eq!(packet, received);
+        let error = PacketBuf::<(u8, u16, u32)>::from_vec(vec![1, 0], false).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+    }
+}
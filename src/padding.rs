@@ -0,0 +1,59 @@
+use crate::{Packable, PackableError, Direction, ErrorKind, buffer_length_error};
+
+/// `N` bytes of `FILL` that carry no value, in place of a `[u8; N]` placeholder
+/// field. Unpacking skips them unless `STRICT`, in which case any other byte fails
+/// with `InvalidValue`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Padding<const N: usize, const FILL: u8 = 0, const STRICT: bool = false>;
+
+impl<const N: usize, const FILL: u8, const STRICT: bool> Packable for Padding<N, FILL, STRICT>{
+    fn pack(&self, _litle_endian: bool) -> Vec<u8> {
+        vec![FILL; N]
+    }
+
+    fn pack_into(&self, out: &mut Vec<u8>, _litle_endian: bool) {
+        out.resize(out.len() + N, FILL);
+    }
+
+    fn size(&self) -> usize {
+        N
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], _litle_endian: bool) -> Result<usize, PackableError> {
+        let padding = buf.get(..N).ok_or_else(|| buffer_length_error(N, buf.len()))?;
+        if let Some(index) = padding.iter().position(|byte| STRICT && *byte != FILL){
+            return Err(PackableError { 
+                error_kind: ErrorKind::InvalidValue, 
+                data: format!("padding byte {} is {:#04x}, not {:#04x}", index, padding[index], FILL),
+                direction: Some(Direction::Unpack),
+            })
+        }
+        Ok(N)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, Packable, from_bytes};
+    use super::Padding;
+
+    #[test]
+    fn test_padding(){
+        let record = (1u8, Padding::<3, 0xff>, 2u8);
+        assert_eq!(vec![1, 0xff, 0xff, 0xff, 2], record.pack(false));
+        assert_eq!(5, record.size());
+        assert_eq!(record, from_bytes(&[1, 0, 0, 0, 2], false).unwrap());
+        assert_eq!(ErrorKind::BufferLengthError, from_bytes::<Padding<2>>(&[0], false).unwrap_err().error_kind);
+
+        let mut strict = Padding::<2, 0, true>;
+        assert_eq!(2, strict.unpack_from(&[0, 0, 9], false).unwrap());
+        let error = strict.unpack_from(&[0, 7], false).unwrap_err();
+        assert_eq!(ErrorKind::InvalidValue, error.error_kind);
+        assert_eq!("padding byte 1 is 0x07, not 0x00", error.data);
+    }
+}
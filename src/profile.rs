@@ -0,0 +1,95 @@
+use crate::{Codec, Framing, Packable, PackableError};
+
+/// Wire settings of one family of devices, selected by type so the same message
+/// types can target several of them, through `to_bytes_for` and `from_bytes_for`
+/// or a `Codec` built with `Codec::for_profile`:
+///
+/// ```
+/// use packable::{Framing, LengthPrefix, WireProfile, from_bytes_for, to_bytes_for};
+///
+/// struct Legacy;
+///
+/// impl WireProfile for Legacy {
+///     const LITLE_ENDIAN: bool = false;
+///     const FRAMING: Framing = Framing::LengthPrefixed(LengthPrefix::U16);
+/// }
+///
+/// struct Compact;
+///
+/// impl WireProfile for Compact {
+///     const LITLE_ENDIAN: bool = true;
+///     const FRAMING: Framing = Framing::LengthPrefixed(LengthPrefix::Remaining);
+/// }
+///
+/// assert_eq!(vec![0, 2, 0x12, 0x34], to_bytes_for::<Legacy, _>(&0x1234u16).unwrap());
+/// assert_eq!(vec![2, 0x34, 0x12], to_bytes_for::<Compact, _>(&0x1234u16).unwrap());
+/// assert_eq!(0x1234u16, from_bytes_for::<Compact, _>(&[2, 0x34, 0x12]).unwrap());
+/// ```
+///
+/// The settings apply to the whole message; fields with a byte order of their own,
+/// such as `Le` or `#[packable(endian = "..")]`, keep it.
+pub trait WireProfile{
+    const LITLE_ENDIAN: bool;
+    const FRAMING: Framing = Framing::None;
+    const MAX_SIZE: Option<usize> = None;
+    const STRICT: bool = false;
+}
+
+impl Codec{
+    pub fn for_profile<P: WireProfile>() -> Self{
+        let codec = Codec::new(P::LITLE_ENDIAN).framing(P::FRAMING).strict(P::STRICT);
+        match P::MAX_SIZE{
+            Some(max_size) => codec.max_size(max_size),
+            None => codec,
+        }
+    }
+}
+
+/// Pack `value` as a frame of profile `P`.
+pub fn to_bytes_for<P: WireProfile, T: Packable + ?Sized>(value: &T) -> Result<Vec<u8>, PackableError>{
+    Codec::for_profile::<P>().encode(value)
+}
+
+/// Unpack a frame of profile `P`.
+pub fn from_bytes_for<P: WireProfile, T: Packable + Default>(data: &[u8]) -> Result<T, PackableError>{
+    Codec::for_profile::<P>().decode(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Be, Codec, ErrorKind, Framing, LengthPrefix};
+    use super::{WireProfile, from_bytes_for, to_bytes_for};
+
+    struct Compact;
+
+    impl WireProfile for Compact{
+        const LITLE_ENDIAN: bool = true;
+        const FRAMING: Framing = Framing::LengthPrefixed(LengthPrefix::Remaining);
+        const MAX_SIZE: Option<usize> = Some(2);
+        const STRICT: bool = true;
+    }
+
+    #[test]
+    fn test_wire_profile(){
+        let codec = Codec::for_profile::<Compact>();
+        assert_eq!(vec![2, 0x34, 0x12], codec.encode(&0x1234u16).unwrap());
+        assert_eq!(ErrorKind::LimitExceeded, codec.encode(&0u32).unwrap_err().error_kind);
+        assert_eq!(ErrorKind::TrailingBytes, codec.decode::<u8>(&[2, 1, 2]).unwrap_err().error_kind);
+    }
+
+    struct Legacy;
+
+    impl WireProfile for Legacy{
+        const LITLE_ENDIAN: bool = false;
+    }
+
+    #[test]
+    fn test_profile_functions(){
+        let message = (0x0102u16, Be(0x0304u16));
+        assert_eq!(vec![1, 2, 3, 4], to_bytes_for::<Legacy, _>(&message).unwrap());
+        assert_eq!(message, from_bytes_for::<Legacy, _>(&[1, 2, 3, 4]).unwrap());
+        let error = to_bytes_for::<Compact, _>(&message).unwrap_err();
+        assert_eq!(ErrorKind::LimitExceeded, error.error_kind);
+        assert_eq!(0x0102u16, from_bytes_for::<Compact, _>(&[2, 2, 1]).unwrap());
+    }
+}
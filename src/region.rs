@@ -0,0 +1,119 @@
+use core::{fmt, marker::PhantomData};
+use crate::{Packable, PackableError, Direction, ErrorKind, UnpackSource, buffer_length_error};
+
+/// A `T` preceded by its length in bytes as an `L`. Unpacking `T` must use up the
+/// whole region: leftover bytes are an error, or with `SKIP_EXTRA` they are kept
+/// aside and written back on pack, so unknown extensions survive a round trip.
+///
+/// # Panics
+///
+/// `pack` panics if the region has grown too large for `L`.
+pub struct Region<L, T, const SKIP_EXTRA: bool = false>{
+    pub value: T,
+    extra: Vec<u8>,
+    len: PhantomData<L>,
+}
+
+impl<L, T, const SKIP_EXTRA: bool> Region<L, T, SKIP_EXTRA>{
+    pub fn new(value: T) -> Self{
+        Region { value, extra: Vec::new(), len: PhantomData }
+    }
+
+    /// Bytes that followed `value` inside the region and were skipped.
+    pub fn extra(&self) -> &[u8]{
+        &self.extra
+    }
+}
+
+impl<L, T: Default, const SKIP_EXTRA: bool> Default for Region<L, T, SKIP_EXTRA>{
+    fn default() -> Self {
+        Region::new(T::default())
+    }
+}
+
+impl<L, T: fmt::Debug, const SKIP_EXTRA: bool> fmt::Debug for Region<L, T, SKIP_EXTRA>{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Region").field("value", &self.value).field("extra", &self.extra).finish()
+    }
+}
+
+impl<L, T, const SKIP_EXTRA: bool> Packable for Region<L, T, SKIP_EXTRA>
+where
+    L: Packable + Default + Copy + TryFrom<usize> + TryInto<usize>,
+    T: Packable,
+{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        let len = self.value.size() + self.extra.len();
+        let Ok(prefix) = L::try_from(len) else {
+            panic!("region of {} bytes does not fit in {}", len, core::any::type_name::<L>())
+        };
+        let mut data = prefix.pack(litle_endian);
+        data.extend(self.value.pack(litle_endian));
+        data.extend_from_slice(&self.extra);
+        data
+    }
+
+    fn size(&self) -> usize {
+        L::default().size() + self.value.size() + self.extra.len()
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        let mut source = buf;
+        let mut prefix = L::default();
+        source.unpack_into(&mut prefix, litle_endian)?;
+        let len: usize = prefix.try_into().map_err(|_| PackableError { 
+            error_kind: ErrorKind::OutOfRange, 
+            data: "region length does not fit in usize".to_string(),
+            direction: Some(Direction::Unpack),
+        })?;
+        let mut region = source.get(..len).ok_or_else(|| buffer_length_error(len, source.len()))?;
+        region.unpack_into(&mut self.value, litle_endian)?;
+        self.extra.clear();
+        if !region.is_empty(){
+            if !SKIP_EXTRA{
+                return Err(PackableError { 
+                    error_kind: ErrorKind::TrailingBytes, 
+                    data: format!("{} bytes left in a region of {}", region.len(), len),
+                    direction: Some(Direction::Unpack),
+                })
+            }
+            self.extra.extend_from_slice(region);
+        }
+        Ok(prefix.size() + len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, Packable, UntilEnd, to_bytes, from_bytes};
+    use super::Region;
+
+    #[test]
+    fn test_region(){
+        let region = Region::<u8, UntilEnd<u16>>::new(UntilEnd(vec![1, 2]));
+        assert_eq!(vec![4, 0, 1, 0, 2], to_bytes(&region, false));
+
+        let (mut region, mut after) = (Region::<u8, UntilEnd<u16>>::default(), 0u8);
+        crate::unpack!(false, &mut vec![2, 0, 7, 9], region, after).unwrap();
+        assert_eq!((vec![7], 9), (region.value.0.clone(), after));
+    }
+
+    #[test]
+    fn test_region_extra(){
+        let error = from_bytes::<Region<u16, u16>>(&[0, 3, 0, 1, 0xff], false).unwrap_err();
+        assert_eq!(ErrorKind::TrailingBytes, error.error_kind);
+
+        let region = from_bytes::<Region<u16, u16, true>>(&[0, 3, 0, 1, 0xff], false).unwrap();
+        assert_eq!((1, &[0xff][..]), (region.value, region.extra()));
+        assert_eq!(5, region.size());
+        assert_eq!(vec![0, 3, 0, 1, 0xff], to_bytes(&region, false));
+
+        let error = from_bytes::<Region<u16, u16>>(&[0, 3, 0, 1], false).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+    }
+}
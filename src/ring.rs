@@ -0,0 +1,198 @@
+use std::{io::{self, Read}, time::{Duration, Instant}};
+use crate::{Codec, Direction, ErrorKind, Packable, PackableError};
+
+/// Fixed-capacity circular byte buffer that decodes frames as bytes trickle in
+/// (e.g. from a UART interrupt), reading across the wrap-around without first
+/// making the buffer contiguous. Each complete frame's payload is copied out
+/// before it is decoded, so bytes can keep arriving meanwhile.
+#[derive(Debug, Clone)]
+pub struct RingBuffer{
+    data: Box<[u8]>,
+    head: usize,
+    len: usize,
+    timeout: Option<Duration>,
+    partial_since: Option<Instant>,
+}
+
+impl RingBuffer{
+    pub fn new(capacity: usize) -> Self{
+        RingBuffer { data: vec![0; capacity].into_boxed_slice(), head: 0, len: 0, timeout: None, partial_since: None }
+    }
+
+    /// Age after which `decode_at` gives up on an incomplete frame, so a peer that
+    /// dies mid-frame cannot wedge the buffer.
+    pub fn frame_timeout(mut self, timeout: Duration) -> Self{
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn capacity(&self) -> usize{
+        self.data.len()
+    }
+
+    pub fn len(&self) -> usize{
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool{
+        self.len == self.data.len()
+    }
+
+    /// Append one byte, returning `false` if the buffer is full.
+    pub fn push(&mut self, byte: u8) -> bool{
+        if self.is_full(){
+            return false
+        }
+        let tail = (self.head + self.len) % self.data.len();
+        self.data[tail] = byte;
+        self.len += 1;
+        true
+    }
+
+    /// Append as many bytes as fit, returning how many were taken.
+    pub fn extend(&mut self, bytes: &[u8]) -> usize{
+        bytes.iter().take_while(|byte| self.push(**byte)).count()
+    }
+
+    /// The buffered bytes in order, as the part up to the end of storage and the
+    /// part that wrapped around to its start.
+    pub fn as_slices(&self) -> (&[u8], &[u8]){
+        let end = self.head + self.len;
+        if end <= self.data.len(){
+            (&self.data[self.head..end], &[])
+        }
+        else{
+            (&self.data[self.head..], &self.data[..end - self.data.len()])
+        }
+    }
+
+    /// Drop `count` bytes from the front, e.g. to resynchronize after garbage.
+    pub fn consume(&mut self, count: usize){
+        let count = count.min(self.len);
+        self.head = (self.head + count) % self.data.len().max(1);
+        self.len -= count;
+        self.partial_since = None;
+    }
+
+    pub fn clear(&mut self){
+        self.head = 0;
+        self.len = 0;
+        self.partial_since = None;
+    }
+
+    /// Decode the frame at the front of the buffer once it is complete. Returns
+    /// `Ok(None)` while bytes are missing. A complete frame is consumed even when
+    /// its payload fails to decode. A framing error, such as a length over the
+    /// codec's limit, drops the first byte, so calling `decode` again looks for a
+    /// frame at the next one and resynchronizes after garbage.
+    pub fn decode<T: Packable + Default>(&mut self, codec: &Codec) -> Result<Option<T>, PackableError>{
+        let (first, second) = self.as_slices();
+        let mut reader = first.chain(second);
+        let (payload, len) = match codec.read_frame(&mut reader, T::default().size()){
+            Ok(Some(frame)) => frame,
+            Ok(None) => return Ok(None),
+            Err(error) if error.error_kind == ErrorKind::BufferLengthError => return Ok(None),
+            Err(error) => {
+                self.consume(1);
+                return Err(codec.record_error(error))
+            }
+        };
+        self.consume(len);
+        codec.record_decode(codec.decode_payload(&payload), len).map(Some)
+    }
+
+    /// Like `decode`, with `now` read from the caller's clock. An incomplete frame
+    /// is timed from the first call that sees it; once older than the frame
+    /// timeout it is dropped with an `Io(TimedOut)` error.
+    pub fn decode_at<T: Packable + Default>(&mut self, codec: &Codec, now: Instant) -> Result<Option<T>, PackableError>{
+        let value = self.decode(codec)?;
+        if value.is_some() || self.is_empty(){
+            return Ok(value)
+        }
+        let since = *self.partial_since.get_or_insert(now);
+        match self.timeout{
+            Some(timeout) if now.saturating_duration_since(since) >= timeout => {
+                let len = self.len;
+                self.clear();
+                Err(codec.record_error(PackableError { 
+                    error_kind: ErrorKind::Io(io::ErrorKind::TimedOut), 
+                    data: format!("partial frame of {} bytes expired after {:?}", len, timeout),
+                    direction: Some(Direction::Unpack),
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io, time::{Duration, Instant}};
+    use crate::{Codec, ErrorKind, Framing, LengthPrefix};
+    use super::RingBuffer;
+
+    #[test]
+    fn test_ring_buffer_wrap(){
+        let mut ring = RingBuffer::new(4);
+        assert_eq!(3, ring.extend(&[1, 2, 3]));
+        ring.consume(2);
+        assert_eq!(3, ring.extend(&[4, 5, 6, 7]));
+        assert!(ring.is_full());
+        assert_eq!((&[3, 4][..], &[5, 6][..]), ring.as_slices());
+    }
+
+    #[test]
+    fn test_ring_buffer_decode(){
+        let codec = Codec::new(false).framing(Framing::LengthPrefixed(LengthPrefix::U8)).max_size(4);
+        let mut ring = RingBuffer::new(6);
+        ring.extend(&[0, 0, 0, 0]);
+        ring.consume(4);
+
+        ring.extend(&[2, 0x12]);
+        assert_eq!(None, ring.decode::<u16>(&codec).unwrap());
+        ring.extend(&[0x34, 1, 0xff]);
+        assert_eq!(Some(0x1234), ring.decode::<u16>(&codec).unwrap());
+        let error = ring.decode::<u16>(&codec).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+        assert!(ring.is_empty());
+
+        ring.extend(&[9]);
+        let error = ring.decode::<u16>(&codec).unwrap_err();
+        assert_eq!(ErrorKind::LimitExceeded, error.error_kind);
+        assert!(ring.is_empty());
+    }
+
+    #[test]
+    fn test_ring_buffer_resync(){
+        let codec = Codec::new(false).framing(Framing::LengthPrefixed(LengthPrefix::U8)).max_size(4);
+        let mut ring = RingBuffer::new(8);
+        ring.extend(&[9, 7, 2, 0x12, 0x34]);
+        let errors: Vec<ErrorKind> = (0..2).map(|_| ring.decode::<u16>(&codec).unwrap_err().error_kind).collect();
+        assert_eq!(vec![ErrorKind::LimitExceeded; 2], errors);
+        assert_eq!(Some(0x1234), ring.decode::<u16>(&codec).unwrap());
+        assert!(ring.is_empty());
+        assert_eq!(2, codec.stats().errors[&ErrorKind::LimitExceeded]);
+    }
+
+    #[test]
+    fn test_ring_buffer_timeout(){
+        let codec = Codec::new(false).framing(Framing::LengthPrefixed(LengthPrefix::U8));
+        let mut ring = RingBuffer::new(8).frame_timeout(Duration::from_millis(100));
+        let start = Instant::now();
+        ring.extend(&[2, 0x12]);
+        assert_eq!(None, ring.decode_at::<u16>(&codec, start).unwrap());
+        assert_eq!(None, ring.decode_at::<u16>(&codec, start + Duration::from_millis(99)).unwrap());
+        let error = ring.decode_at::<u16>(&codec, start + Duration::from_millis(100)).unwrap_err();
+        assert_eq!(ErrorKind::Io(io::ErrorKind::TimedOut), error.error_kind);
+        assert!(ring.is_empty());
+
+        ring.extend(&[2, 0x12, 0x34, 1]);
+        assert_eq!(Some(0x1234), ring.decode_at::<u16>(&codec, start).unwrap());
+        assert_eq!(None, ring.decode_at::<u16>(&codec, start + Duration::from_secs(1)).unwrap());
+        assert_eq!(1, ring.len());
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{Packable, PackableError, Direction, ErrorKind, UnpackSource};
+
+/// Run-length encoded bytes for sparse or padding-heavy sections: the decoded
+/// length as a `u32`, then runs of a count (1 to 255) and the repeated byte.
+/// Unpacking rejects empty runs and runs past the declared length, and leaves the
+/// value empty if it fails. Runs of the same byte need not be merged, so the bytes
+/// consumed may exceed `size()`, which counts the runs `pack` would write.
+///
+/// # Panics
+///
+/// `pack` panics on more than `u32::MAX` bytes.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Rle<T>(pub T);
+
+impl Rle<Vec<u8>>{
+    fn runs(&self) -> impl Iterator<Item = (u8, u8)> + '_{
+        self.0.chunk_by(|a, b| a == b).flat_map(|run| {
+            run.chunks(u8::MAX as usize).map(|chunk| (chunk.len() as u8, chunk[0]))
+        })
+    }
+}
+
+impl Packable for Rle<Vec<u8>>{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        let Ok(len) = u32::try_from(self.0.len()) else {
+            panic!("{} bytes do not fit a u32 run-length header", self.0.len())
+        };
+        let mut data = len.pack(litle_endian);
+        for (count, byte) in self.runs(){
+            data.extend([count, byte]);
+        }
+        data
+    }
+
+    fn size(&self) -> usize {
+        4 + 2 * self.runs().count()
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        self.0.clear();
+        let result = self.decode(buf, litle_endian);
+        if result.is_err(){
+            self.0.clear();
+        }
+        result
+    }
+}
+
+impl Rle<Vec<u8>>{
+    fn decode(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        let mut source = buf;
+        let mut len = 0u32;
+        source.unpack_into(&mut len, litle_endian)?;
+        let len = len as usize;
+        while self.0.len() < len{
+            let (mut count, mut byte) = (0u8, 0u8);
+            crate::unpack!(litle_endian, &mut source, count, byte)?;
+            if count == 0 || self.0.len() + count as usize > len{
+                return Err(PackableError { 
+                    error_kind: ErrorKind::InvalidEncoding, 
+                    data: format!("run of {} at offset {} in {} bytes", count, self.0.len(), len),
+                    direction: Some(Direction::Unpack),
+                })
+            }
+            self.0.resize(self.0.len() + count as usize, byte);
+        }
+        Ok(buf.len() - source.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, Packable, from_bytes};
+    use super::Rle;
+
+    #[test]
+    fn test_rle(){
+        let mut raw = vec![0u8; 300];
+        raw[10] = 7;
+        let rle = Rle(raw);
+        let data = rle.pack(false);
+        assert_eq!(vec![0, 0, 1, 44, 10, 0, 1, 7, 255, 0, 34, 0], data);
+        assert_eq!(data.len(), rle.size());
+        assert_eq!(rle, from_bytes(&data, false).unwrap());
+
+        let error = from_bytes::<Rle<Vec<u8>>>(&[0, 0, 0, 2, 0, 1], false).unwrap_err();
+        assert_eq!(ErrorKind::InvalidEncoding, error.error_kind);
+        let error = from_bytes::<Rle<Vec<u8>>>(&[0, 0, 0, 2, 3, 1], false).unwrap_err();
+        assert_eq!(ErrorKind::InvalidEncoding, error.error_kind);
+        let error = from_bytes::<Rle<Vec<u8>>>(&[0, 0, 0, 2, 1, 1], false).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+    }
+
+    #[test]
+    fn test_rle_unmerged_runs(){
+        let (mut rle, mut after) = (Rle(vec![9]), 0u8);
+        assert_eq!(9, unpack!(false, &mut vec![0, 0, 0, 2, 1, 5, 1, 5, 3], rle, after).unwrap());
+        assert_eq!((Rle(vec![5, 5]), 3), (rle.clone(), after));
+
+        assert!(rle.unpack_from(&[0, 0, 0, 3, 1, 5, 1, 5], false).is_err());
+        assert!(rle.0.is_empty());
+    }
+}
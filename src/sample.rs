@@ -0,0 +1,199 @@
+use crate::{Bytes, RawCount, Scale, Scaled, UntilEnd, Vlq};
+
+/// Most elements a sampled `Vec`, `String` or `UntilEnd` gets, so samples stay
+/// small enough for narrow length prefixes.
+const MAX_LEN: u64 = 8;
+
+/// Small deterministic generator (xorshift64*) so samples are reproducible from a
+/// seed without pulling in a random number crate.
+#[derive(Debug, Clone)]
+pub struct SampleRng{
+    state: u64,
+}
+
+impl SampleRng{
+    pub fn new(seed: u64) -> Self{
+        SampleRng { state: seed.max(1) }
+    }
+
+    pub fn next_u64(&mut self) -> u64{
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Uniform-ish value in `0..bound`; `bound` must not be zero.
+    pub fn below(&mut self, bound: u64) -> u64{
+        self.next_u64() % bound
+    }
+
+    pub fn fill(&mut self, bytes: &mut [u8]){
+        for chunk in bytes.chunks_mut(8){
+            let random = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&random[..chunk.len()]);
+        }
+    }
+}
+
+/// Plausible random instances, for load-testing encoders and feeding simulators.
+/// `#[derive(Sample)]` draws each field in turn, honouring the `#[packable(..)]`
+/// attributes `Packable` would: `as` and `scale` fields fit their wire type,
+/// computed and `size_of` fields match the others, and enums pick one of their
+/// variants or a value from their reserved ranges.
+pub trait Sample: Sized {
+    fn sample(rng: &mut SampleRng) -> Self;
+}
+
+macro_rules! impl_sample_integer {
+    ( $($t:ty),* ) => {
+        $(
+            impl Sample for $t {
+                fn sample(rng: &mut SampleRng) -> Self {
+                    rng.next_u64() as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_sample_integer!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+impl Sample for u128{
+    fn sample(rng: &mut SampleRng) -> Self {
+        (rng.next_u64() as u128) << 64 | rng.next_u64() as u128
+    }
+}
+
+impl Sample for i128{
+    fn sample(rng: &mut SampleRng) -> Self {
+        u128::sample(rng) as i128
+    }
+}
+
+// Floats are drawn from [-1, 1) scaled by a random power of two, so they are always
+// finite and span a range of magnitudes.
+impl Sample for f32{
+    fn sample(rng: &mut SampleRng) -> Self {
+        let unit = (rng.next_u64() >> 40) as f32 / (1u64 << 23) as f32 - 1.0;
+        unit * 2f32.powi(rng.below(64) as i32 - 32)
+    }
+}
+
+impl Sample for f64{
+    fn sample(rng: &mut SampleRng) -> Self {
+        let unit = (rng.next_u64() >> 11) as f64 / (1u64 << 52) as f64 - 1.0;
+        unit * 2f64.powi(rng.below(128) as i32 - 64)
+    }
+}
+
+impl Sample for bool{
+    fn sample(rng: &mut SampleRng) -> Self {
+        rng.next_u64() & 1 == 1
+    }
+}
+
+/// Mostly ASCII, with some code points from the rest of the range.
+impl Sample for char{
+    fn sample(rng: &mut SampleRng) -> Self {
+        match rng.below(4){
+            0 => char::from_u32(rng.below(0x11_0000) as u32).unwrap_or(char::REPLACEMENT_CHARACTER),
+            _ => (b' ' + rng.below(95) as u8) as char,
+        }
+    }
+}
+
+impl<T: Sample, const N: usize> Sample for [T; N]{
+    fn sample(rng: &mut SampleRng) -> Self {
+        core::array::from_fn(|_| T::sample(rng))
+    }
+}
+
+impl<T: Sample> Sample for Option<T>{
+    fn sample(rng: &mut SampleRng) -> Self {
+        bool::sample(rng).then(|| T::sample(rng))
+    }
+}
+
+impl<T: Sample> Sample for Vec<T>{
+    fn sample(rng: &mut SampleRng) -> Self {
+        (0..rng.below(MAX_LEN + 1)).map(|_| T::sample(rng)).collect()
+    }
+}
+
+impl<T: Sample> Sample for UntilEnd<T>{
+    fn sample(rng: &mut SampleRng) -> Self {
+        UntilEnd(Sample::sample(rng))
+    }
+}
+
+impl Sample for String{
+    fn sample(rng: &mut SampleRng) -> Self {
+        (0..rng.below(MAX_LEN + 1)).map(|_| char::sample(rng)).collect()
+    }
+}
+
+/// A raw count within `S::MIN..=S::MAX` when both are finite, otherwise any.
+impl<W: Sample + RawCount, S: Scale> Sample for Scaled<W, S>{
+    fn sample(rng: &mut SampleRng) -> Self {
+        let (low, high) = ((S::MIN - S::OFFSET) / S::SCALE, (S::MAX - S::OFFSET) / S::SCALE);
+        let (low, high) = (low.min(high).ceil(), low.max(high).floor());
+        match low.is_finite() && high.is_finite() && low <= high{
+            true => Scaled::from_raw(W::from_f64(low + rng.below(((high - low) as u64).saturating_add(1)) as f64)),
+            false => Scaled::from_raw(W::sample(rng)),
+        }
+    }
+}
+
+impl<const N: usize> Sample for Bytes<N>{
+    fn sample(rng: &mut SampleRng) -> Self {
+        Bytes(Sample::sample(rng))
+    }
+}
+
+impl<const N: usize> Sample for Vlq<N>{
+    fn sample(rng: &mut SampleRng) -> Self {
+        let value = match Vlq::<N>::MAX.checked_add(1){
+            Some(bound) => rng.below(bound),
+            None => rng.next_u64(),
+        };
+        Vlq::new(value).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Scale, Scaled, Vlq, to_bytes, from_bytes};
+    use super::{Sample, SampleRng};
+
+    struct Percent;
+
+    impl Scale for Percent{
+        const SCALE: f64 = 0.5;
+        const MIN: f64 = 10.2;
+        const MAX: f64 = 20.2;
+    }
+
+    #[test]
+    fn test_sample(){
+        let mut rng = SampleRng::new(42);
+        let first: [u64; 4] = core::array::from_fn(|_| rng.next_u64());
+        let mut rng = SampleRng::new(42);
+        assert_eq!(first, core::array::from_fn(|_| rng.next_u64()));
+
+        for _ in 0..100{
+            let value = f64::sample(&mut rng);
+            assert!(value.is_finite());
+            let quantity = Vlq::<2>::sample(&mut rng);
+            assert!(quantity.get() <= Vlq::<2>::MAX);
+            assert_eq!(quantity, from_bytes(&to_bytes(&quantity, false), false).unwrap());
+            let names = Vec::<String>::sample(&mut rng);
+            assert!(names.len() <= 8);
+            assert_eq!(names, from_bytes::<Vec<String>>(&to_bytes(&names, false), false).unwrap());
+            let percent = Scaled::<u8, Percent>::sample(&mut rng).plausible();
+            assert!(percent.is_some(), "{:?}", percent);
+        }
+        let flags = <[Option<bool>; 64]>::sample(&mut rng);
+        assert!(flags.contains(&None) && flags.contains(&Some(true)) && flags.contains(&Some(false)));
+    }
+}
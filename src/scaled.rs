@@ -0,0 +1,255 @@
+use core::{fmt, marker::PhantomData};
+use crate::{Direction, ErrorKind, Packable, PackableError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding{
+    Nearest,
+    Floor,
+    Ceil,
+    Truncate,
+}
+
+/// What unpacking does with a value outside `Scale::MIN..=Scale::MAX`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangePolicy{
+    /// Fail with `ErrorKind::InvalidValue`.
+    Error,
+    /// Replace it with the nearest bound.
+    Clamp,
+    /// Keep the raw count; `Scaled::plausible` returns `None`.
+    Missing,
+}
+
+/// Linear conversion between raw counts and engineering units, as register maps in
+/// sensor datasheets specify it: `value = raw * SCALE + OFFSET`.
+///
+/// ```
+/// use packable::{Scale, Scaled};
+///
+/// struct Celsius;
+///
+/// impl Scale for Celsius {
+///     const SCALE: f64 = 0.1;
+///     const OFFSET: f64 = -40.0;
+/// }
+///
+/// let temperature = Scaled::<u16, Celsius>::new(21.5);
+/// assert_eq!(615, temperature.raw());
+/// assert_eq!(vec![0x02, 0x67], packable::to_bytes(&temperature, false));
+/// ```
+pub trait Scale {
+    const SCALE: f64;
+    const OFFSET: f64 = 0.0;
+    const ROUNDING: Rounding = Rounding::Nearest;
+    /// Plausible physical range, in engineering units.
+    const MIN: f64 = f64::NEG_INFINITY;
+    const MAX: f64 = f64::INFINITY;
+    const RANGE: RangePolicy = RangePolicy::Error;
+}
+
+/// Integer types usable as raw counts.
+pub trait RawCount: Copy {
+    /// Saturates at the bounds of the type.
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! impl_raw_count {
+    ( $($t:ty),* ) => {
+        $(
+            impl RawCount for $t {
+                fn from_f64(value: f64) -> Self {
+                    value as $t
+                }
+
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    };
+}
+
+impl_raw_count!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+/// The raw count for `value`, `(value - offset) / scale` rounded as asked, saturating
+/// at the bounds of `W`. Fields marked `#[packable(as = "u16", scale = 0.1)]` pack
+/// through this.
+pub fn to_raw_count<W: RawCount>(value: f64, scale: f64, offset: f64, rounding: Rounding) -> W{
+    let raw = (value - offset) / scale;
+    W::from_f64(match rounding{
+        Rounding::Nearest => raw.round(),
+        Rounding::Floor => raw.floor(),
+        Rounding::Ceil => raw.ceil(),
+        Rounding::Truncate => raw.trunc(),
+    })
+}
+
+/// The engineering value of `raw`, `raw * scale + offset`.
+pub fn from_raw_count<W: RawCount>(raw: W, scale: f64, offset: f64) -> f64{
+    raw.to_f64() * scale + offset
+}
+
+/// An engineering value stored on the wire as a raw `W` count scaled by `S`. A raw
+/// count in the plausible range is kept as is. Outside it, `S::RANGE` decides:
+/// `Error` fails and leaves the previous value, `Clamp` rewrites the count to the
+/// nearest bound, so packing again sends the clamped value, and `Missing` keeps the
+/// count as is.
+pub struct Scaled<W, S>{
+    raw: W,
+    scale: PhantomData<S>,
+}
+
+impl<W: RawCount, S: Scale> Scaled<W, S>{
+    /// Values outside the range of `W` saturate. With `RangePolicy::Clamp` the value
+    /// is first clamped to the plausible range.
+    pub fn new(value: f64) -> Self{
+        let value = match S::RANGE{
+            RangePolicy::Clamp => value.clamp(S::MIN, S::MAX),
+            _ => value,
+        };
+        Scaled::from_raw(to_raw_count(value, S::SCALE, S::OFFSET, S::ROUNDING))
+    }
+
+    pub fn from_raw(raw: W) -> Self{
+        Scaled { raw, scale: PhantomData }
+    }
+
+    pub fn raw(&self) -> W{
+        self.raw
+    }
+
+    pub fn get(&self) -> f64{
+        from_raw_count(self.raw, S::SCALE, S::OFFSET)
+    }
+
+    pub fn set(&mut self, value: f64){
+        *self = Scaled::new(value);
+    }
+
+    /// The value, or `None` if it is outside the plausible range.
+    pub fn plausible(&self) -> Option<f64>{
+        let value = self.get();
+        (S::MIN..=S::MAX).contains(&value).then_some(value)
+    }
+}
+
+impl<W: Default, S> Default for Scaled<W, S>{
+    fn default() -> Self {
+        Scaled { raw: W::default(), scale: PhantomData }
+    }
+}
+
+impl<W: Clone, S> Clone for Scaled<W, S>{
+    fn clone(&self) -> Self {
+        Scaled { raw: self.raw.clone(), scale: PhantomData }
+    }
+}
+
+impl<W: Copy, S> Copy for Scaled<W, S>{}
+
+impl<W: PartialEq, S> PartialEq for Scaled<W, S>{
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl<W: RawCount, S: Scale> fmt::Debug for Scaled<W, S>{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+impl<W: Packable + RawCount, S: Scale> Packable for Scaled<W, S>{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        self.raw.pack(litle_endian)
+    }
+
+    fn size(&self) -> usize {
+        self.raw.size()
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        let mut decoded = Scaled::<W, S>::from_raw(self.raw);
+        let consumed = decoded.raw.unpack_from(buf, litle_endian)?;
+        if decoded.plausible().is_none(){
+            match S::RANGE{
+                RangePolicy::Error => return Err(PackableError { 
+                    error_kind: ErrorKind::InvalidValue, 
+                    data: format!("{} outside plausible range {}..={}", decoded.get(), S::MIN, S::MAX),
+                    direction: Some(Direction::Unpack),
+                }),
+                RangePolicy::Clamp => decoded = Scaled::new(decoded.get()),
+                RangePolicy::Missing => {}
+            }
+        }
+        *self = decoded;
+        Ok(consumed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, Packable, from_bytes};
+    use super::{RangePolicy, Rounding, Scale, Scaled};
+
+    struct Celsius;
+
+    impl Scale for Celsius{
+        const SCALE: f64 = 0.1;
+        const OFFSET: f64 = -40.0;
+    }
+
+    struct Millivolts;
+
+    impl Scale for Millivolts{
+        const SCALE: f64 = 0.001;
+        const ROUNDING: Rounding = Rounding::Floor;
+    }
+
+    #[test]
+    fn test_scaled(){
+        let temperature = from_bytes::<Scaled<u16, Celsius>>(&[0x02, 0x67], false).unwrap();
+        assert!((temperature.get() - 21.5).abs() < 1e-9);
+        assert_eq!(0, Scaled::<u16, Celsius>::new(-50.0).raw());
+        assert_eq!(u16::MAX, Scaled::<u16, Celsius>::new(1e9).raw());
+        assert_eq!(1, Scaled::<u8, Millivolts>::new(0.0019).raw());
+        assert_eq!(2, Scaled::<u8, Celsius>::new(-39.84).raw());
+    }
+
+    struct Humidity<const P: u8>;
+
+    impl<const P: u8> Scale for Humidity<P>{
+        const SCALE: f64 = 0.5;
+        const MIN: f64 = 0.0;
+        const MAX: f64 = 100.0;
+        const RANGE: RangePolicy = match P {
+            0 => RangePolicy::Error,
+            1 => RangePolicy::Clamp,
+            _ => RangePolicy::Missing,
+        };
+    }
+
+    #[test]
+    fn test_scaled_range(){
+        let error = from_bytes::<Scaled<u8, Humidity<0>>>(&[0xff], false).unwrap_err();
+        assert_eq!(ErrorKind::InvalidValue, error.error_kind);
+        let mut humidity = Scaled::<u8, Humidity<0>>::from_raw(10);
+        assert!(humidity.unpack_from(&[0xff], false).is_err());
+        assert_eq!(10, humidity.raw());
+        assert_eq!(Some(50.0), from_bytes::<Scaled<u8, Humidity<0>>>(&[100], false).unwrap().plausible());
+
+        let clamped = from_bytes::<Scaled<u8, Humidity<1>>>(&[0xff], false).unwrap();
+        assert_eq!(200, clamped.raw());
+        assert_eq!(200, Scaled::<u8, Humidity<1>>::new(120.0).raw());
+
+        let missing = from_bytes::<Scaled<u8, Humidity<2>>>(&[0xff], false).unwrap();
+        assert_eq!(0xff, missing.raw());
+        assert_eq!(None, missing.plausible());
+    }
+}
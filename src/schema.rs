@@ -0,0 +1,110 @@
+use std::collections::{BTreeMap, HashMap};
+use crate::{Be, Bytes, Flag, Le, Padding};
+
+/// A fingerprint of a type's wire layout, so two endpoints can check at connection
+/// time that they agree on it. Field names do not count, only the order and
+/// layout of what is packed. Byte order is chosen per call rather than per type,
+/// so it has to be agreed separately, e.g. through a `WireProfile`.
+///
+/// `#[derive(Schema)]` computes it from the fields of a struct, or the repr and
+/// discriminants of an enum.
+pub trait Schema{
+    const SCHEMA_HASH: u64;
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// FNV-1a of `name`, the starting point of a hash.
+pub const fn schema_hash(name: &str) -> u64{
+    let bytes = name.as_bytes();
+    let mut hash = FNV_OFFSET;
+    let mut index = 0;
+    while index < bytes.len(){
+        hash = (hash ^ bytes[index] as u64).wrapping_mul(FNV_PRIME);
+        index += 1;
+    }
+    hash
+}
+
+/// Fold `part` into `hash`. The order of parts matters.
+pub const fn schema_combine(hash: u64, part: u64) -> u64{
+    let bytes = part.to_le_bytes();
+    let mut hash = hash;
+    let mut index = 0;
+    while index < bytes.len(){
+        hash = (hash ^ bytes[index] as u64).wrapping_mul(FNV_PRIME);
+        index += 1;
+    }
+    hash
+}
+
+macro_rules! impl_schema_primitive {
+    ( $( $t:ty ),* ) => {
+        $(
+            impl Schema for $t{
+                const SCHEMA_HASH: u64 = schema_hash(stringify!($t));
+            }
+        )*
+    };
+}
+
+impl_schema_primitive!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64, bool, char, String);
+
+impl<T: Schema, const N: usize> Schema for [T; N]{
+    const SCHEMA_HASH: u64 = schema_combine(schema_combine(schema_hash("array"), N as u64), T::SCHEMA_HASH);
+}
+
+impl<const N: usize> Schema for Bytes<N>{
+    const SCHEMA_HASH: u64 = <[u8; N]>::SCHEMA_HASH;
+}
+
+impl<const N: usize, const FILL: u8, const STRICT: bool> Schema for Padding<N, FILL, STRICT>{
+    const SCHEMA_HASH: u64 = <[u8; N]>::SCHEMA_HASH;
+}
+
+impl<T: Schema> Schema for Flag<T>{
+    const SCHEMA_HASH: u64 = T::SCHEMA_HASH;
+}
+
+impl<T: Schema> Schema for Le<T>{
+    const SCHEMA_HASH: u64 = schema_combine(schema_hash("le"), T::SCHEMA_HASH);
+}
+
+impl<T: Schema> Schema for Be<T>{
+    const SCHEMA_HASH: u64 = schema_combine(schema_hash("be"), T::SCHEMA_HASH);
+}
+
+impl<T: Schema> Schema for Vec<T>{
+    const SCHEMA_HASH: u64 = schema_combine(schema_hash("vec"), T::SCHEMA_HASH);
+}
+
+impl<T: Schema> Schema for Option<T>{
+    const SCHEMA_HASH: u64 = schema_combine(schema_hash("option"), T::SCHEMA_HASH);
+}
+
+impl<K: Schema, V: Schema> Schema for BTreeMap<K, V>{
+    const SCHEMA_HASH: u64 = schema_combine(schema_combine(schema_hash("map"), K::SCHEMA_HASH), V::SCHEMA_HASH);
+}
+
+impl<K: Schema, V: Schema, S> Schema for HashMap<K, V, S>{
+    const SCHEMA_HASH: u64 = BTreeMap::<K, V>::SCHEMA_HASH;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+    use crate::Bytes;
+    use super::Schema;
+
+    #[test]
+    fn test_schema_hash(){
+        assert_eq!(0xcbf2_9ce4_8422_2325, super::schema_hash(""));
+        assert_eq!(0xaf63_dc4c_8601_ec8c, super::schema_hash("a"));
+        assert_ne!(u16::SCHEMA_HASH, i16::SCHEMA_HASH);
+        assert_ne!(<[u8; 2]>::SCHEMA_HASH, <[u8; 3]>::SCHEMA_HASH);
+        assert_eq!(<[u8; 4]>::SCHEMA_HASH, Bytes::<4>::SCHEMA_HASH);
+        assert_eq!(BTreeMap::<u16, u32>::SCHEMA_HASH, HashMap::<u16, u32>::SCHEMA_HASH);
+        assert_ne!(BTreeMap::<u16, u32>::SCHEMA_HASH, BTreeMap::<u32, u16>::SCHEMA_HASH);
+    }
+}
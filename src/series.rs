@@ -0,0 +1,266 @@
+use core::marker::PhantomData;
+use std::io::{Read, Seek, SeekFrom, Write};
+use crate::{Checksum, Crc32, Direction, Endian, ErrorKind, Packable, PackableError, buffer_length_error};
+
+/// Records that carry their own time, used for block time ranges and seeks.
+pub trait Timestamped{
+    fn timestamp(&self) -> u64;
+}
+
+/// Start of every block: how many records follow, the times of the first and last
+/// of them, and a CRC-32 of their packed bytes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BlockHeader{
+    pub count: u32,
+    pub first: u64,
+    pub last: u64,
+    pub crc: u32,
+}
+
+const HEADER_SIZE: usize = 24;
+
+impl Packable for BlockHeader{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        pack!(litle_endian, self.count, self.first, self.last, self.crc)
+    }
+
+    fn size(&self) -> usize {
+        HEADER_SIZE
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        let mut source = buf;
+        unpack!(litle_endian, &mut source, self.count, self.first, self.last, self.crc)
+    }
+}
+
+fn block_size_error(block_size: usize) -> PackableError{
+    PackableError { 
+        error_kind: ErrorKind::OutOfRange, 
+        data: format!("block size {} leaves no room after the {} byte header", block_size, HEADER_SIZE),
+        direction: None,
+    }
+}
+
+/// Writes records in time order into fixed-size blocks, each a `BlockHeader`
+/// followed by as many whole records as fit, padded with zeros.
+pub struct SeriesWriter<W, T>{
+    writer: W,
+    block_size: usize,
+    litle_endian: bool,
+    header: BlockHeader,
+    records: Vec<u8>,
+    previous: Option<u64>,
+    record: PhantomData<T>,
+}
+
+impl<W: Write, T: Packable + Timestamped> SeriesWriter<W, T>{
+    pub fn new(writer: W, block_size: usize, endian: impl Into<Endian>) -> Result<Self, PackableError>{
+        let litle_endian = endian.into().is_little();
+        if block_size <= HEADER_SIZE{
+            return Err(block_size_error(block_size))
+        }
+        Ok(SeriesWriter { 
+            writer, 
+            block_size, 
+            litle_endian, 
+            header: BlockHeader::default(), 
+            records: Vec::new(), 
+            previous: None, 
+            record: PhantomData,
+        })
+    }
+
+    /// Append `record`, writing out the current block first if it does not fit.
+    /// Fails with `InvalidValue` if `record` is older than the one before it and
+    /// with `OutOfRange` if it cannot fit in an empty block.
+    pub fn push(&mut self, record: &T) -> Result<(), PackableError>{
+        let time = record.timestamp();
+        if self.previous.is_some_and(|previous| time < previous){
+            return Err(PackableError { 
+                error_kind: ErrorKind::InvalidValue, 
+                data: format!("record at {} is older than the previous one", time),
+                direction: Some(Direction::Pack),
+            })
+        }
+        let data = record.pack(self.litle_endian);
+        if data.len() > self.block_size - HEADER_SIZE{
+            return Err(PackableError { 
+                error_kind: ErrorKind::OutOfRange, 
+                data: format!("record of {} bytes does not fit a {} byte block", data.len(), self.block_size),
+                direction: Some(Direction::Pack),
+            })
+        }
+        if HEADER_SIZE + self.records.len() + data.len() > self.block_size{
+            self.flush()?;
+        }
+        if self.header.count == 0{
+            self.header.first = time;
+        }
+        self.header.last = time;
+        self.header.count += 1;
+        self.records.extend(data);
+        self.previous = Some(time);
+        Ok(())
+    }
+
+    /// Write out the current block, even if it is not full.
+    pub fn flush(&mut self) -> Result<(), PackableError>{
+        if self.header.count == 0{
+            return Ok(())
+        }
+        self.header.crc = Crc32::checksum(&self.records);
+        let mut block = self.header.pack(self.litle_endian);
+        block.append(&mut self.records);
+        block.resize(self.block_size, 0);
+        self.writer.write_all(&block).map_err(|error| PackableError::from(error).during(Direction::Pack))?;
+        self.header = BlockHeader::default();
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<W, PackableError>{
+        self.flush()?;
+        self.writer.flush().map_err(|error| PackableError::from(error).during(Direction::Pack))?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads a file written by `SeriesWriter`, keeping every block header in memory
+/// as an index for time-based seeks.
+pub struct SeriesReader<R, T>{
+    reader: R,
+    block_size: usize,
+    litle_endian: bool,
+    blocks: Vec<BlockHeader>,
+    record: PhantomData<T>,
+}
+
+impl<R: Read + Seek, T: Packable + Default + Timestamped> SeriesReader<R, T>{
+    pub fn open(mut reader: R, block_size: usize, endian: impl Into<Endian>) -> Result<Self, PackableError>{
+        let litle_endian = endian.into().is_little();
+        if block_size <= HEADER_SIZE{
+            return Err(block_size_error(block_size))
+        }
+        let len = reader.seek(SeekFrom::End(0)).map_err(|error| PackableError::from(error).during(Direction::Unpack))? as usize;
+        if !len.is_multiple_of(block_size){
+            return Err(buffer_length_error(len.next_multiple_of(block_size), len))
+        }
+        let mut blocks = Vec::with_capacity(len / block_size);
+        let mut header = vec![0; HEADER_SIZE];
+        for block in 0..len / block_size{
+            reader.seek(SeekFrom::Start((block * block_size) as u64))
+                .and_then(|_| reader.read_exact(&mut header))
+                .map_err(|error| PackableError::from(error).during(Direction::Unpack))?;
+            blocks.push(crate::from_bytes(&header, litle_endian)?);
+        }
+        Ok(SeriesReader { reader, block_size, litle_endian, blocks, record: PhantomData })
+    }
+
+    pub fn blocks(&self) -> &[BlockHeader]{
+        &self.blocks
+    }
+
+    /// The first block that may hold records at or after `time`; `blocks().len()`
+    /// if every record is older.
+    pub fn seek(&self, time: u64) -> usize{
+        self.blocks.partition_point(|header| header.last < time)
+    }
+
+    /// Every record of `block`, checked against the header CRC.
+    pub fn read_block(&mut self, block: usize) -> Result<Vec<T>, PackableError>{
+        let header = *self.blocks.get(block).ok_or_else(|| PackableError { 
+            error_kind: ErrorKind::OutOfRange, 
+            data: format!("block {} of {}", block, self.blocks.len()),
+            direction: Some(Direction::Unpack),
+        })?;
+        let mut data = vec![0; self.block_size];
+        self.reader.seek(SeekFrom::Start((block * self.block_size) as u64))
+            .and_then(|_| self.reader.read_exact(&mut data))
+            .map_err(|error| PackableError::from(error).during(Direction::Unpack))?;
+        let mut source = &data[HEADER_SIZE..];
+        let mut records = Vec::with_capacity(header.count as usize);
+        for _ in 0..header.count{
+            let mut record = T::default();
+            unpack!(self.litle_endian, &mut source, record)?;
+            records.push(record);
+        }
+        let end = data.len() - source.len();
+        let crc = Crc32::checksum(&data[HEADER_SIZE..end]);
+        if crc != header.crc{
+            return Err(PackableError { 
+                error_kind: ErrorKind::ChecksumMismatch, 
+                data: format!("block {}: expected crc {:#010x}, got {:#010x}", block, header.crc, crc),
+                direction: Some(Direction::Unpack),
+            })
+        }
+        Ok(records)
+    }
+
+    /// Records timed from `start` to `end` inclusive, reading only the blocks that
+    /// overlap that range.
+    pub fn range(&mut self, start: u64, end: u64) -> Result<Vec<T>, PackableError>{
+        let mut records = Vec::new();
+        for block in self.seek(start)..self.blocks.len(){
+            if self.blocks[block].first > end{
+                break
+            }
+            records.extend(self.read_block(block)?.into_iter().filter(|record| (start..=end).contains(&record.timestamp())));
+        }
+        Ok(records)
+    }
+
+    pub fn into_inner(self) -> R{
+        self.reader
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use crate::ErrorKind;
+    use super::{SeriesReader, SeriesWriter, Timestamped};
+
+    impl Timestamped for (u64, u16){
+        fn timestamp(&self) -> u64{
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_series(){
+        let mut writer = SeriesWriter::new(Cursor::new(Vec::new()), 24 + 3 * 10, false).unwrap();
+        for time in 1..=7u64{
+            writer.push(&(time * 10, time as u16)).unwrap();
+        }
+        let error = writer.push(&(65, 0)).unwrap_err();
+        assert_eq!(ErrorKind::InvalidValue, error.error_kind);
+        let mut file = writer.finish().unwrap().into_inner();
+        assert_eq!(3 * 54, file.len());
+
+        let mut reader = SeriesReader::<_, (u64, u16)>::open(Cursor::new(file.clone()), 54, false).unwrap();
+        assert_eq!(vec![3, 3, 1], reader.blocks().iter().map(|header| header.count).collect::<Vec<_>>());
+        assert_eq!((40, 60), (reader.blocks()[1].first, reader.blocks()[1].last));
+        assert_eq!(1, reader.seek(45));
+        assert_eq!(3, reader.seek(71));
+        assert_eq!(vec![(30, 3), (40, 4), (50, 5)], reader.range(25, 55).unwrap());
+        assert_eq!(vec![(70, 7)], reader.read_block(2).unwrap());
+
+        file[54 + 24] ^= 1;
+        let mut reader = SeriesReader::<_, (u64, u16)>::open(Cursor::new(file), 54, false).unwrap();
+        assert_eq!(ErrorKind::ChecksumMismatch, reader.read_block(1).unwrap_err().error_kind);
+    }
+
+    #[test]
+    fn test_series_sizes(){
+        assert!(SeriesWriter::<_, (u64, u16)>::new(Vec::new(), 24, false).is_err());
+        let mut writer = SeriesWriter::new(Vec::new(), 32, false).unwrap();
+        assert_eq!(ErrorKind::OutOfRange, writer.push(&(1u64, 2u16)).unwrap_err().error_kind);
+        let error = SeriesReader::<_, (u64, u16)>::open(Cursor::new(vec![0; 40]), 32, false).err().unwrap();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+    }
+}
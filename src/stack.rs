@@ -0,0 +1,130 @@
+use crate::{Packable, PackableError, Direction, ErrorKind, UnpackSource, buffer_length_error, string::utf8};
+
+fn capacity_error(len: usize, capacity: usize) -> PackableError{
+    PackableError { 
+        error_kind: ErrorKind::LimitExceeded, 
+        data: format!("{} does not fit a capacity of {}", len, capacity),
+        direction: Some(Direction::Unpack),
+    }
+}
+
+/// Pack into a fixed-capacity `heapless::Vec`, failing with `BufferLengthError` if
+/// the value needs more than `N` bytes. Backs `PackableExt::pack_to_heapless`.
+pub(crate) fn pack_to_heapless<T: Packable + ?Sized, const N: usize>(value: &T, litle_endian: bool) -> Result<heapless::Vec<u8, N>, PackableError>{
+    let mut out = heapless::Vec::new();
+    out.extend_from_slice(&value.pack(litle_endian)).map_err(|_| PackableError { 
+        direction: Some(Direction::Pack),
+        ..buffer_length_error(value.size(), N)
+    })?;
+    Ok(out)
+}
+
+/// Packs like `Vec<T>`, behind a `u32` count. More than `N` elements fail to unpack
+/// with `LimitExceeded`, before any of them is read.
+impl<T: Packable + Default, const N: usize> Packable for heapless::Vec<T, N>{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.size());
+        self.pack_into(&mut data, litle_endian);
+        data
+    }
+
+    fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+        (self.len() as u32).pack_into(out, litle_endian);
+        for item in self{
+            item.pack_into(out, litle_endian);
+        }
+    }
+
+    fn size(&self) -> usize {
+        0u32.size() + self.iter().map(Packable::size).sum::<usize>()
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        let mut source = buf;
+        let mut count = 0u32;
+        source.unpack_into(&mut count, litle_endian)?;
+        let count = count as usize;
+        if count > N{
+            return Err(capacity_error(count, N))
+        }
+        self.truncate(count);
+        for index in 0..count{
+            if index == self.len(){
+                let _ = self.push(T::default());
+            }
+            source.unpack_into(&mut self[index], litle_endian)?;
+        }
+        Ok(buf.len() - source.len())
+    }
+}
+
+/// Packs like `String`, behind a `u32` byte length. More than `N` bytes fail to
+/// unpack with `LimitExceeded`.
+impl<const N: usize> Packable for heapless::String<N>{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.size());
+        self.pack_into(&mut data, litle_endian);
+        data
+    }
+
+    fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+        (self.len() as u32).pack_into(out, litle_endian);
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn size(&self) -> usize {
+        0u32.size() + self.len()
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        let mut source = buf;
+        let mut len = 0u32;
+        source.unpack_into(&mut len, litle_endian)?;
+        let len = len as usize;
+        if len > N{
+            return Err(capacity_error(len, N))
+        }
+        let bytes = source.get(..len).ok_or_else(|| buffer_length_error(len, source.len()))?;
+        self.clear();
+        let _ = self.push_str(utf8(bytes)?);
+        Ok(4 + len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, PackableExt, to_bytes, from_bytes};
+
+    #[test]
+    fn test_pack_to_heapless(){
+        let frame = (0x0102u16, 3u8).pack_to_heapless::<4>(false).unwrap();
+        assert_eq!(&[1, 2, 3], frame.as_slice());
+        let error = 0u64.pack_to_heapless::<4>(false).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+    }
+
+    #[test]
+    fn test_heapless_types(){
+        let samples = heapless::Vec::<u16, 4>::from_slice(&[1, 2]).unwrap();
+        assert_eq!(to_bytes(&vec![1u16, 2], false), to_bytes(&samples, false));
+        assert_eq!(samples, from_bytes::<heapless::Vec<u16, 4>>(&to_bytes(&samples, true), true).unwrap());
+        let error = from_bytes::<heapless::Vec<u8, 1>>(&[0, 0, 0, 2, 1, 2], false).unwrap_err();
+        assert_eq!(ErrorKind::LimitExceeded, error.error_kind);
+
+        let name: heapless::String<8> = heapless::String::try_from("node").unwrap();
+        assert_eq!(to_bytes(&"node".to_string(), false), to_bytes(&name, false));
+        assert_eq!(name, from_bytes::<heapless::String<8>>(&to_bytes(&name, false), false).unwrap());
+        let error = from_bytes::<heapless::String<2>>(&to_bytes(&name, false), false).unwrap_err();
+        assert_eq!(ErrorKind::LimitExceeded, error.error_kind);
+    }
+}
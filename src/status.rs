@@ -0,0 +1,107 @@
+use crate::{Packable, PackableError, Direction, ErrorKind, UnpackSource};
+
+/// A status byte followed by the success payload when it is `OK`, or by the error
+/// payload when it is `ERR`. Any other status fails to unpack with
+/// `InvalidDiscriminant`; the codes default to 0 and 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusPayload<T, E, const OK: u8 = 0, const ERR: u8 = 1>(pub Result<T, E>);
+
+impl<T, E, const OK: u8, const ERR: u8> StatusPayload<T, E, OK, ERR>{
+    /// The status byte this value packs with.
+    pub fn status(&self) -> u8{
+        if self.0.is_ok() { OK } else { ERR }
+    }
+
+    pub fn into_result(self) -> Result<T, E>{
+        self.0
+    }
+}
+
+impl<T: Default, E, const OK: u8, const ERR: u8> Default for StatusPayload<T, E, OK, ERR>{
+    fn default() -> Self {
+        StatusPayload(Ok(T::default()))
+    }
+}
+
+impl<T, E, const OK: u8, const ERR: u8> From<Result<T, E>> for StatusPayload<T, E, OK, ERR>{
+    fn from(result: Result<T, E>) -> Self {
+        StatusPayload(result)
+    }
+}
+
+impl<T: Packable + Default, E: Packable + Default, const OK: u8, const ERR: u8> Packable for StatusPayload<T, E, OK, ERR>{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.size());
+        self.pack_into(&mut data, litle_endian);
+        data
+    }
+
+    fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+        self.status().pack_into(out, litle_endian);
+        match &self.0{
+            Ok(value) => value.pack_into(out, litle_endian),
+            Err(error) => error.pack_into(out, litle_endian),
+        }
+    }
+
+    fn size(&self) -> usize {
+        1 + match &self.0{
+            Ok(value) => value.size(),
+            Err(error) => error.size(),
+        }
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        let mut source = buf;
+        let mut status = 0u8;
+        source.unpack_into(&mut status, litle_endian)?;
+        match status{
+            _ if status == OK => {
+                let mut value = T::default();
+                source.unpack_into(&mut value, litle_endian)?;
+                self.0 = Ok(value);
+            }
+            _ if status == ERR => {
+                let mut error = E::default();
+                source.unpack_into(&mut error, litle_endian)?;
+                self.0 = Err(error);
+            }
+            _ => return Err(PackableError { 
+                error_kind: ErrorKind::InvalidDiscriminant, 
+                data: format!("{:#04x} is neither the ok ({:#04x}) nor the error ({:#04x}) status", status, OK, ERR),
+                direction: Some(Direction::Unpack),
+            }),
+        }
+        Ok(buf.len() - source.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, Packable, from_bytes};
+    use super::StatusPayload;
+
+    #[test]
+    fn test_status_payload(){
+        let reading: StatusPayload<u16, u8> = Ok(0x0102).into();
+        assert_eq!(vec![0, 1, 2], reading.pack(false));
+        assert_eq!(3, reading.size());
+        assert_eq!(reading, from_bytes(&[0, 1, 2], false).unwrap());
+        let mut failed = reading;
+        assert_eq!(2, failed.unpack_from(&[1, 7], false).unwrap());
+        assert_eq!(Err(7), failed.into_result());
+
+        type Ack = StatusPayload<u8, u16, 0x06, 0x15>;
+        let nak: Ack = Err(0x0304).into();
+        assert_eq!(0x15, nak.status());
+        assert_eq!(vec![0x15, 4, 3], nak.pack(true));
+        assert_eq!(nak, from_bytes(&[0x15, 4, 3], true).unwrap());
+        let error = from_bytes::<Ack>(&[0, 1], false).unwrap_err();
+        assert_eq!(ErrorKind::InvalidDiscriminant, error.error_kind);
+    }
+}
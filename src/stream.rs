@@ -0,0 +1,100 @@
+use std::io::{self, Write, Seek, SeekFrom};
+use crate::{Endian, Packable};
+
+/// Pack every item of `iter` straight into `writer`, returning how many were written.
+pub fn pack_iter<I, W>(iter: I, writer: &mut W, endian: impl Into<Endian>) -> io::Result<usize>
+where
+    I: IntoIterator,
+    I::Item: Packable,
+    W: Write,
+{
+    let litle_endian = endian.into().is_little();
+    let mut count = 0;
+    for item in iter{
+        writer.write_all(&item.pack(litle_endian))?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Like `pack_iter`, preceded by a `u32` item count. The count is written as a
+/// placeholder first and patched once the iterator is exhausted.
+pub fn pack_iter_counted<I, W>(iter: I, writer: &mut W, endian: impl Into<Endian>) -> io::Result<u32>
+where
+    I: IntoIterator,
+    I::Item: Packable,
+    W: Write + Seek,
+{
+    let litle_endian = endian.into().is_little();
+    let start = writer.stream_position()?;
+    writer.write_all(&0u32.pack(litle_endian))?;
+    let count = pack_iter(iter, writer, litle_endian)?;
+    let count = u32::try_from(count).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "more than u32::MAX items"))?;
+    let end = writer.stream_position()?;
+    writer.seek(SeekFrom::Start(start))?;
+    writer.write_all(&count.pack(litle_endian))?;
+    writer.seek(SeekFrom::Start(end))?;
+    Ok(count)
+}
+
+/// Writer that feeds every byte it passes on to `hasher`, so a digest of packed
+/// output is computed while it is written, e.g. `|chunk| digest.update(chunk)`.
+/// Seeking is not supported: patched bytes would not reach the hasher.
+pub struct HashWriter<W, H>{
+    inner: W,
+    hasher: H,
+}
+
+impl<W: Write, H: FnMut(&[u8])> HashWriter<W, H>{
+    pub fn new(inner: W, hasher: H) -> Self{
+        HashWriter { inner, hasher }
+    }
+
+    pub fn get_ref(&self) -> &W{
+        &self.inner
+    }
+
+    pub fn into_parts(self) -> (W, H){
+        (self.inner, self.hasher)
+    }
+}
+
+impl<W: Write, H: FnMut(&[u8])> Write for HashWriter<W, H>{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let count = self.inner.write(buf)?;
+        (self.hasher)(&buf[..count]);
+        Ok(count)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use super::{HashWriter, pack_iter, pack_iter_counted};
+
+    #[test]
+    fn test_pack_iter(){
+        let mut out = Vec::new();
+        assert_eq!(3, pack_iter((1..4u16).map(|v| v * 2), &mut out, false).unwrap());
+        assert_eq!(vec![0, 2, 0, 4, 0, 6], out);
+
+        let mut out = Cursor::new(vec![9]);
+        out.set_position(1);
+        assert_eq!(2, pack_iter_counted([1u8, 2], &mut out, true).unwrap());
+        assert_eq!(vec![9, 2, 0, 0, 0, 1, 2], out.into_inner());
+    }
+
+    #[test]
+    fn test_hash_writer(){
+        let mut sum = 0u32;
+        let mut writer = HashWriter::new(Vec::new(), |chunk: &[u8]| sum += chunk.iter().map(|&b| b as u32).sum::<u32>());
+        pack_iter([0x0102u16, 0x0304], &mut writer, false).unwrap();
+        let (out, _) = writer.into_parts();
+        assert_eq!(vec![1, 2, 3, 4], out);
+        assert_eq!(10, sum);
+    }
+}
@@ -0,0 +1,338 @@
+use core::{fmt, marker::PhantomData, mem, str};
+use std::ffi::CString;
+use crate::{Packable, PackableError, Direction, ErrorKind, UnpackSource, buffer_length_error};
+
+/// UTF-8 bytes preceded by their count as a `u32`; see `PrefixedString` for other
+/// widths. Invalid UTF-8 fails to unpack with `InvalidUtf8`.
+impl Packable for String{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.size());
+        pack_prefixed::<u32>(self, &mut data, litle_endian);
+        data
+    }
+
+    fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+        pack_prefixed::<u32>(self, out, litle_endian)
+    }
+
+    fn size(&self) -> usize {
+        0u32.size() + self.len()
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        unpack_prefixed::<u32>(self, data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        unpack_prefixed::<u32>(self, buf, litle_endian)
+    }
+}
+
+/// A `String` whose byte count is packed as an `L`.
+///
+/// # Panics
+///
+/// `pack` panics if the string is too long for `L`.
+pub struct PrefixedString<L>{
+    pub value: String,
+    len: PhantomData<L>,
+}
+
+impl<L> PrefixedString<L>{
+    pub fn new(value: String) -> Self{
+        PrefixedString { value, len: PhantomData }
+    }
+}
+
+impl<L> Default for PrefixedString<L>{
+    fn default() -> Self {
+        PrefixedString::new(String::new())
+    }
+}
+
+impl<L> Clone for PrefixedString<L>{
+    fn clone(&self) -> Self {
+        PrefixedString::new(self.value.clone())
+    }
+}
+
+impl<L> PartialEq for PrefixedString<L>{
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<L> fmt::Debug for PrefixedString<L>{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<L> Packable for PrefixedString<L>
+where
+    L: Packable + Default + TryFrom<usize> + TryInto<usize>,
+{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.size());
+        pack_prefixed::<L>(&self.value, &mut data, litle_endian);
+        data
+    }
+
+    fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+        pack_prefixed::<L>(&self.value, out, litle_endian)
+    }
+
+    fn size(&self) -> usize {
+        L::default().size() + self.value.len()
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        unpack_prefixed::<L>(&mut self.value, data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        unpack_prefixed::<L>(&mut self.value, buf, litle_endian)
+    }
+}
+
+/// The bytes followed by a NUL terminator. Unpacking scans for the terminator and
+/// fails with `BufferLengthError` if the input ends first.
+impl Packable for CString{
+    fn pack(&self, _litle_endian: bool) -> Vec<u8> {
+        self.as_bytes_with_nul().to_vec()
+    }
+
+    fn size(&self) -> usize {
+        self.as_bytes_with_nul().len()
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], _litle_endian: bool) -> Result<usize, PackableError> {
+        let end = buf.iter().position(|byte| *byte == 0).ok_or_else(|| PackableError { 
+            error_kind: ErrorKind::BufferLengthError, 
+            data: format!("no NUL terminator in {} bytes", buf.len()),
+            direction: Some(Direction::Unpack),
+        })?;
+        let mut bytes = mem::take(self).into_bytes_with_nul();
+        bytes.clear();
+        bytes.extend_from_slice(&buf[..=end]);
+        *self = CString::from_vec_with_nul(bytes).map_err(|error| PackableError { 
+            error_kind: ErrorKind::InvalidEncoding, 
+            data: format!("{}", error),
+            direction: Some(Direction::Unpack),
+        })?;
+        Ok(end + 1)
+    }
+}
+
+/// A string stored in exactly `N` bytes, padded at the end with `PAD`, as in file
+/// headers and fixed-layout records. Trailing `PAD` bytes are trimmed on unpack.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct FixedString<const N: usize, const PAD: u8 = 0>{
+    value: String,
+}
+
+impl<const N: usize, const PAD: u8> FixedString<N, PAD>{
+    /// Fails with `OutOfRange` if `value` is longer than `N` bytes.
+    pub fn new(value: &str) -> Result<Self, PackableError>{
+        if value.len() > N{
+            return Err(PackableError { 
+                error_kind: ErrorKind::OutOfRange, 
+                data: format!("{:?} is longer than {} bytes", value, N),
+                direction: None,
+            })
+        }
+        Ok(FixedString { value: value.to_string() })
+    }
+
+    pub fn as_str(&self) -> &str{
+        &self.value
+    }
+}
+
+impl<const N: usize, const PAD: u8> Packable for FixedString<N, PAD>{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        let mut data = Vec::with_capacity(N);
+        self.pack_into(&mut data, litle_endian);
+        data
+    }
+
+    fn pack_into(&self, out: &mut Vec<u8>, _litle_endian: bool) {
+        out.extend_from_slice(self.value.as_bytes());
+        out.resize(out.len() + N - self.value.len(), PAD);
+    }
+
+    fn size(&self) -> usize {
+        N
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], _litle_endian: bool) -> Result<usize, PackableError> {
+        let bytes = buf.get(..N).ok_or_else(|| buffer_length_error(N, buf.len()))?;
+        let len = bytes.iter().rposition(|byte| *byte != PAD).map_or(0, |last| last + 1);
+        self.value.clear();
+        self.value.push_str(utf8(&bytes[..len])?);
+        Ok(N)
+    }
+}
+
+/// A string stored as UTF-16 code units in the requested byte order, preceded by
+/// the number of units as a `u16`. Unpaired surrogates fail to unpack with
+/// `InvalidEncoding`.
+///
+/// # Panics
+///
+/// `pack` panics if the string needs more than `u16::MAX` units.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Utf16String{
+    pub value: String,
+}
+
+impl Utf16String{
+    pub fn new(value: String) -> Self{
+        Utf16String { value }
+    }
+}
+
+impl Packable for Utf16String{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        let units: Vec<u16> = self.value.encode_utf16().collect();
+        let Ok(len) = u16::try_from(units.len()) else {
+            panic!("string of {} UTF-16 units does not fit a u16 length", units.len())
+        };
+        let mut data = Vec::with_capacity(2 + 2 * units.len());
+        data.extend(len.pack(litle_endian));
+        for unit in units{
+            data.extend(unit.pack(litle_endian));
+        }
+        data
+    }
+
+    fn size(&self) -> usize {
+        2 + 2 * self.value.encode_utf16().count()
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        let mut source = buf;
+        let mut len = 0u16;
+        source.unpack_into(&mut len, litle_endian)?;
+        let bytes = source.get(..2 * len as usize).ok_or_else(|| buffer_length_error(2 * len as usize, source.len()))?;
+        let units = bytes.chunks_exact(2).map(|unit| match litle_endian{
+            true => u16::from_le_bytes([unit[0], unit[1]]),
+            false => u16::from_be_bytes([unit[0], unit[1]]),
+        });
+        self.value.clear();
+        for char in char::decode_utf16(units){
+            self.value.push(char.map_err(|error| PackableError { 
+                error_kind: ErrorKind::InvalidEncoding, 
+                data: format!("{}", error),
+                direction: Some(Direction::Unpack),
+            })?);
+        }
+        Ok(2 + bytes.len())
+    }
+}
+
+fn pack_prefixed<L: Packable + TryFrom<usize>>(value: &str, out: &mut Vec<u8>, litle_endian: bool){
+    let Ok(len) = L::try_from(value.len()) else {
+        panic!("string of {} bytes does not fit a {} length", value.len(), core::any::type_name::<L>())
+    };
+    len.pack_into(out, litle_endian);
+    out.extend_from_slice(value.as_bytes());
+}
+
+fn unpack_prefixed<L>(value: &mut String, data: &[u8], litle_endian: bool) -> Result<usize, PackableError>
+where
+    L: Packable + Default + TryInto<usize>,
+{
+    let mut source = data;
+    let mut len = L::default();
+    source.unpack_into(&mut len, litle_endian)?;
+    let len: usize = len.try_into().map_err(|_| PackableError { 
+        error_kind: ErrorKind::OutOfRange, 
+        data: "string length does not fit in usize".to_string(),
+        direction: Some(Direction::Unpack),
+    })?;
+    let bytes = source.get(..len).ok_or_else(|| buffer_length_error(len, source.len()))?;
+    value.clear();
+    value.push_str(utf8(bytes)?);
+    Ok(data.len() - source.len() + len)
+}
+
+pub(crate) fn utf8(bytes: &[u8]) -> Result<&str, PackableError>{
+    str::from_utf8(bytes).map_err(|error| PackableError { 
+        error_kind: ErrorKind::InvalidUtf8, 
+        data: format!("{}", error),
+        direction: Some(Direction::Unpack),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+    use crate::{ErrorKind, Packable, to_bytes, from_bytes};
+    use super::{FixedString, PrefixedString, Utf16String};
+
+    #[test]
+    fn test_string(){
+        assert_eq!(vec![0, 0, 0, 2, b'h', b'i'], to_bytes(&"hi".to_string(), false));
+        assert_eq!("hi", from_bytes::<String>(&[2, 0, 0, 0, b'h', b'i', 0], true).unwrap());
+        let error = from_bytes::<String>(&[0, 0, 0, 1, 0xff], false).unwrap_err();
+        assert_eq!(ErrorKind::InvalidUtf8, error.error_kind);
+        let error = from_bytes::<String>(&[0, 0, 0, 3, b'h'], false).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+
+        let name = PrefixedString::<u8>::new("caf\u{e9}".to_string());
+        assert_eq!(6, name.size());
+        assert_eq!(name, from_bytes(&to_bytes(&name, false), false).unwrap());
+    }
+
+    #[test]
+    fn test_c_string(){
+        let name = CString::new("eth0").unwrap();
+        assert_eq!(b"eth0\0".to_vec(), to_bytes(&name, false));
+        let (mut name, mut after) = (CString::default(), 0u8);
+        assert_eq!(4, unpack!(false, &mut vec![b'l', b'o', 0, 7, 9], name, after).unwrap());
+        assert_eq!((c"lo".to_owned(), 7), (name.clone(), after));
+        let error = unpack!(false, &mut vec![b'l', b'o'], name).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+    }
+
+    #[test]
+    fn test_fixed_string(){
+        let name = FixedString::<6>::new("abc").unwrap();
+        assert_eq!(b"abc\0\0\0".to_vec(), to_bytes(&name, false));
+        assert_eq!("ab", from_bytes::<FixedString<4, b' '>>(b"ab  ", false).unwrap().as_str());
+        assert_eq!("", from_bytes::<FixedString<2>>(&[0, 0], false).unwrap().as_str());
+        assert_eq!(ErrorKind::OutOfRange, FixedString::<2>::new("abc").unwrap_err().error_kind);
+        assert_eq!(ErrorKind::InvalidUtf8, from_bytes::<FixedString<2>>(&[0xff, 0], false).unwrap_err().error_kind);
+    }
+
+    #[test]
+    fn test_utf16_string(){
+        let name = Utf16String::new("a\u{1f600}".to_string());
+        assert_eq!(8, name.size());
+        assert_eq!(vec![3, 0, b'a', 0, 0x3d, 0xd8, 0x00, 0xde], to_bytes(&name, true));
+        assert_eq!(vec![0, 3, 0, b'a', 0xd8, 0x3d, 0xde, 0x00], to_bytes(&name, false));
+        assert_eq!(name, from_bytes(&to_bytes(&name, false), false).unwrap());
+        let error = from_bytes::<Utf16String>(&[1, 0, 0x00, 0xd8], true).unwrap_err();
+        assert_eq!(ErrorKind::InvalidEncoding, error.error_kind);
+        let error = from_bytes::<Utf16String>(&[2, 0, b'a', 0], true).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+    }
+}
@@ -0,0 +1,119 @@
+use std::io::{self, Write};
+use crate::{Direction, ErrorKind, Packable, PackableError, UnpackSource, write_chunked};
+
+/// Elements repeated until the input runs out, for formats with neither a count nor
+/// a terminator. Must be the last thing unpacked from its source; a trailing partial
+/// element is an error, and so is an element type that reads no bytes. Reuses the vector's capacity across unpacks.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct UntilEnd<T>(pub Vec<T>);
+
+impl<T: Packable + Default> Packable for UntilEnd<T>{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.size());
+        self.pack_into(&mut data, litle_endian);
+        data
+    }
+
+    fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+        for item in &self.0{
+            item.pack_into(out, litle_endian);
+        }
+    }
+
+    fn pack_to_writer<W: Write + ?Sized>(&self, writer: &mut W, litle_endian: bool) -> io::Result<()> {
+        write_chunked(writer, &[], &self.0, |item, chunk| item.pack_into(chunk, litle_endian))
+    }
+
+    fn size(&self) -> usize {
+        self.0.iter().map(Packable::size).sum()
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        self.0.clear();
+        let mut source = buf;
+        while !source.is_empty(){
+            let mut item = T::default();
+            if source.unpack_into(&mut item, litle_endian)? == 0{
+                return Err(PackableError { 
+                    error_kind: ErrorKind::InvalidEncoding, 
+                    data: format!("{} reads no bytes, so cannot repeat until the end", core::any::type_name::<T>()),
+                    direction: Some(Direction::Unpack),
+                })
+            }
+            self.0.push(item);
+        }
+        Ok(buf.len())
+    }
+}
+
+/// Every byte left in the input, packed verbatim: the payload after a fixed header.
+/// Like `UntilEnd`, it must be the last thing unpacked from its source.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Remaining(pub Vec<u8>);
+
+impl Packable for Remaining{
+    fn pack(&self, _litle_endian: bool) -> Vec<u8> {
+        self.0.clone()
+    }
+
+    fn pack_into(&self, out: &mut Vec<u8>, _litle_endian: bool) {
+        out.extend_from_slice(&self.0)
+    }
+
+    fn pack_to_writer<W: Write + ?Sized>(&self, writer: &mut W, _litle_endian: bool) -> io::Result<()> {
+        writer.write_all(&self.0)
+    }
+
+    fn size(&self) -> usize {
+        self.0.len()
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], _litle_endian: bool) -> Result<usize, PackableError> {
+        self.0.clear();
+        self.0.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, to_bytes};
+    use super::{Remaining, UntilEnd};
+
+    #[test]
+    fn test_until_end(){
+        let (mut count, mut items) = (0u8, UntilEnd::<u16>::default());
+        assert_eq!(5, unpack!(false, &mut vec![2, 0, 1, 0, 2], count, items).unwrap());
+        assert_eq!((2, vec![1, 2]), (count, items.0.clone()));
+        assert_eq!(vec![1, 0, 2, 0], to_bytes(&items, true));
+
+        let error = unpack!(false, &mut vec![2, 0, 1, 0], count, items).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+    }
+
+    #[test]
+    fn test_remaining(){
+        let (mut kind, mut payload) = (0u16, Remaining::default());
+        assert_eq!(5, unpack!(false, &mut &[0, 7, 1, 2, 3][..], kind, payload).unwrap());
+        assert_eq!((7, vec![1, 2, 3]), (kind, payload.0.clone()));
+        assert_eq!(vec![0, 7, 1, 2, 3], pack!(false, kind, payload));
+        assert_eq!(2, unpack!(false, &mut vec![0, 8], kind, payload).unwrap());
+        assert!(payload.0.is_empty());
+    }
+
+    #[test]
+    fn test_until_end_empty_element(){
+        let error = crate::from_bytes::<UntilEnd<[u8; 0]>>(&[1], false).unwrap_err();
+        assert_eq!(ErrorKind::InvalidEncoding, error.error_kind);
+    }
+}
@@ -0,0 +1,197 @@
+use core::{fmt, marker::PhantomData};
+use std::io::{self, Write};
+use crate::{Packable, PackableError, Direction, ErrorKind, UnpackSource, write_chunked};
+
+/// Elements preceded by their count as an `L`. `Vec<T>` itself packs the same
+/// way with a `u32` count.
+///
+/// # Panics
+///
+/// `pack` panics if there are too many elements for `L`.
+pub struct LenPrefixed<L, T>{
+    pub items: Vec<T>,
+    len: PhantomData<L>,
+}
+
+impl<L, T> LenPrefixed<L, T>{
+    pub fn new(items: Vec<T>) -> Self{
+        LenPrefixed { items, len: PhantomData }
+    }
+}
+
+impl<L, T> Default for LenPrefixed<L, T>{
+    fn default() -> Self {
+        LenPrefixed::new(Vec::new())
+    }
+}
+
+impl<L, T: Clone> Clone for LenPrefixed<L, T>{
+    fn clone(&self) -> Self {
+        LenPrefixed::new(self.items.clone())
+    }
+}
+
+impl<L, T: PartialEq> PartialEq for LenPrefixed<L, T>{
+    fn eq(&self, other: &Self) -> bool {
+        self.items == other.items
+    }
+}
+
+impl<L, T: fmt::Debug> fmt::Debug for LenPrefixed<L, T>{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.items.fmt(f)
+    }
+}
+
+impl<L, T> Packable for LenPrefixed<L, T>
+where
+    L: Packable + Default + Copy + TryFrom<usize> + TryInto<usize>,
+    T: Packable + Default,
+{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.size());
+        pack_counted::<L, T>(&self.items, &mut data, litle_endian);
+        data
+    }
+
+    fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+        pack_counted::<L, T>(&self.items, out, litle_endian)
+    }
+
+    fn pack_to_writer<W: Write + ?Sized>(&self, writer: &mut W, litle_endian: bool) -> io::Result<()> {
+        write_counted::<L, T, W>(&self.items, writer, litle_endian)
+    }
+
+    fn size(&self) -> usize {
+        L::default().size() + self.items.iter().map(Packable::size).sum::<usize>()
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        unpack_counted::<L, T>(&mut self.items, data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        unpack_counted::<L, T>(&mut self.items, buf, litle_endian)
+    }
+}
+
+impl<T: Packable + Default> Packable for Vec<T>{
+    fn pack(&self, litle_endian: bool) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.size());
+        pack_counted::<u32, T>(self, &mut data, litle_endian);
+        data
+    }
+
+    fn pack_into(&self, out: &mut Vec<u8>, litle_endian: bool) {
+        pack_counted::<u32, T>(self, out, litle_endian)
+    }
+
+    fn pack_to_writer<W: Write + ?Sized>(&self, writer: &mut W, litle_endian: bool) -> io::Result<()> {
+        write_counted::<u32, T, W>(self, writer, litle_endian)
+    }
+
+    fn size(&self) -> usize {
+        0u32.size() + self.iter().map(Packable::size).sum::<usize>()
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        unpack_counted::<u32, T>(self, data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], litle_endian: bool) -> Result<usize, PackableError> {
+        unpack_counted::<u32, T>(self, buf, litle_endian)
+    }
+}
+
+fn pack_counted<L, T>(items: &[T], out: &mut Vec<u8>, litle_endian: bool)
+where
+    L: Packable + TryFrom<usize>,
+    T: Packable,
+{
+    let Ok(count) = L::try_from(items.len()) else {
+        panic!("{} elements do not fit a {} count", items.len(), core::any::type_name::<L>())
+    };
+    count.pack_into(out, litle_endian);
+    for item in items{
+        item.pack_into(out, litle_endian);
+    }
+}
+
+fn write_counted<L, T, W>(items: &[T], writer: &mut W, litle_endian: bool) -> io::Result<()>
+where
+    L: Packable + TryFrom<usize>,
+    T: Packable,
+    W: Write + ?Sized,
+{
+    let Ok(count) = L::try_from(items.len()) else {
+        panic!("{} elements do not fit a {} count", items.len(), core::any::type_name::<L>())
+    };
+    write_chunked(writer, &count.pack(litle_endian), items, |item, chunk| item.pack_into(chunk, litle_endian))
+}
+
+/// Elements already in `items` are unpacked into in place, so their own buffers
+/// are reused. The vector only grows as elements are actually read, so a bogus
+/// count cannot allocate ahead of the data. Elements that read no bytes would not
+/// use up the data, so their count is limited to the length of the input.
+fn unpack_counted<L, T>(items: &mut Vec<T>, data: &[u8], litle_endian: bool) -> Result<usize, PackableError>
+where
+    L: Packable + Default + TryInto<usize>,
+    T: Packable + Default,
+{
+    let mut source = data;
+    let mut count = L::default();
+    source.unpack_into(&mut count, litle_endian)?;
+    let count: usize = count.try_into().map_err(|_| PackableError { 
+        error_kind: ErrorKind::OutOfRange, 
+        data: "element count does not fit in usize".to_string(),
+        direction: Some(Direction::Unpack),
+    })?;
+    items.truncate(count);
+    for index in 0..count{
+        if index == items.len(){
+            items.push(T::default());
+        }
+        if source.unpack_into(&mut items[index], litle_endian)? == 0 && count > data.len(){
+            return Err(PackableError { 
+                error_kind: ErrorKind::LimitExceeded, 
+                data: format!("{} elements of no bytes from {} bytes of input", count, data.len()),
+                direction: Some(Direction::Unpack),
+            })
+        }
+    }
+    Ok(data.len() - source.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, Packable, to_bytes, from_bytes};
+    use super::LenPrefixed;
+
+    #[test]
+    fn test_vec(){
+        assert_eq!(vec![0, 0, 0, 2, 0, 1, 0, 2], to_bytes(&vec![1u16, 2], false));
+        assert_eq!(vec![vec![7u8], vec![]], from_bytes::<Vec<Vec<u8>>>(&[0, 0, 0, 2, 0, 0, 0, 1, 7, 0, 0, 0, 0], false).unwrap());
+
+        let mut items = vec![vec![1u8, 2, 3], vec![4]];
+        assert_eq!(10, unpack!(false, &mut vec![0, 0, 0, 1, 0, 0, 0, 2, 5, 6], items).unwrap());
+        assert_eq!(vec![vec![5, 6]], items);
+        assert!(items[0].capacity() >= 3);
+
+        let error = from_bytes::<Vec<u16>>(&[0xff, 0xff, 0xff, 0xff, 0, 1], false).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+
+        assert_eq!(2, from_bytes::<Vec<[u8; 0]>>(&[0, 0, 0, 2], false).unwrap().len());
+        let error = from_bytes::<Vec<crate::Trailing<u8>>>(&[0xff, 0xff, 0xff, 0xff], false).unwrap_err();
+        assert_eq!(ErrorKind::LimitExceeded, error.error_kind);
+    }
+
+    #[test]
+    fn test_len_prefixed(){
+        let items = LenPrefixed::<u8, u16>::new(vec![1, 2]);
+        assert_eq!(vec![2, 1, 0, 2, 0], to_bytes(&items, true));
+        assert_eq!(5, items.size());
+        assert_eq!(items, from_bytes(&[2, 1, 0, 2, 0], true).unwrap());
+    }
+}
@@ -0,0 +1,155 @@
+use core::{fmt, str::FromStr};
+use crate::{ErrorKind, Packable, PackableError, from_bytes};
+
+/// One conformance vector: bytes in, expected decoded value out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestVector<T>{
+    pub name: String,
+    pub litle_endian: bool,
+    pub input: Vec<u8>,
+    /// The decoded value, or the kind of error when decoding must fail.
+    pub expected: Result<T, ErrorKind>,
+}
+
+impl<T: Packable + Default + PartialEq + fmt::Debug> TestVector<T>{
+    /// Decode the input and compare the value with the expected one. It must also
+    /// pack back to the exact input.
+    pub fn check(&self) -> Result<(), String>{
+        let result = from_bytes::<T>(&self.input, self.litle_endian);
+        let failure = match (result, &self.expected){
+            (Ok(value), Ok(expected)) if value != *expected => format!("decoded {:?}, expected {:?}", value, expected),
+            (Ok(value), Ok(_)) if value.pack(self.litle_endian) != self.input => format!("repacking {:?} does not give the input back", value),
+            (Ok(_), Ok(_)) => return Ok(()),
+            (Err(error), Err(kind)) if error.error_kind == *kind => return Ok(()),
+            (Ok(value), Err(kind)) => format!("decoded {:?}, expected error {:?}", value, kind),
+            (Err(error), _) => format!("{}", error),
+        };
+        Err(format!("{}: {}", self.name, failure))
+    }
+}
+
+fn parse_error(line: usize, message: &str) -> PackableError{
+    PackableError { 
+        error_kind: ErrorKind::InvalidEncoding, 
+        data: format!("test vector line {}: {}", line, message),
+        direction: None,
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Vec<u8>>{
+    let digits: Vec<u32> = hex.chars().filter(|c| !c.is_whitespace()).map(|c| c.to_digit(16)).collect::<Option<_>>()?;
+    if !digits.len().is_multiple_of(2){
+        return None
+    }
+    Some(digits.chunks(2).map(|pair| (pair[0] << 4 | pair[1]) as u8).collect())
+}
+
+fn parse_error_kind(name: &str) -> Option<ErrorKind>{
+    Some(match name{
+        "TryFromSliceError" => ErrorKind::TryFromSliceError,
+        "BufferLengthError" => ErrorKind::BufferLengthError,
+        "OutOfRange" => ErrorKind::OutOfRange,
+        "LimitExceeded" => ErrorKind::LimitExceeded,
+        "TrailingBytes" => ErrorKind::TrailingBytes,
+        "InvalidEncoding" => ErrorKind::InvalidEncoding,
+        "InvalidValue" => ErrorKind::InvalidValue,
+        "EndiannessMismatch" => ErrorKind::EndiannessMismatch,
+        "InvalidDiscriminant" => ErrorKind::InvalidDiscriminant,
+        "InvalidChar" => ErrorKind::InvalidChar,
+        "ZeroValue" => ErrorKind::ZeroValue,
+        "ChecksumMismatch" => ErrorKind::ChecksumMismatch,
+        "InvalidUtf8" => ErrorKind::InvalidUtf8,
+        _ => return None,
+    })
+}
+
+/// Parse vectors written one per line as `name,le|be,hex input,expected`. The
+/// expected value runs to the end of the line, may contain commas, and is parsed
+/// with `FromStr`, or names an `ErrorKind` as `error:BufferLengthError` when
+/// decoding must fail. Blank lines and lines starting with `#` are skipped.
+///
+/// For types without `FromStr`, such as derived structs, `vector_tests!` reads the
+/// same format at compile time with the expected values written as Rust.
+pub fn parse_vectors<T: FromStr>(text: &str) -> Result<Vec<TestVector<T>>, PackableError>{
+    let mut vectors = Vec::new();
+    for (index, line) in text.lines().enumerate(){
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#'){
+            continue
+        }
+        let mut fields = line.splitn(4, ',');
+        let (Some(name), Some(endian), Some(hex), Some(expected)) = (fields.next(), fields.next(), fields.next(), fields.next()) else {
+            return Err(parse_error(index + 1, "expected 4 comma separated fields"))
+        };
+        let litle_endian = match endian.trim(){
+            "le" => true,
+            "be" => false,
+            _ => return Err(parse_error(index + 1, "endianness must be le or be")),
+        };
+        let input = parse_hex(hex).ok_or_else(|| parse_error(index + 1, "invalid hex input"))?;
+        let expected = match expected.trim().strip_prefix("error:"){
+            Some(kind) => Err(parse_error_kind(kind.trim()).ok_or_else(|| parse_error(index + 1, "unknown error kind"))?),
+            None => Ok(expected.trim().parse().map_err(|_| parse_error(index + 1, "invalid expected value"))?),
+        };
+        vectors.push(TestVector { name: name.trim().to_string(), litle_endian, input, expected });
+    }
+    Ok(vectors)
+}
+
+/// Run `check` on each vector. Returns one message per failing vector.
+pub fn check_vectors<T: Packable + Default + PartialEq + fmt::Debug>(vectors: &[TestVector<T>]) -> Result<(), Vec<String>>{
+    let failures: Vec<String> = vectors.iter().filter_map(|vector| vector.check().err()).collect();
+    if failures.is_empty() { Ok(()) } else { Err(failures) }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ErrorKind;
+    use super::{TestVector, parse_vectors, check_vectors};
+
+    const VECTORS: &str = "
+        # name, endianness, input, expected
+        small,be,00 7f,127
+        swapped,le,7f 00,127
+        short,be,7f,error:BufferLengthError
+    ";
+
+    #[test]
+    fn test_vectors(){
+        let vectors = parse_vectors::<u16>(VECTORS).unwrap();
+        assert_eq!(3, vectors.len());
+        assert_eq!(vec![0x7f, 0], vectors[1].input);
+        assert_eq!((Ok(127), Err(ErrorKind::BufferLengthError)), (vectors[1].expected, vectors[2].expected));
+        check_vectors(&vectors).unwrap();
+
+        let failures = check_vectors(&parse_vectors::<i8>(VECTORS).unwrap()).unwrap_err();
+        assert_eq!(vec![
+            "small: decoded 0, expected 127",
+            "swapped: repacking 127 does not give the input back",
+            "short: decoded 127, expected error BufferLengthError",
+        ], failures);
+        let vector = TestVector::<u8> { name: "wrong".to_string(), litle_endian: false, input: vec![0x7f], expected: Err(ErrorKind::InvalidValue) };
+        assert_eq!("wrong: decoded 127, expected error InvalidValue", vector.check().unwrap_err());
+
+        assert!(parse_vectors::<u8>("a,be,0").is_err());
+        assert!(parse_vectors::<u8>("a,xx,00,0").is_err());
+        assert!(parse_vectors::<u8>("a,be,0g,0").is_err());
+        assert!(parse_vectors::<u8>("a,be,00,256").is_err());
+        assert!(parse_vectors::<u8>("a,be,00,error:Unknown").is_err());
+    }
+
+    #[cfg(feature = "derive")]
+    #[allow(dead_code)]
+    mod conformance {
+        use crate::Bytes;
+
+        #[derive(Debug, Default, PartialEq, crate::Packable)]
+        struct Route{
+            kind: u8,
+            hops: u16,
+            address: Bytes<2>,
+        }
+
+        crate::vector_tests!(Route, "testdata/route.csv");
+    }
+}
@@ -0,0 +1,93 @@
+use std::{collections::HashMap, fs::File, io::{BufReader, Read}, path::Path};
+use crate::{Codec, ErrorKind, Packable, PackableError};
+
+/// Outcome of scanning a stream of framed records with `verify`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Report{
+    pub records: usize,
+    pub valid: usize,
+    pub bytes: u64,
+    pub errors: HashMap<ErrorKind, usize>,
+    /// Offset of the first frame that failed to decode.
+    pub first_error_offset: Option<u64>,
+    /// The stream ended in a way that made the rest of it unreadable, e.g. a
+    /// truncated frame.
+    pub truncated: bool,
+}
+
+impl Report{
+    pub fn is_ok(&self) -> bool{
+        self.valid == self.records && !self.truncated
+    }
+
+    fn record_error(&mut self, error: &PackableError, offset: u64){
+        *self.errors.entry(error.error_kind).or_default() += 1;
+        self.first_error_offset.get_or_insert(offset);
+    }
+}
+
+/// Decode every record framed by `codec` in `reader`. A record that is framed
+/// correctly but fails to decode is counted and skipped; a broken frame stops the
+/// scan because the following frame boundaries are lost.
+pub fn verify<T: Packable + Default, R: Read>(reader: &mut R, codec: &Codec) -> Report{
+    let mut report = Report::default();
+    let unframed_size = T::default().size();
+    loop{
+        match codec.read_frame(reader, unframed_size){
+            Ok(Some((payload, len))) => {
+                report.records += 1;
+                match codec.decode_payload::<T>(&payload){
+                    Ok(_) => report.valid += 1,
+                    Err(error) => report.record_error(&error, report.bytes),
+                }
+                report.bytes += len as u64;
+            }
+            Ok(None) => break,
+            Err(error) => {
+                report.truncated = true;
+                report.record_error(&error, report.bytes);
+                break
+            }
+        }
+    }
+    report
+}
+
+pub fn verify_file<T: Packable + Default>(path: impl AsRef<Path>, codec: &Codec) -> Result<Report, PackableError>{
+    let mut reader = BufReader::new(File::open(path)?);
+    Ok(verify::<T, _>(&mut reader, codec))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Codec, ErrorKind, Framing, LengthPrefix, Vlq};
+    use super::{verify, verify_file};
+
+    #[test]
+    fn test_verify(){
+        let codec = Codec::new(false).framing(Framing::LengthPrefixed(LengthPrefix::U8)).strict(true);
+        let data = [1, 0x05, 2, 0x81, 0x00, 1, 0x80, 1, 0x7f, 3, 0x81];
+        let report = verify::<Vlq, _>(&mut &data[..], &codec);
+        assert_eq!((4, 3, 9), (report.records, report.valid, report.bytes));
+        assert_eq!(Some(5), report.first_error_offset);
+        assert_eq!(Some(&1), report.errors.get(&ErrorKind::InvalidEncoding));
+        assert_eq!(Some(&1), report.errors.get(&ErrorKind::BufferLengthError));
+        assert!(report.truncated && !report.is_ok());
+
+        let report = verify::<u16, _>(&mut &[0u8, 1, 0, 2][..], &Codec::new(false));
+        assert!(report.is_ok());
+        assert_eq!(2, report.records);
+    }
+
+    #[test]
+    fn test_verify_file(){
+        let path = std::env::temp_dir().join(format!("packable-verify-{}", std::process::id()));
+        std::fs::write(&path, [0u8, 0, 0, 1]).unwrap();
+        let report = verify_file::<u32>(&path, &Codec::new(true)).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!((1, 1), (report.records, report.valid));
+
+        let error = verify_file::<u32>(&path, &Codec::new(true)).unwrap_err();
+        assert_eq!(ErrorKind::Io(std::io::ErrorKind::NotFound), error.error_kind);
+    }
+}
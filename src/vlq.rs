@@ -0,0 +1,153 @@
+use crate::{Packable, PackableError, Direction, ErrorKind, buffer_length_error};
+
+/// Variable-length quantity as used by MIDI: 7 bits per byte, most significant group
+/// first, the high bit set on every byte but the last, and at most `N` bytes. The
+/// digit order is fixed by the format, so the endianness argument is ignored.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Vlq<const N: usize = 4>(u64);
+
+impl<const N: usize> Vlq<N>{
+    pub const MAX: u64 = if 7 * N >= 64 { u64::MAX } else { (1 << (7 * N)) - 1 };
+
+    pub fn new(value: u64) -> Result<Self, PackableError>{
+        if value > Self::MAX{
+            return Err(PackableError { 
+                error_kind: ErrorKind::OutOfRange, 
+                data: format!("{} does not fit in a {} byte quantity", value, N),
+                direction: None,
+            })
+        }
+        Ok(Vlq(value))
+    }
+
+    pub fn get(&self) -> u64{
+        self.0
+    }
+}
+
+impl<const N: usize> Packable for Vlq<N>{
+    fn pack(&self, _litle_endian: bool) -> Vec<u8> {
+        let size = self.size();
+        (0..size).rev().map(|group| {
+            let digit = (self.0 >> (7 * group)) as u8 & 0x7f;
+            if group > 0 { digit | 0x80 } else { digit }
+        }).collect()
+    }
+
+    fn size(&self) -> usize {
+        let bits = 64 - self.0.leading_zeros() as usize;
+        bits.div_ceil(7).max(1)
+    }
+
+    fn unpack(&mut self, data: &mut Vec<u8>, litle_endian: bool) -> Result<(), PackableError> {
+        self.unpack_from(data, litle_endian)?;
+        Ok(())
+    }
+
+    fn unpack_from(&mut self, buf: &[u8], _litle_endian: bool) -> Result<usize, PackableError> {
+        if buf.first() == Some(&0x80){
+            return Err(PackableError { 
+                error_kind: ErrorKind::InvalidEncoding, 
+                data: "quantity is not minimally encoded".to_string(),
+                direction: Some(Direction::Unpack),
+            })
+        }
+        let mut value = 0u64;
+        for index in 0..N{
+            let byte = *buf.get(index).ok_or_else(|| buffer_length_error(index + 1, buf.len()))?;
+            if value > u64::MAX >> 7{
+                return Err(PackableError { 
+                    error_kind: ErrorKind::OutOfRange, 
+                    data: "quantity overflows 64 bits".to_string(),
+                    direction: Some(Direction::Unpack),
+                })
+            }
+            value = value << 7 | (byte & 0x7f) as u64;
+            if byte & 0x80 == 0{
+                self.0 = value;
+                return Ok(index + 1)
+            }
+        }
+        Err(PackableError { 
+            error_kind: ErrorKind::InvalidEncoding, 
+            data: format!("quantity longer than {} bytes", N),
+            direction: Some(Direction::Unpack),
+        })
+    }
+}
+
+/// Conversions to and from the unsigned integers, so a `Vlq` can stand in for one
+/// through `PackAs`, e.g. as the tag of a derived enum.
+macro_rules! impl_vlq_convert {
+    ( $( $int:ty ),* ) => {
+        $(
+            impl<const N: usize> TryFrom<$int> for Vlq<N>{
+                type Error = PackableError;
+
+                fn try_from(value: $int) -> Result<Self, PackableError> {
+                    Vlq::new(u64::from(value))
+                }
+            }
+
+            impl<const N: usize> TryFrom<Vlq<N>> for $int{
+                type Error = PackableError;
+
+                fn try_from(value: Vlq<N>) -> Result<Self, PackableError> {
+                    value.0.try_into().map_err(|_| PackableError { 
+                        error_kind: ErrorKind::OutOfRange, 
+                        data: format!("{} does not fit in {}", value.0, stringify!($int)),
+                        direction: None,
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_vlq_convert!(u8, u16, u32, u64);
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, to_bytes, from_bytes};
+    use super::Vlq;
+
+    #[test]
+    fn test_vlq_pack(){
+        let cases: [(u64, &[u8]); 7] = [
+            (0, &[0x00]), (0x7f, &[0x7f]), (0x80, &[0x81, 0x00]), (0x2000, &[0xc0, 0x00]),
+            (0x3fff, &[0xff, 0x7f]), (0x4000, &[0x81, 0x80, 0x00]), (0x0fff_ffff, &[0xff, 0xff, 0xff, 0x7f]),
+        ];
+        for (value, data) in cases{
+            let vlq = Vlq::<4>::new(value).unwrap();
+            assert_eq!(data, to_bytes(&vlq, false));
+            assert_eq!(vlq, from_bytes(data, true).unwrap());
+        }
+        assert!(Vlq::<4>::new(0x1000_0000).is_err());
+    }
+
+    #[test]
+    fn test_vlq_unpack(){
+        let (mut delta, mut status) = (Vlq::<4>::default(), 0u8);
+        assert_eq!(3, crate::unpack!(false, &mut vec![0x81, 0x00, 0x90], delta, status).unwrap());
+        assert_eq!((0x80, 0x90), (delta.get(), status));
+
+        let mut slice: &[u8] = &[0xc0, 0x00, 0x42];
+        crate::unpack!(false, &mut slice, delta, status).unwrap();
+        assert_eq!((0x2000, 0x42), (delta.get(), status));
+
+        let error = from_bytes::<Vlq<2>>(&[0x81, 0x80, 0x00], false).unwrap_err();
+        assert_eq!(ErrorKind::InvalidEncoding, error.error_kind);
+        let error = from_bytes::<Vlq<4>>(&[0x80, 0x01], false).unwrap_err();
+        assert_eq!(ErrorKind::InvalidEncoding, error.error_kind);
+        let error = from_bytes::<Vlq<4>>(&[0x81], false).unwrap_err();
+        assert_eq!(ErrorKind::BufferLengthError, error.error_kind);
+    }
+
+    #[test]
+    fn test_vlq_convert(){
+        assert_eq!(0x80, Vlq::<2>::try_from(0x80u16).unwrap().get());
+        assert!(Vlq::<2>::try_from(0x4000u16).is_err());
+        assert_eq!(0x80u8, u8::try_from(Vlq::<2>::new(0x80).unwrap()).unwrap());
+        assert!(u8::try_from(Vlq::<2>::new(0x100).unwrap()).is_err());
+    }
+}